@@ -0,0 +1,139 @@
+use amethyst::Error;
+use rand::Rng;
+use serde_derive::Deserialize;
+
+use std::{f32::consts::PI, fs, path::Path};
+
+use gv_core::{
+    actions::player::{PlayerLookAction, PlayerWalkAction, SpellId},
+    ecs::resources::GameRng,
+    math::Vector2,
+};
+
+const RANDOM_CHANGE_MIN_FRAMES: u64 = 30;
+const RANDOM_CHANGE_MAX_FRAMES: u64 = 120;
+/// A random bot tries to cast a spell once in this number of frames on average.
+const RANDOM_CAST_FRAMES: u32 = 20;
+
+/// The actions a bot wants to perform in the current frame.
+#[derive(Debug, Clone, Default)]
+pub struct BotActions {
+    pub walk_action: PlayerWalkAction,
+    pub look_action: PlayerLookAction,
+    pub cast_spell: Option<SpellId>,
+}
+
+/// A single step of a bot script, which is a RON list of these.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptStep {
+    /// How many frames the step lasts.
+    pub frames: u64,
+    /// A walking direction, a bot stops if it's `None`.
+    pub walk: Option<(f32, f32)>,
+    pub look: (f32, f32),
+    /// Is casted every frame of the step, a server rejects the casts that are cooling down.
+    pub cast: Option<SpellId>,
+}
+
+pub enum BotBehaviour {
+    /// Changes walking and looking directions at random intervals and casts random spells.
+    Random {
+        rng: GameRng,
+        next_change_frame: u64,
+        actions: BotActions,
+    },
+    /// Plays the steps in a loop.
+    Scripted { steps: Vec<ScriptStep> },
+}
+
+impl BotBehaviour {
+    pub fn random(seed: u64) -> Self {
+        BotBehaviour::Random {
+            rng: GameRng::new(seed),
+            next_change_frame: 0,
+            actions: BotActions::default(),
+        }
+    }
+
+    pub fn load_script(path: &Path) -> amethyst::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        let steps: Vec<ScriptStep> = ron::de::from_str(&contents).map_err(|err| {
+            Error::from_string(format!("invalid RON: {} ({})", err, path.display()))
+        })?;
+
+        if steps.iter().all(|step| step.frames == 0) {
+            return Err(Error::from_string(format!(
+                "a script must have at least one step with positive frames ({})",
+                path.display()
+            )));
+        }
+        Ok(BotBehaviour::Scripted { steps })
+    }
+
+    pub fn actions(&mut self, frame_number: u64) -> BotActions {
+        match self {
+            BotBehaviour::Random {
+                rng,
+                next_change_frame,
+                actions,
+            } => {
+                if frame_number >= *next_change_frame {
+                    *next_change_frame = frame_number
+                        + rng.gen_range(RANDOM_CHANGE_MIN_FRAMES, RANDOM_CHANGE_MAX_FRAMES);
+                    actions.walk_action = if rng.gen_bool(0.2) {
+                        PlayerWalkAction::Stop
+                    } else {
+                        PlayerWalkAction::Walk {
+                            direction: random_direction(rng),
+                        }
+                    };
+                    actions.look_action = PlayerLookAction {
+                        direction: random_direction(rng),
+                    };
+                }
+
+                let mut actions = actions.clone();
+                actions.cast_spell = if rng.gen_ratio(1, RANDOM_CAST_FRAMES) {
+                    Some(SpellId::ALL[rng.gen_range(0, SpellId::ALL.len())])
+                } else {
+                    None
+                };
+                actions
+            }
+            BotBehaviour::Scripted { steps } => {
+                let script_frames: u64 = steps.iter().map(|step| step.frames).sum();
+                let mut step_frame = frame_number % script_frames;
+                let step = steps
+                    .iter()
+                    .find(|step| {
+                        if step_frame < step.frames {
+                            true
+                        } else {
+                            step_frame -= step.frames;
+                            false
+                        }
+                    })
+                    .expect("Expected a script step for a frame");
+
+                BotActions {
+                    walk_action: step.walk.map_or(PlayerWalkAction::Stop, |(x, y)| {
+                        PlayerWalkAction::Walk {
+                            direction: Vector2::new(x, y),
+                        }
+                    }),
+                    look_action: PlayerLookAction {
+                        direction: Vector2::new(step.look.0, step.look.1),
+                    },
+                    cast_spell: step.cast,
+                }
+            }
+        }
+    }
+}
+
+fn random_direction(rng: &mut GameRng) -> Vector2 {
+    let angle = rng.gen_range(0.0, 2.0 * PI);
+    Vector2::new(angle.cos(), angle.sin())
+}
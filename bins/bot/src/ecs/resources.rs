@@ -0,0 +1,25 @@
+use std::{net::SocketAddr, time::Duration};
+
+pub const EXIT_CODE_SUCCESS: i32 = 0;
+pub const EXIT_CODE_DISCONNECTED: i32 = 1;
+pub const EXIT_CODE_STALLED: i32 = 2;
+/// A server sent a message that contradicts the state of a bot.
+pub const EXIT_CODE_PROTOCOL_ERROR: i32 = 3;
+
+pub struct BotSettings {
+    pub server_addr: SocketAddr,
+    pub nickname: String,
+    /// A bot gets ready once the room has this many players.
+    pub players_to_start: usize,
+    /// A bot leaves after playing for this long since the first game starts,
+    /// plays until disconnected if `None`.
+    pub duration: Option<Duration>,
+    /// The longest `PauseWaitingForPlayers` stall that isn't considered a failure.
+    pub max_stall: Duration,
+}
+
+#[derive(Default)]
+pub struct BotStatus {
+    /// Is set when a bot wants to quit.
+    pub exit_code: Option<i32>,
+}
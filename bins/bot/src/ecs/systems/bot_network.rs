@@ -0,0 +1,630 @@
+use amethyst::{
+    core::{SystemDesc, Time},
+    ecs::{Read, ReadExpect, ReaderId, System, SystemData, World, Write, WriteExpect},
+    network::simulation::{
+        DeliveryRequirement, NetworkSimulationEvent, TransportResource, UrgencyRequirement,
+    },
+    shrev::EventChannel,
+};
+
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use gv_core::{
+    actions::{
+        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        ClientActionUpdate,
+    },
+    ecs::resources::world::{
+        ImmediatePlayerActionsUpdates, PlayerLookActionUpdates, PAUSE_FRAME_THRESHOLD,
+    },
+    math::Vector2,
+    net::{
+        client_message::{ClientMessage, ClientMessagePayload},
+        fragmentation::{whole_message, FragmentationError, MessageReassembler},
+        server_message::{ServerMessage, ServerMessagePayload},
+        world_update_encoding::WorldUpdatePositionsHistory,
+        NetIdentifier, NetUpdate, BUILD_HASH, INTERPOLATION_FRAME_DELAY, PROTOCOL_VERSION,
+    },
+    progression::PlayerStats,
+    FRAME_RATE,
+};
+
+use crate::{
+    behaviour::BotBehaviour,
+    ecs::resources::{
+        BotSettings, BotStatus, EXIT_CODE_DISCONNECTED, EXIT_CODE_PROTOCOL_ERROR,
+        EXIT_CODE_STALLED, EXIT_CODE_SUCCESS,
+    },
+};
+
+const HEARTBEAT_FRAME_INTERVAL: u64 = 10;
+const BROADCAST_FRAME_INTERVAL: u64 = 5;
+const HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+/// How far from a player bots aim their spells.
+const CAST_DISTANCE: f32 = 300.0;
+
+pub struct BotNetworkSystemDesc {
+    behaviour: BotBehaviour,
+}
+
+impl BotNetworkSystemDesc {
+    pub fn new(behaviour: BotBehaviour) -> Self {
+        Self { behaviour }
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, BotNetworkSystem> for BotNetworkSystemDesc {
+    fn build(self, world: &mut World) -> BotNetworkSystem {
+        <BotNetworkSystem as System<'_>>::SystemData::setup(world);
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+        BotNetworkSystem::new(reader, self.behaviour)
+    }
+}
+
+/// Speaks the same protocol as ClientNetworkSystem and GameUpdatesBroadcastingSystem of a real
+/// client do, but instead of simulating the game it just counts frames and sends actions
+/// of a BotBehaviour. As bots don't simulate the game, they can't detect desyncs, they only
+/// put load on a server and report disconnects and stalls.
+pub struct BotNetworkSystem {
+    reader: ReaderId<NetworkSimulationEvent>,
+    behaviour: BotBehaviour,
+    join_sent_at: Option<Instant>,
+    connection_id: Option<NetIdentifier>,
+    last_heartbeat_frame: u64,
+    /// When the first game started, a server may host several games in a row.
+    playing_since: Option<Instant>,
+    /// Is set to Some when a bot wants to quit, but gives the transport a frame to send
+    /// the last messages.
+    exit_code: Option<i32>,
+    game: Option<BotGame>,
+    message_reassembler: MessageReassembler,
+}
+
+/// Mirrors the client side state of a multiplayer game.
+struct BotGame {
+    player_net_id: NetIdentifier,
+    started_at_engine_frame: u64,
+    frame_number: u64,
+    client_action_id_autoinc: NetIdentifier,
+    last_acknowledged_update_id: u64,
+    last_acknowledged_update_frame: u64,
+    last_acknowledged_positions_id: u64,
+    world_update_positions: WorldUpdatePositionsHistory,
+    waiting_network: bool,
+    waiting_for_players_pause_id: NetIdentifier,
+    lagging_players: Vec<NetIdentifier>,
+    is_paused_by_admin: bool,
+    stalled_at: Option<Instant>,
+    last_broadcasted_frame: u64,
+    walk_action: PlayerWalkAction,
+    look_action: PlayerLookAction,
+    look_actions_updates: Vec<(u64, Vec<NetUpdate<ClientActionUpdate<PlayerLookAction>>>)>,
+    /// The last position a server has reported for a bot's player.
+    position: Vector2,
+}
+
+impl BotGame {
+    fn new(player_net_id: NetIdentifier, started_at_engine_frame: u64) -> Self {
+        Self {
+            player_net_id,
+            started_at_engine_frame,
+            frame_number: 0,
+            client_action_id_autoinc: 0,
+            last_acknowledged_update_id: 0,
+            last_acknowledged_update_frame: 0,
+            last_acknowledged_positions_id: 0,
+            world_update_positions: WorldUpdatePositionsHistory::default(),
+            waiting_network: true,
+            waiting_for_players_pause_id: 0,
+            lagging_players: Vec::new(),
+            is_paused_by_admin: false,
+            stalled_at: None,
+            last_broadcasted_frame: 0,
+            walk_action: PlayerWalkAction::Stop,
+            look_action: PlayerLookAction::default(),
+            look_actions_updates: Vec::new(),
+            position: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    fn next_client_action_id(&mut self) -> NetIdentifier {
+        let id = self.client_action_id_autoinc;
+        self.client_action_id_autoinc = self.client_action_id_autoinc.wrapping_add(1);
+        id
+    }
+
+    fn client_action_update<T>(&mut self, action: T) -> Vec<NetUpdate<ClientActionUpdate<T>>> {
+        vec![NetUpdate {
+            entity_net_id: self.player_net_id,
+            data: ClientActionUpdate {
+                client_action_id: self.next_client_action_id(),
+                action,
+            },
+        }]
+    }
+
+    /// Repeats the logic of ClientNetworkSystem for skipping frames.
+    fn is_waiting(&mut self, engine_frame_number: u64) -> bool {
+        // Clients always skip first INTERPOLATION_FRAME_DELAY frames on game start.
+        if engine_frame_number - self.started_at_engine_frame < INTERPOLATION_FRAME_DELAY {
+            return true;
+        }
+
+        let frames_ahead = self.frame_number.saturating_sub(
+            self.last_acknowledged_update_frame
+                .saturating_sub(INTERPOLATION_FRAME_DELAY),
+        );
+        if self.waiting_network {
+            self.waiting_network = frames_ahead != 0;
+        } else if frames_ahead > PAUSE_FRAME_THRESHOLD {
+            log::debug!(
+                "Waiting for server. Frames ahead: {}. Current frame: {}",
+                frames_ahead,
+                self.frame_number
+            );
+            self.waiting_network = true;
+        }
+
+        let waiting_for_players = (!self.lagging_players.is_empty() || self.is_paused_by_admin)
+            && self.frame_number + INTERPOLATION_FRAME_DELAY >= self.last_acknowledged_update_frame;
+
+        self.waiting_network || waiting_for_players
+    }
+}
+
+impl BotNetworkSystem {
+    fn new(reader: ReaderId<NetworkSimulationEvent>, behaviour: BotBehaviour) -> Self {
+        Self {
+            reader,
+            behaviour,
+            join_sent_at: None,
+            connection_id: None,
+            last_heartbeat_frame: 0,
+            playing_since: None,
+            exit_code: None,
+            game: None,
+            message_reassembler: MessageReassembler::default(),
+        }
+    }
+
+    fn quit(&mut self, exit_code: i32) {
+        if self.exit_code.is_none() {
+            self.exit_code = Some(exit_code);
+        }
+    }
+}
+
+impl<'s> System<'s> for BotNetworkSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        ReadExpect<'s, BotSettings>,
+        WriteExpect<'s, BotStatus>,
+        Write<'s, TransportResource>,
+        Read<'s, EventChannel<NetworkSimulationEvent>>,
+    );
+
+    fn run(&mut self, (time, settings, mut status, mut transport, net_events): Self::SystemData) {
+        if let Some(exit_code) = self.exit_code {
+            status.exit_code = Some(exit_code);
+            return;
+        }
+        let engine_frame_number = time.frame_number();
+        let server_addr = settings.server_addr;
+
+        let join_sent_at = *self.join_sent_at.get_or_insert_with(|| {
+            log::info!("Sending a JoinRoom message to {}", server_addr);
+            send_message(
+                &mut transport,
+                server_addr,
+                ClientMessagePayload::JoinRoom {
+                    build_hash: BUILD_HASH.to_owned(),
+                    sent_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .expect("Expected a duration unix timestamp"),
+                    nickname: settings.nickname.clone(),
+                    stats: PlayerStats::default(),
+                    reconnect_token: None,
+                    is_spectator: false,
+                },
+                DeliveryRequirement::Reliable,
+            );
+            Instant::now()
+        });
+        if self.connection_id.is_none()
+            && join_sent_at.elapsed() > Duration::from_secs(HANDSHAKE_TIMEOUT_SECS)
+        {
+            log::error!("Haven't received a Handshake from {}", server_addr);
+            self.quit(EXIT_CODE_DISCONNECTED);
+            return;
+        }
+
+        for net_event in net_events.read(&mut self.reader) {
+            match net_event {
+                NetworkSimulationEvent::Message(addr, payload) if *addr == server_addr => {
+                    let message = match self.message_reassembler.receive(payload) {
+                        Ok(Some(message)) => message,
+                        Ok(None) => continue,
+                        Err(FragmentationError::IncompatibleVersion { protocol_version }) => {
+                            log::error!(
+                                "The server runs protocol version {}, expected {}",
+                                protocol_version,
+                                PROTOCOL_VERSION
+                            );
+                            self.quit(EXIT_CODE_DISCONNECTED);
+                            continue;
+                        }
+                        Err(err) => {
+                            log::warn!("Dropping a datagram from the server: {}", err);
+                            continue;
+                        }
+                    };
+                    match bincode::deserialize::<ServerMessage>(&message) {
+                        Ok(message) => self.process_message(
+                            &settings,
+                            &mut transport,
+                            engine_frame_number,
+                            message.payload,
+                        ),
+                        Err(err) => log::warn!("Failed to decode a server message: {:?}", err),
+                    }
+                }
+                NetworkSimulationEvent::Disconnect(addr) if *addr == server_addr => {
+                    log::error!("Lost the connection to {}", server_addr);
+                    self.quit(EXIT_CODE_DISCONNECTED);
+                }
+                _ => {}
+            }
+        }
+        if self.exit_code.is_some() {
+            return;
+        }
+
+        if engine_frame_number - self.last_heartbeat_frame > HEARTBEAT_FRAME_INTERVAL {
+            self.last_heartbeat_frame = engine_frame_number;
+            send_message(
+                &mut transport,
+                server_addr,
+                ClientMessagePayload::Heartbeat,
+                DeliveryRequirement::Reliable,
+            );
+        }
+
+        if let (Some(duration), Some(playing_since)) = (settings.duration, self.playing_since) {
+            if playing_since.elapsed() > duration {
+                log::info!("Leaving after playing for {:?}", duration);
+                send_message(
+                    &mut transport,
+                    server_addr,
+                    ClientMessagePayload::Disconnect,
+                    DeliveryRequirement::Reliable,
+                );
+                self.quit(EXIT_CODE_SUCCESS);
+                return;
+            }
+        }
+
+        let game = if let Some(game) = &mut self.game {
+            game
+        } else {
+            return;
+        };
+
+        if let Some(stalled_at) = game.stalled_at {
+            if stalled_at.elapsed() > settings.max_stall {
+                log::error!(
+                    "The server has been waiting for players {:?} for {:?} (frame {})",
+                    game.lagging_players,
+                    stalled_at.elapsed(),
+                    game.frame_number
+                );
+                self.quit(EXIT_CODE_STALLED);
+                return;
+            }
+        }
+
+        if game.is_waiting(engine_frame_number) {
+            return;
+        }
+
+        let frame_number = game.frame_number + INTERPOLATION_FRAME_DELAY;
+        let actions = self.behaviour.actions(game.frame_number);
+
+        if actions.walk_action != game.walk_action {
+            game.walk_action = actions.walk_action.clone();
+            let updates = game.client_action_update(actions.walk_action);
+            send_message(
+                &mut transport,
+                server_addr,
+                ClientMessagePayload::WalkActions(ImmediatePlayerActionsUpdates {
+                    frame_number,
+                    updates,
+                }),
+                DeliveryRequirement::Reliable,
+            );
+        }
+
+        if let Some(spell) = actions.cast_spell {
+            let cast_action = PlayerCastAction {
+                spell,
+                cast_position: game.position,
+                target_position: game.position + actions.look_action.direction * CAST_DISTANCE,
+            };
+            let updates = game.client_action_update(cast_action);
+            send_message(
+                &mut transport,
+                server_addr,
+                ClientMessagePayload::CastActions(ImmediatePlayerActionsUpdates {
+                    frame_number,
+                    updates,
+                }),
+                DeliveryRequirement::Reliable,
+            );
+        }
+
+        if actions.look_action != game.look_action {
+            game.look_action = actions.look_action.clone();
+            let updates = game.client_action_update(actions.look_action);
+            game.look_actions_updates.push((frame_number, updates));
+        }
+        if game.frame_number.wrapping_sub(game.last_broadcasted_frame) > BROADCAST_FRAME_INTERVAL {
+            game.last_broadcasted_frame = game.frame_number;
+            send_message(
+                &mut transport,
+                server_addr,
+                ClientMessagePayload::LookActions(PlayerLookActionUpdates {
+                    updates: game.look_actions_updates.drain(..).collect(),
+                }),
+                DeliveryRequirement::Reliable,
+            );
+        }
+
+        game.frame_number += 1;
+    }
+}
+
+impl BotNetworkSystem {
+    fn process_message(
+        &mut self,
+        settings: &BotSettings,
+        transport: &mut TransportResource,
+        engine_frame_number: u64,
+        payload: ServerMessagePayload,
+    ) {
+        let server_addr = settings.server_addr;
+        match payload {
+            ServerMessagePayload::Heartbeat | ServerMessagePayload::Pong { .. } => {}
+            ServerMessagePayload::Ping(ping_id) => send_message(
+                transport,
+                server_addr,
+                ClientMessagePayload::Pong {
+                    ping_id,
+                    frame_number: self.game.as_ref().map_or(0, |game| game.frame_number),
+                },
+                DeliveryRequirement::Unreliable,
+            ),
+            ServerMessagePayload::Handshake {
+                net_id,
+                is_host,
+                tick_rate,
+                ..
+            } => {
+                if tick_rate != FRAME_RATE {
+                    log::error!(
+                        "The server runs at {} frames per second, expected {}",
+                        tick_rate,
+                        FRAME_RATE
+                    );
+                    self.quit(EXIT_CODE_PROTOCOL_ERROR);
+                    return;
+                }
+                log::info!(
+                    "Received Handshake from a server ({}), is_host: {}",
+                    net_id,
+                    is_host
+                );
+                self.connection_id = Some(net_id);
+            }
+            ServerMessagePayload::UpdateRoomPlayers(players) => {
+                log::info!("Updated room players (player count: {})", players.len());
+                let is_ready = players
+                    .iter()
+                    .find(|player| Some(player.connection_id) == self.connection_id)
+                    .map_or(true, |player| player.is_ready);
+                if !is_ready && players.len() >= settings.players_to_start {
+                    log::info!("Getting ready");
+                    send_message(
+                        transport,
+                        server_addr,
+                        ClientMessagePayload::SetReady(true),
+                        DeliveryRequirement::Reliable,
+                    );
+                }
+            }
+            ServerMessagePayload::UpdateLobbyCountdown(secs_left) => {
+                if let Some(secs_left) = secs_left {
+                    log::info!("The game starts in {}s", secs_left);
+                } else {
+                    log::info!("The lobby countdown is cancelled");
+                }
+            }
+            ServerMessagePayload::StartGame { seed, players, .. } => {
+                if self.game.is_some() {
+                    return;
+                }
+                let player_net_id = self.connection_id.and_then(|connection_id| {
+                    players
+                        .iter()
+                        .find(|(_, player)| player.connection_id == connection_id)
+                        .map(|(entity_net_id, _)| *entity_net_id)
+                });
+                let player_net_id = if let Some(player_net_id) = player_net_id {
+                    player_net_id
+                } else {
+                    log::error!(
+                        "Received StartGame without a player with connection id {:?}",
+                        self.connection_id
+                    );
+                    self.quit(EXIT_CODE_PROTOCOL_ERROR);
+                    return;
+                };
+                log::info!(
+                    "Starting a new game (seed {}) as a player with net id {}",
+                    seed,
+                    player_net_id
+                );
+                self.playing_since.get_or_insert_with(Instant::now);
+                self.game = Some(BotGame::new(player_net_id, engine_frame_number));
+            }
+            // Bots never send reconnect tokens.
+            ServerMessagePayload::ResumeGame { .. }
+            | ServerMessagePayload::WorldSnapshot { .. } => {
+                log::warn!("Ignoring an unexpected message for rejoining players");
+            }
+            ServerMessagePayload::EndGame => {
+                if let Some(game) = self.game.take() {
+                    log::info!(
+                        "The game is over after {} frames, returning to the room",
+                        game.frame_number
+                    );
+                }
+            }
+            ServerMessagePayload::UpdateWorld {
+                id,
+                base_id,
+                positions_id,
+                positions_base_id,
+                updates,
+            } => {
+                let game = if let Some(game) = &mut self.game {
+                    game
+                } else {
+                    return;
+                };
+                let is_next_update = base_id <= game.last_acknowledged_update_id
+                    && game.last_acknowledged_update_id < id;
+                let decoded_updates = match game.world_update_positions.get(positions_base_id) {
+                    Some(positions) if is_next_update => Some(updates.decode(positions)),
+                    _ => None,
+                };
+                if let Some(decoded_updates) = decoded_updates {
+                    match decoded_updates {
+                        Ok((updates, positions)) => {
+                            game.last_acknowledged_update_id = id;
+                            game.last_acknowledged_positions_id = positions_id;
+                            game.world_update_positions
+                                .forget_older_than(positions_base_id);
+                            game.world_update_positions.insert(positions_id, positions);
+                            for update in updates {
+                                game.last_acknowledged_update_frame =
+                                    game.last_acknowledged_update_frame.max(update.frame_number);
+                                let player_net_id = game.player_net_id;
+                                if let Some(walk_update) = update
+                                    .player_walk_actions_updates
+                                    .iter()
+                                    .find(|walk_update| walk_update.entity_net_id == player_net_id)
+                                {
+                                    game.position = *walk_update.position;
+                                }
+                            }
+                        }
+                        Err(err) => log::error!("Failed to decode world updates: {:?}", err),
+                    }
+                }
+
+                send_message(
+                    transport,
+                    server_addr,
+                    ClientMessagePayload::AcknowledgeWorldUpdate {
+                        id: game.last_acknowledged_update_id,
+                        positions_id: game.last_acknowledged_positions_id,
+                    },
+                    DeliveryRequirement::Unreliable,
+                );
+            }
+            ServerMessagePayload::DiscardWalkActions(discarded_actions) => {
+                log::warn!("The server discarded walk actions {:?}", discarded_actions);
+            }
+            ServerMessagePayload::ReportPlayersNetStatus { players, .. } => {
+                for player in players {
+                    log::debug!(
+                        "Player {}: frame {}, average lagging behind {}, latency {}ms",
+                        player.connection_id,
+                        player.frame_number,
+                        player.average_lagging_behind,
+                        player.latency_ms
+                    );
+                }
+            }
+            ServerMessagePayload::PauseWaitingForPlayers { id, players } => {
+                if let Some(game) = &mut self.game {
+                    if game.waiting_for_players_pause_id < id {
+                        game.waiting_for_players_pause_id = id;
+                        // Pauses by an admin aren't stalls.
+                        game.is_paused_by_admin = players.is_empty();
+                        if game.is_paused_by_admin {
+                            log::info!(
+                                "The game is paused by an admin (frame {})",
+                                game.frame_number
+                            );
+                        } else {
+                            log::warn!(
+                                "The server is waiting for players {:?} (frame {})",
+                                players,
+                                game.frame_number
+                            );
+                            game.stalled_at.get_or_insert_with(Instant::now);
+                        }
+                        game.lagging_players = players;
+                    }
+                }
+            }
+            ServerMessagePayload::UnpauseWaitingForPlayers(id) => {
+                if let Some(game) = &mut self.game {
+                    if game.waiting_for_players_pause_id <= id {
+                        if let Some(stalled_at) = game.stalled_at.take() {
+                            log::warn!(
+                                "The server has resumed the game after {:?} (frame {})",
+                                stalled_at.elapsed(),
+                                game.frame_number
+                            );
+                        }
+                        game.waiting_for_players_pause_id = id;
+                        game.lagging_players.clear();
+                        game.is_paused_by_admin = false;
+                    }
+                }
+            }
+            ServerMessagePayload::Disconnect(disconnect_reason) => {
+                log::error!("Received a Disconnect message: {:?}", disconnect_reason);
+                self.quit(EXIT_CODE_DISCONNECTED);
+            }
+        }
+    }
+}
+
+fn send_message(
+    transport: &mut TransportResource,
+    addr: SocketAddr,
+    payload: ClientMessagePayload,
+    delivery: DeliveryRequirement,
+) {
+    let message = ClientMessage {
+        session_id: 0,
+        payload,
+    };
+    log::trace!("Sending: {:#?}", message);
+    let sent_message =
+        bincode::serialize(&message).expect("Expected to serialize a client message");
+    // Messages of bots are always small enough to not need fragmenting.
+    transport.send_with_requirements(
+        addr,
+        &whole_message(&sent_message),
+        delivery,
+        UrgencyRequirement::Immediate,
+    );
+}
@@ -0,0 +1,3 @@
+mod bot_network;
+
+pub use self::bot_network::{BotNetworkSystem, BotNetworkSystemDesc};
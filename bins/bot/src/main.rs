@@ -0,0 +1,200 @@
+#![allow(clippy::type_complexity)]
+
+mod behaviour;
+mod ecs;
+mod state;
+
+use amethyst::{
+    core::frame_limiter::FrameRateLimitStrategy,
+    network::simulation::laminar::{LaminarConfig, LaminarNetworkBundle, LaminarSocket},
+    prelude::{Application, GameDataBuilder, SystemDesc},
+    Logger, LoggerConfig,
+};
+
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use gv_core::{net::CLIENT_RECEIVE_BUFFER_MAX_SIZE, FRAME_RATE};
+
+use crate::{
+    behaviour::BotBehaviour,
+    ecs::{
+        resources::{BotSettings, BotStatus},
+        systems::BotNetworkSystemDesc,
+    },
+    state::BotState,
+};
+
+fn main() -> amethyst::Result<()> {
+    let cli_matches = clap::App::new("grumpy_visitors_bot")
+        .version("0.1")
+        .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
+        .about("A headless client that plays Grumpy Visitors on a server for soak testing")
+        .arg(
+            clap::Arg::with_name("server-addr")
+                .short("s")
+                .long("server-addr")
+                .value_name("SERVER_ADDR")
+                .help("Specifies the address of a server to join")
+                .default_value("127.0.0.1:3455")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("nickname")
+                .short("n")
+                .long("nickname")
+                .value_name("NICKNAME")
+                .default_value("bot")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("players")
+                .short("p")
+                .long("players")
+                .value_name("PLAYERS")
+                .help("A bot gets ready once this many players join")
+                .default_value("4")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("script")
+                .long("script")
+                .value_name("FILE")
+                .help("Plays the actions from a RON script instead of random ones")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seeds random actions, defaults to a random seed")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("duration-secs")
+                .short("d")
+                .long("duration-secs")
+                .value_name("SECS")
+                .help("Leaves after playing for this long since the first game starts")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("max-stall-secs")
+                .long("max-stall-secs")
+                .value_name("SECS")
+                .help("Fails if a server waits for lagging players for longer than this")
+                .default_value("10")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let server_addr = cli_matches
+        .value_of("server-addr")
+        .expect("Expected a default value if not passed via CLI")
+        .parse()?;
+    let nickname = cli_matches
+        .value_of("nickname")
+        .expect("Expected a default value if not passed via CLI")
+        .to_owned();
+    let players_to_start = cli_matches
+        .value_of("players")
+        .expect("Expected a default value if not passed via CLI")
+        .parse()?;
+    let max_stall = Duration::from_secs(
+        cli_matches
+            .value_of("max-stall-secs")
+            .expect("Expected a default value if not passed via CLI")
+            .parse()?,
+    );
+    let duration = cli_matches
+        .value_of("duration-secs")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()?;
+    let seed = cli_matches
+        .value_of("seed")
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or_else(rand::random);
+
+    let logging_config: LoggerConfig = ::std::fs::read_to_string("bot_logging_config.toml")
+        .map_err(|err| {
+            log::warn!(
+                "Failed to read bot_logging_config.toml, using the defaults: {:?}",
+                err
+            )
+        })
+        .and_then(|config_contents| {
+            toml::from_str(&config_contents).map_err(|err| {
+                log::warn!(
+                    "Failed to read bot_logging_config.toml, using the defaults: {:?}",
+                    err
+                )
+            })
+        })
+        .unwrap_or_default();
+    let log_nickname = nickname.clone();
+    Logger::from_config_formatter(logging_config, move |out, message, record| {
+        out.finish(format_args!(
+            "[{level}][BOT {nickname}][{target}] {message}",
+            level = record.level(),
+            nickname = log_nickname,
+            target = record.target(),
+            message = message,
+        ))
+    })
+    .start();
+
+    let behaviour = if let Some(script_path) = cli_matches.value_of("script") {
+        BotBehaviour::load_script(Path::new(script_path))?
+    } else {
+        log::info!("Playing random actions with seed {}", seed);
+        BotBehaviour::random(seed)
+    };
+
+    let exit_code = Arc::new(AtomicI32::new(0));
+    let mut builder = Application::build(
+        "./",
+        BotState {
+            exit_code: exit_code.clone(),
+        },
+    )?;
+    builder.world.insert(BotSettings {
+        server_addr,
+        nickname,
+        players_to_start,
+        duration,
+        max_stall,
+    });
+    builder.world.insert(BotStatus::default());
+
+    let laminar_config = LaminarConfig {
+        receive_buffer_max_size: CLIENT_RECEIVE_BUFFER_MAX_SIZE,
+        ..LaminarConfig::default()
+    };
+    let socket = LaminarSocket::bind_with_config("0.0.0.0:0", laminar_config)?;
+
+    let game_data_builder = GameDataBuilder::default()
+        .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+        .with(
+            BotNetworkSystemDesc::new(behaviour).build(&mut builder.world),
+            "bot_network_system",
+            &[],
+        );
+
+    let mut game = builder
+        .with_frame_limit(FrameRateLimitStrategy::Yield, FRAME_RATE)
+        .build(game_data_builder)?;
+    game.run();
+
+    let exit_code = exit_code.load(Ordering::SeqCst);
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
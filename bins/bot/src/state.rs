@@ -0,0 +1,23 @@
+use amethyst::prelude::{GameData, SimpleState, SimpleTrans, StateData, Trans};
+
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+};
+
+use crate::ecs::resources::BotStatus;
+
+pub struct BotState {
+    /// Is read by `main` after the application quits.
+    pub exit_code: Arc<AtomicI32>,
+}
+
+impl SimpleState for BotState {
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(exit_code) = data.world.fetch::<BotStatus>().exit_code {
+            self.exit_code.store(exit_code, Ordering::SeqCst);
+            return Trans::Quit;
+        }
+        Trans::None
+    }
+}
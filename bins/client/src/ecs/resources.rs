@@ -6,6 +6,8 @@ use std::{
     process::{Child, Command, ExitStatus},
 };
 
+use gv_core::net::world_update_encoding::WorldUpdatePositionsHistory;
+
 #[derive(Default)]
 pub struct DisplayDebugInfoSettings {
     pub display_health: bool,
@@ -15,6 +17,9 @@ pub struct DisplayDebugInfoSettings {
 pub struct LastAcknowledgedUpdate {
     pub id: u64,
     pub frame_number: u64,
+    pub positions_id: u64,
+    /// Positions that the next world updates may be encoded with.
+    pub positions: WorldUpdatePositionsHistory,
 }
 
 #[derive(Default)]
@@ -26,6 +31,7 @@ pub enum UiNetworkCommand {
     Host {
         nickname: String,
         server_addr: SocketAddr,
+        is_spectator: bool,
     },
     Connect {
         nickname: String,
@@ -34,7 +40,7 @@ pub enum UiNetworkCommand {
     Kick {
         player_number: usize,
     },
-    Start,
+    ToggleReady,
     Leave,
     Reset,
 }
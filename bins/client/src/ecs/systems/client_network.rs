@@ -1,6 +1,6 @@
 use amethyst::{
     ecs::{Entities, Join, ReadExpect, System, World, Write, WriteExpect, WriteStorage},
-    network::simulation::{laminar::LaminarSocketResource, TransportResource},
+    network::simulation::TransportResource,
     shred::{ResourceId, SystemData},
 };
 
@@ -10,7 +10,10 @@ use std::{
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use gv_client_shared::ecs::resources::{ConnectionStatus, MultiplayerRoomState};
+use gv_client_shared::{
+    ecs::resources::{ConnectionStatus, MultiplayerRoomState},
+    profile::{Profile, ReconnectToken},
+};
 use gv_core::{
     actions::monster_spawn::SpawnActions,
     ecs::{
@@ -19,7 +22,8 @@ use gv_core::{
             net::{MultiplayerGameState, MultiplayerRoomPlayer, PlayersNetStatus},
             world::{
                 FramedUpdates, PlayerActionUpdates, ReceivedPlayerUpdate,
-                ReceivedServerWorldUpdate, ServerWorldUpdate, PAUSE_FRAME_THRESHOLD,
+                ReceivedServerWorldUpdate, ServerWorldUpdate, WorldSnapshot, WorldSnapshotChunks,
+                PAUSE_FRAME_THRESHOLD,
             },
             GameEngineState, NewGameEngineState,
         },
@@ -28,12 +32,14 @@ use gv_core::{
     net::{
         client_message::ClientMessagePayload,
         server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
-        NetEvent, NetIdentifier, INTERPOLATION_FRAME_DELAY,
+        world_update_encoding::WorldUpdatePositionsHistory,
+        NetEvent, NetIdentifier, BUILD_HASH, INTERPOLATION_FRAME_DELAY,
     },
+    FRAME_RATE,
 };
 use gv_game::{
-    ecs::resources::ConnectionEvents,
-    utils::net::{send_message_reliable, send_message_unreliable},
+    ecs::resources::{ConnectionEvents, GameMode},
+    utils::net::{send_message_reliable, send_message_unreliable, transport::LocalTransportAddr},
 };
 
 use crate::ecs::resources::{
@@ -51,6 +57,7 @@ pub struct ClientNetworkSystemData<'s> {
     multiplayer_room_state: WriteExpect<'s, MultiplayerRoomState>,
     multiplayer_game_state: WriteExpect<'s, MultiplayerGameState>,
     new_game_engine_sate: WriteExpect<'s, NewGameEngineState>,
+    game_mode: WriteExpect<'s, GameMode>,
     last_acknowledged_update: WriteExpect<'s, LastAcknowledgedUpdate>,
     framed_updates: WriteExpect<'s, FramedUpdates<ReceivedServerWorldUpdate>>,
     player_actions_updates: WriteExpect<'s, FramedUpdates<PlayerActionUpdates>>,
@@ -58,9 +65,10 @@ pub struct ClientNetworkSystemData<'s> {
     server_command: WriteExpect<'s, ServerCommand>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
     players_net_status: WriteExpect<'s, PlayersNetStatus>,
+    profile: WriteExpect<'s, Profile>,
     net_connection_models: WriteStorage<'s, NetConnectionModel>,
     transport: Write<'s, TransportResource>,
-    laminar_socket: WriteExpect<'s, LaminarSocketResource>,
+    local_transport_addr: ReadExpect<'s, LocalTransportAddr>,
 }
 
 #[derive(Default)]
@@ -69,6 +77,13 @@ pub struct ClientNetworkSystem {
     last_heartbeat_frame: u64,
     has_sent_join_message: bool,
     nickname: String,
+    resumed_game: Option<(
+        u64,
+        Vec<(NetIdentifier, MultiplayerRoomPlayer)>,
+        Option<usize>,
+    )>,
+    world_snapshot_chunks: WorldSnapshotChunks,
+    world_snapshot: Option<WorldSnapshot>,
 }
 
 impl ClientNetworkSystem {
@@ -91,15 +106,11 @@ impl ClientNetworkSystem {
                 self.nickname = nickname;
                 system_data.multiplayer_room_state.is_active = true;
                 system_data.multiplayer_room_state.is_host = true;
+                system_data.multiplayer_room_state.is_spectator = false;
                 system_data.multiplayer_room_state.connection_status =
                     ConnectionStatus::Connecting(Instant::now());
 
-                let mut host_client_addr = system_data
-                    .laminar_socket
-                    .get_mut()
-                    .expect("Expected a LaminarSocket")
-                    .local_addr()
-                    .expect("Expected a local address for a Laminar socket");
+                let mut host_client_addr = system_data.local_transport_addr.0;
                 match &mut host_client_addr {
                     SocketAddr::V4(addr) => addr.set_ip(Ipv4Addr::new(127, 0, 0, 1)),
                     SocketAddr::V6(addr) => addr.set_ip(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
@@ -117,10 +128,12 @@ impl ClientNetworkSystem {
             UiNetworkCommand::Connect {
                 nickname,
                 server_addr,
+                is_spectator,
             } => {
                 self.nickname = nickname;
                 system_data.multiplayer_room_state.is_active = true;
                 system_data.multiplayer_room_state.is_host = false;
+                system_data.multiplayer_room_state.is_spectator = is_spectator;
                 system_data.multiplayer_room_state.connection_status =
                     ConnectionStatus::Connecting(Instant::now());
 
@@ -133,10 +146,18 @@ impl ClientNetworkSystem {
                     &mut system_data.transport,
                     &net_connection_model,
                     ClientMessagePayload::JoinRoom {
+                        build_hash: BUILD_HASH.to_owned(),
                         sent_at: SystemTime::now()
                             .duration_since(UNIX_EPOCH)
                             .expect("Expected a duration unix timestamp"),
                         nickname: self.nickname.clone(),
+                        stats: system_data.profile.stats,
+                        reconnect_token: if is_spectator {
+                            None
+                        } else {
+                            system_data.profile.reconnect_token(server_addr)
+                        },
+                        is_spectator,
                     },
                 );
 
@@ -156,17 +177,25 @@ impl ClientNetworkSystem {
                 },
             ),
 
-            UiNetworkCommand::Start => {
-                if system_data.multiplayer_room_state.is_host {
+            UiNetworkCommand::ToggleReady => {
+                let connection_id = system_data
+                    .multiplayer_room_state
+                    .connection_status
+                    .connection_id();
+                let player = system_data
+                    .multiplayer_game_state
+                    .players
+                    .iter()
+                    .find(|player| Some(player.connection_id) == connection_id);
+                if let Some(player) = player {
+                    let is_ready = !player.is_ready;
                     send_message_reliable(
                         &mut system_data.transport,
                         server_connection(&mut system_data.net_connection_models),
-                        ClientMessagePayload::StartHostedGame,
+                        ClientMessagePayload::SetReady(is_ready),
                     );
                 } else {
-                    log::error!(
-                        "Client check failed: only host can send a StartHostedGame message"
-                    );
+                    log::error!("Client check failed: couldn't find ourselves in the room");
                 }
             }
 
@@ -191,6 +220,9 @@ impl ClientNetworkSystem {
             UiNetworkCommand::Reset => {
                 self.has_sent_join_message = false;
                 self.last_heartbeat_frame = 0;
+                self.resumed_game = None;
+                self.world_snapshot_chunks.reset();
+                self.world_snapshot = None;
                 system_data.multiplayer_room_state.connection_status =
                     ConnectionStatus::NotConnected;
                 system_data.multiplayer_game_state.reset();
@@ -208,6 +240,54 @@ fn update_room_players(
     *multiplayer_game_state.update_players() = players;
 }
 
+fn start_game(
+    multiplayer_room_state: &mut MultiplayerRoomState,
+    multiplayer_game_state: &mut MultiplayerGameState,
+    new_game_engine_state: &mut NewGameEngineState,
+    seed: u64,
+    net_ids_and_players: Vec<(NetIdentifier, MultiplayerRoomPlayer)>,
+) {
+    let (entity_net_ids, players): (Vec<NetIdentifier>, Vec<MultiplayerRoomPlayer>) =
+        net_ids_and_players.into_iter().unzip();
+
+    update_room_players(multiplayer_game_state, players);
+
+    let connection_id = multiplayer_room_state
+        .connection_status
+        .connection_id()
+        .expect("Expected to be connected when starting a game");
+
+    let mut found_ourselves = false;
+    // Looking for an entity_net_id of a client's player
+    // and storing it in the MultiplayerRoomState.
+    for (i, player) in multiplayer_game_state
+        .update_players()
+        .iter_mut()
+        .enumerate()
+    {
+        player.entity_net_id = entity_net_ids[i];
+        if connection_id == player.connection_id {
+            log::info!(
+                "Starting a new game as a player with net id {}",
+                player.entity_net_id
+            );
+            found_ourselves = true;
+            multiplayer_room_state.player_net_id = player.entity_net_id;
+        }
+    }
+    if multiplayer_room_state.is_spectator {
+        log::info!("Starting a new game as a spectator");
+    } else if !found_ourselves {
+        panic!(
+            "Couldn't found a player with connection id {}",
+            connection_id
+        );
+    }
+    multiplayer_game_state.game_seed = seed;
+    multiplayer_game_state.is_playing = true;
+    new_game_engine_state.0 = GameEngineState::Playing;
+}
+
 impl<'s> System<'s> for ClientNetworkSystem {
     type SystemData = ClientNetworkSystemData<'s>;
 
@@ -277,7 +357,9 @@ impl<'s> System<'s> for ClientNetworkSystem {
                     }) => match payload {
                         ServerMessagePayload::Handshake { .. } => true,
                         ServerMessagePayload::UpdateRoomPlayers(_) => true,
-                        ServerMessagePayload::StartGame(_) => true,
+                        ServerMessagePayload::StartGame { .. } => true,
+                        ServerMessagePayload::ResumeGame { .. } => true,
+                        ServerMessagePayload::WorldSnapshot { .. } => true,
                         _ => false,
                     },
                     _ => false,
@@ -299,9 +381,41 @@ impl<'s> System<'s> for ClientNetworkSystem {
                         | ServerMessagePayload::Pong { .. } => {}
 
                         ServerMessagePayload::Handshake {
+                            build_hash,
                             net_id: connection_id,
                             is_host,
+                            reconnect_token,
+                            max_players,
+                            tick_rate,
                         } => {
+                            // Servers of other protocol versions are caught by
+                            // NetConnectionManager.
+                            if build_hash != BUILD_HASH {
+                                log::warn!(
+                                    "The server runs a different build ({}) of the same protocol",
+                                    build_hash
+                                );
+                            }
+                            if tick_rate != FRAME_RATE {
+                                log::error!(
+                                    "The server runs at {} frames per second, expected {}",
+                                    tick_rate,
+                                    FRAME_RATE
+                                );
+                                send_message_reliable(
+                                    &mut system_data.transport,
+                                    net_connection_model,
+                                    ClientMessagePayload::Disconnect,
+                                );
+                                net_connection_model.disconnected = true;
+                                system_data.multiplayer_room_state.connection_status =
+                                    ConnectionStatus::Disconnected(
+                                        DisconnectReason::IncompatibleTickRate {
+                                            server_tick_rate: tick_rate,
+                                        },
+                                    );
+                                continue;
+                            }
                             log::info!(
                                 "Received Handshake from a server ({}), is_host: {}",
                                 connection_id,
@@ -316,79 +430,136 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                     &mut system_data.transport,
                                     net_connection_model,
                                     ClientMessagePayload::JoinRoom {
+                                        build_hash: BUILD_HASH.to_owned(),
                                         sent_at: SystemTime::now()
                                             .duration_since(UNIX_EPOCH)
                                             .expect("Expected a duration unix timestamp"),
                                         nickname: self.nickname.clone(),
+                                        stats: system_data.profile.stats,
+                                        reconnect_token: None,
+                                        is_spectator: false,
                                     },
                                 );
                             }
 
+                            // A hosting client can't rejoin, as the server stops with it.
+                            // Spectators can just join again.
+                            if !is_host && !system_data.multiplayer_room_state.is_spectator {
+                                system_data.profile.reconnect_token = Some(ReconnectToken {
+                                    server_addr: net_connection_model.addr,
+                                    token: reconnect_token,
+                                });
+                                if let Err(err) = system_data.profile.save() {
+                                    log::error!("Failed to save the profile: {}", err);
+                                }
+                            }
+
                             system_data.multiplayer_room_state.connection_status =
                                 ConnectionStatus::Connected(connection_id);
                             system_data.multiplayer_room_state.is_host = is_host;
+                            system_data.multiplayer_room_state.max_players = max_players as usize;
                         }
                         ServerMessagePayload::UpdateRoomPlayers(players) => {
                             update_room_players(&mut system_data.multiplayer_game_state, players);
                         }
-                        ServerMessagePayload::StartGame(net_ids_and_players) => {
+                        ServerMessagePayload::UpdateLobbyCountdown(secs_left) => {
+                            system_data.multiplayer_room_state.lobby_countdown = secs_left;
+                        }
+                        ServerMessagePayload::StartGame {
+                            seed,
+                            players: net_ids_and_players,
+                            campaign_level,
+                        } => {
                             system_data.last_acknowledged_update.frame_number = 0;
                             system_data.last_acknowledged_update.id = 0;
-
-                            let (entity_net_ids, players): (
-                                Vec<NetIdentifier>,
-                                Vec<MultiplayerRoomPlayer>,
-                            ) = net_ids_and_players.into_iter().unzip();
-
-                            update_room_players(&mut system_data.multiplayer_game_state, players);
-
-                            let connection_id = system_data
-                                .multiplayer_room_state
-                                .connection_status
-                                .connection_id()
-                                .expect(
-                                    "Expected to be connected when receiving StartGame message",
-                                );
-
-                            let mut found_ourselves = false;
-                            // Looking for an entity_net_id of a client's player
-                            // and storing it in the MultiplayerRoomState.
-                            for (i, player) in system_data
-                                .multiplayer_game_state
-                                .update_players()
-                                .iter_mut()
-                                .enumerate()
-                            {
-                                player.entity_net_id = entity_net_ids[i];
-                                if connection_id == player.connection_id {
-                                    log::info!(
-                                        "Starting a new game as a player with net id {}",
-                                        player.entity_net_id
-                                    );
-                                    found_ourselves = true;
-                                    system_data.multiplayer_room_state.player_net_id =
-                                        player.entity_net_id;
-                                }
-                            }
-                            if !found_ourselves {
-                                panic!(
-                                    "Couldn't found a player with connection id {}",
-                                    connection_id
-                                );
-                            }
-                            system_data.multiplayer_game_state.is_playing = true;
-                            system_data.new_game_engine_sate.0 = GameEngineState::Playing;
+                            system_data.last_acknowledged_update.positions_id = 0;
+                            system_data.last_acknowledged_update.positions =
+                                WorldUpdatePositionsHistory::default();
+                            system_data.multiplayer_room_state.lobby_countdown = None;
+                            // Frame numbers start from 0 again if a server hosts several games.
+                            *system_data.framed_updates = FramedUpdates::default();
+                            *system_data.game_mode = GameMode::from_campaign_level(campaign_level);
+
+                            start_game(
+                                &mut system_data.multiplayer_room_state,
+                                &mut system_data.multiplayer_game_state,
+                                &mut system_data.new_game_engine_sate,
+                                seed,
+                                net_ids_and_players,
+                            );
                         }
-                        ServerMessagePayload::UpdateWorld { id, mut updates } => {
-                            send_message_unreliable(
-                                &mut system_data.transport,
-                                net_connection_model,
-                                ClientMessagePayload::AcknowledgeWorldUpdate(id),
+                        ServerMessagePayload::ResumeGame {
+                            seed,
+                            players: net_ids_and_players,
+                            campaign_level,
+                        } => {
+                            self.resumed_game = Some((seed, net_ids_and_players, campaign_level));
+                        }
+                        ServerMessagePayload::WorldSnapshot {
+                            chunk_index,
+                            chunks_count,
+                            chunk,
+                        } => {
+                            log::debug!(
+                                "Received a WorldSnapshot chunk ({} of {})",
+                                chunk_index + 1,
+                                chunks_count
                             );
-
-                            if system_data.last_acknowledged_update.id < id {
+                            let world_snapshot = self.world_snapshot_chunks.add_chunk(
+                                chunk_index as usize,
+                                chunks_count as usize,
+                                chunk,
+                            );
+                            if world_snapshot.is_some() {
+                                self.world_snapshot = world_snapshot;
+                            }
+                        }
+                        ServerMessagePayload::EndGame => {
+                            log::info!("The game is over, returning to the room");
+                            let multiplayer_game_state = &mut *system_data.multiplayer_game_state;
+                            multiplayer_game_state.is_playing = false;
+                            multiplayer_game_state.waiting_network = false;
+                            multiplayer_game_state.waiting_for_players = false;
+                            multiplayer_game_state.is_paused_by_admin = false;
+                            multiplayer_game_state.lagging_players.clear();
+                        }
+                        // A rejoining client may receive updates before it resumes the game,
+                        // they will be resent after we start acknowledging them.
+                        ServerMessagePayload::UpdateWorld { .. }
+                            if !system_data.multiplayer_game_state.is_playing => {}
+                        ServerMessagePayload::UpdateWorld {
+                            id,
+                            base_id,
+                            positions_id,
+                            positions_base_id,
+                            updates,
+                        } => {
+                            let last_acknowledged_update =
+                                &mut *system_data.last_acknowledged_update;
+                            let is_next_update = base_id <= last_acknowledged_update.id
+                                && last_acknowledged_update.id < id;
+                            let positions =
+                                last_acknowledged_update.positions.get(positions_base_id);
+                            let updates = match positions {
+                                Some(positions) if is_next_update => updates
+                                    .decode(positions)
+                                    .map_err(|err| {
+                                        log::error!("Failed to decode world updates: {:?}", err)
+                                    })
+                                    .ok(),
+                                _ => None,
+                            };
+
+                            if let Some((mut updates, positions)) = updates {
                                 updates.sort_by(|a, b| a.frame_number.cmp(&b.frame_number));
 
+                                last_acknowledged_update.positions_id = positions_id;
+                                last_acknowledged_update
+                                    .positions
+                                    .forget_older_than(positions_base_id);
+                                last_acknowledged_update
+                                    .positions
+                                    .insert(positions_id, positions);
                                 system_data.last_acknowledged_update.id = id;
                                 system_data.last_acknowledged_update.frame_number =
                                     system_data.last_acknowledged_update.frame_number.max(
@@ -405,13 +576,31 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data.framed_updates.reserve_updates(frame_to_reserve);
                                 system_data.spawn_actions.reserve_updates(frame_to_reserve);
 
+                                // Spectators don't predict any of the players.
+                                let controlled_players =
+                                    if system_data.multiplayer_room_state.is_spectator {
+                                        Vec::new()
+                                    } else {
+                                        vec![system_data.multiplayer_room_state.player_net_id]
+                                    };
                                 apply_world_updates(
-                                    vec![system_data.multiplayer_room_state.player_net_id],
+                                    controlled_players,
                                     &mut system_data.framed_updates,
                                     &mut system_data.spawn_actions,
                                     updates,
                                 );
                             }
+
+                            // Acknowledging only applied updates, as the ones that follow
+                            // a lost message can't be applied.
+                            send_message_unreliable(
+                                &mut system_data.transport,
+                                net_connection_model,
+                                ClientMessagePayload::AcknowledgeWorldUpdate {
+                                    id: system_data.last_acknowledged_update.id,
+                                    positions_id: system_data.last_acknowledged_update.positions_id,
+                                },
+                            );
                         }
                         ServerMessagePayload::DiscardWalkActions(discarded_actions) => {
                             discard_walk_actions(
@@ -438,6 +627,8 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 system_data
                                     .multiplayer_game_state
                                     .waiting_for_players_pause_id = id;
+                                system_data.multiplayer_game_state.is_paused_by_admin =
+                                    players.is_empty();
                                 system_data.multiplayer_game_state.lagging_players = players;
                             }
                         }
@@ -448,6 +639,7 @@ impl<'s> System<'s> for ClientNetworkSystem {
                                 <= id
                             {
                                 system_data.multiplayer_game_state.waiting_for_players = false;
+                                system_data.multiplayer_game_state.is_paused_by_admin = false;
                                 system_data
                                     .multiplayer_game_state
                                     .waiting_for_players_pause_id = id;
@@ -498,6 +690,37 @@ impl<'s> System<'s> for ClientNetworkSystem {
             }
         }
 
+        // A rejoining client resumes the game once it has received the whole WorldSnapshot.
+        if self.resumed_game.is_some() && self.world_snapshot.is_some() {
+            let (seed, net_ids_and_players, campaign_level) = self.resumed_game.take().unwrap();
+            let world_snapshot = self.world_snapshot.take().unwrap();
+            log::info!(
+                "Rejoining a running game (frame {})",
+                world_snapshot.frame_number
+            );
+            system_data.last_acknowledged_update.frame_number = world_snapshot.frame_number;
+            system_data.last_acknowledged_update.id = world_snapshot.update_id;
+            system_data.last_acknowledged_update.positions_id = 0;
+            system_data.last_acknowledged_update.positions = WorldUpdatePositionsHistory::default();
+            system_data.multiplayer_room_state.lobby_countdown = None;
+            // The snapshot contains the state after simulating its frame.
+            let resumed_frame_number = world_snapshot.frame_number + 1;
+            *system_data.framed_updates = FramedUpdates::starting_from(resumed_frame_number);
+            *system_data.spawn_actions = FramedUpdates::starting_from(resumed_frame_number);
+            *system_data.player_actions_updates =
+                FramedUpdates::starting_from(resumed_frame_number);
+            system_data.multiplayer_game_state.world_snapshot = Some(world_snapshot);
+            *system_data.game_mode = GameMode::from_campaign_level(campaign_level);
+
+            start_game(
+                &mut system_data.multiplayer_room_state,
+                &mut system_data.multiplayer_game_state,
+                &mut system_data.new_game_engine_sate,
+                seed,
+                net_ids_and_players,
+            );
+        }
+
         if system_data.game_time_service.engine_time().frame_number() - self.last_heartbeat_frame
             > HEARTBEAT_FRAME_INTERVAL
             && !net_connection_model.disconnected
@@ -516,6 +739,7 @@ impl<'s> System<'s> for ClientNetworkSystem {
             .multiplayer_game_state
             .lagging_players
             .is_empty()
+            || system_data.multiplayer_game_state.is_paused_by_admin
         {
             let server_frame = system_data
                 .framed_updates
@@ -595,23 +819,13 @@ fn apply_world_updates(
     spawn_actions: &mut FramedUpdates<SpawnActions>,
     mut incoming_updates: Vec<ServerWorldUpdate>,
 ) {
+    // A client that has rejoined a running game doesn't have the frames before its snapshot.
+    let first_available_frame_number = framed_updates.updates.front().unwrap().frame_number;
+    incoming_updates.retain(|update| update.frame_number >= first_available_frame_number);
     if incoming_updates.is_empty() {
         return;
     }
 
-    let first_incoming_frame_number = incoming_updates
-        .first()
-        .unwrap()
-        .frame_number
-        .saturating_sub(INTERPOLATION_FRAME_DELAY);
-    let first_available_frame_number = framed_updates.updates.front().unwrap().frame_number;
-    assert!(
-        first_incoming_frame_number >= first_available_frame_number,
-        "Tried to apply a too old ServerUpdate (frame {}), when the first available frame is {}",
-        first_incoming_frame_number,
-        first_available_frame_number,
-    );
-
     let controlled_player_updates =
         collect_controlled_player_updates(&controlled_players, &mut incoming_updates);
 
@@ -626,6 +840,11 @@ fn apply_world_updates(
             )
         })
         .unwrap();
+    // The same goes for the controlled player updates, which are applied with a delay.
+    let skipped_controlled_player_updates =
+        first_available_frame_number.saturating_sub(controlled_start_frame_number);
+    let controlled_start_frame_number =
+        controlled_start_frame_number.max(first_available_frame_number);
 
     spawn_actions.oldest_updated_frame = others_start_frame_number;
     for (spawn_actions, server_update) in spawn_actions
@@ -636,7 +855,9 @@ fn apply_world_updates(
     }
 
     framed_updates.oldest_updated_frame = controlled_start_frame_number;
-    let mut controlled_player_updates_iter = controlled_player_updates.into_iter();
+    let mut controlled_player_updates_iter = controlled_player_updates
+        .into_iter()
+        .skip(skipped_controlled_player_updates as usize);
     let mut incoming_updates_iter = incoming_updates.into_iter();
 
     for frame_updates in framed_updates.updates_iter_mut(controlled_start_frame_number) {
@@ -0,0 +1,85 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+
+use gv_client_shared::{ecs::resources::MultiplayerRoomState, profile::Profile};
+use gv_core::{
+    ecs::resources::{net::MultiplayerGameState, GameEngineState, GameLevelState},
+    progression::EXPERIENCE_PER_KILL,
+};
+use gv_game::ecs::resources::ReplayPlayback;
+
+/// Awards experience for monster kills and saves the profile on level-ups and once a game is over.
+/// In multiplayer only the monsters killed by the local player count.
+#[derive(Default)]
+pub struct ExperienceSystem {
+    last_monsters_killed: u32,
+    has_unsaved_progress: bool,
+}
+
+impl<'s> System<'s> for ExperienceSystem {
+    type SystemData = (
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        ReadExpect<'s, ReplayPlayback>,
+        WriteExpect<'s, Profile>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_engine_state,
+            game_level_state,
+            multiplayer_game_state,
+            multiplayer_room_state,
+            replay_playback,
+            mut profile,
+        ): Self::SystemData,
+    ) {
+        // Multiplayer state is reset when players return to the lobby, so kills are counted
+        // only while a game is running.
+        let monsters_killed = if *game_engine_state != GameEngineState::Playing {
+            self.last_monsters_killed
+        } else if !multiplayer_game_state.is_playing {
+            game_level_state.monsters_killed
+        } else if multiplayer_room_state.is_spectator {
+            0
+        } else {
+            game_level_state
+                .monsters_killed_by_players
+                .get(&multiplayer_room_state.player_net_id)
+                .copied()
+                .unwrap_or(0)
+        };
+
+        // GameLevelState gets reset on starting a new game.
+        if monsters_killed < self.last_monsters_killed {
+            self.last_monsters_killed = 0;
+        }
+
+        let new_kills = monsters_killed - self.last_monsters_killed;
+        self.last_monsters_killed = monsters_killed;
+        let mut levels_gained = 0;
+        // Watching replays doesn't award experience.
+        if new_kills > 0 && !replay_playback.is_active() {
+            levels_gained = profile.add_experience(u64::from(new_kills) * EXPERIENCE_PER_KILL);
+            self.has_unsaved_progress = true;
+            if levels_gained > 0 {
+                log::info!(
+                    "Reached level {} ({} unspent skill point(s))",
+                    profile.level,
+                    profile.unspent_points
+                );
+            }
+        }
+
+        // Survival games don't end unless players quit, so we also save on level-ups.
+        let should_save = levels_gained > 0 || *game_engine_state != GameEngineState::Playing;
+        if self.has_unsaved_progress && should_save {
+            self.has_unsaved_progress = false;
+            if let Err(err) = profile.save() {
+                log::error!("Failed to save the profile: {}", err);
+            }
+        }
+    }
+}
@@ -5,7 +5,11 @@ use amethyst::{
 };
 
 use gv_client_shared::ecs::{components::HealthUiGraphics, resources::HEALTH_UI_SCREEN_PADDING};
-use gv_core::{ecs::components::Player, math::Vector2};
+use gv_core::{
+    ecs::components::{ClientPlayerActions, Player},
+    math::Vector2,
+};
+use gv_game::ecs::resources::SpellDefinitions;
 
 use crate::ecs::system_data::ui::UiFinderMut;
 
@@ -28,7 +32,8 @@ impl<'s> System<'s> for HealthUiSystem {
         let half_screen_height = screen_dimensions.height() / 2.0;
 
         for (player, health_ui) in (&players, &mut health_uis).join() {
-            health_ui.health = player.health / 100.0;
+            let max_health = player.stats.max_health();
+            health_ui.health = player.health / max_health;
             health_ui.screen_position = Vector2::new(
                 -half_screen_width + HEALTH_UI_SCREEN_PADDING,
                 -half_screen_height + HEALTH_UI_SCREEN_PADDING,
@@ -36,8 +41,49 @@ impl<'s> System<'s> for HealthUiSystem {
 
             if let Some(ui_health_label) = ui_finder.find("ui_health_label") {
                 ui_texts.get_mut(ui_health_label).unwrap().text =
-                    format!("{:.0}/100", num::Float::max(0.0, player.health));
+                    format!("{:.0}/{:.0}", num::Float::max(0.0, player.health), max_health);
             }
         }
     }
 }
+
+/// Shows the selected spell and the mana of a controlled player.
+pub struct SpellUiSystem;
+
+impl<'s> System<'s> for SpellUiSystem {
+    type SystemData = (
+        UiFinderMut<'s>,
+        ReadExpect<'s, SpellDefinitions>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, ClientPlayerActions>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            ui_finder,
+            spell_definitions,
+            players,
+            client_player_actions,
+            mut ui_texts,
+        ): Self::SystemData,
+    ) {
+        let ui_spell_label = if let Some(ui_spell_label) = ui_finder.find("ui_spell_label") {
+            ui_spell_label
+        } else {
+            return;
+        };
+
+        for (player, client_player_actions) in (&players, &client_player_actions).join() {
+            let spell_definition = spell_definitions.get(client_player_actions.selected_spell);
+            ui_texts.get_mut(ui_spell_label).unwrap().text = format!(
+                "{} ({:.0} mana), mana: {:.0}/{:.0}",
+                spell_definition.name,
+                spell_definition.mana_cost,
+                player.mana,
+                player.stats.max_mana()
+            );
+        }
+    }
+}
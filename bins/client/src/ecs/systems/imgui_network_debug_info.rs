@@ -1,5 +1,5 @@
 use amethyst::{
-    ecs::{ReadExpect, System},
+    ecs::{ReadExpect, System, WriteExpect},
     window::ScreenDimensions,
 };
 use amethyst_imgui::imgui::{self, im_str};
@@ -8,9 +8,12 @@ use gv_core::ecs::resources::{
     net::{MultiplayerGameState, PlayersNetStatus},
     GameEngineState,
 };
+use gv_game::utils::net::simulation::{NetworkConditions, NetworkSimulator};
 
 use crate::ecs::resources::DisplayDebugInfoSettings;
 
+const MAX_SLIDER_DELAY_MS: i32 = 1000;
+
 pub struct ImguiNetworkDebugInfoSystem;
 
 impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
@@ -20,6 +23,7 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
         ReadExpect<'s, MultiplayerGameState>,
         ReadExpect<'s, DisplayDebugInfoSettings>,
         ReadExpect<'s, PlayersNetStatus>,
+        WriteExpect<'s, NetworkSimulator>,
     );
 
     fn run(
@@ -30,6 +34,7 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
             multiplayer_game_state,
             display_debug_info_settings,
             players_net_status,
+            mut network_simulator,
         ): Self::SystemData,
     ) {
         if !game_engine_state.is_playing() {
@@ -93,7 +98,50 @@ impl<'s> System<'s> for ImguiNetworkDebugInfoSystem {
                             ui.text(player_net_status.latency_ms.to_string());
                         }
                     });
+
+                imgui::Window::new(im_str!("Network Simulation"))
+                    .save_settings(false)
+                    .position(
+                        [
+                            screen_dimensions.width() / screen_dimensions.hidpi_factor() as f32,
+                            180.0,
+                        ],
+                        imgui::Condition::FirstUseEver,
+                    )
+                    .position_pivot([1.1, 0.0])
+                    .size([250.0, 170.0], imgui::Condition::FirstUseEver)
+                    .bg_alpha(0.7)
+                    .build(ui, || {
+                        let conditions = &mut network_simulator.conditions;
+                        delay_slider(ui, im_str!("Latency, ms"), &mut conditions.latency_ms);
+                        delay_slider(ui, im_str!("Jitter, ms"), &mut conditions.jitter_ms);
+                        percentage_slider(ui, im_str!("Loss, %"), &mut conditions.loss);
+                        percentage_slider(
+                            ui,
+                            im_str!("Duplication, %"),
+                            &mut conditions.duplication,
+                        );
+                        percentage_slider(ui, im_str!("Reordering, %"), &mut conditions.reordering);
+                        if ui.small_button(im_str!("Reset")) {
+                            *conditions = NetworkConditions::default();
+                        }
+                    });
             }
         });
     }
 }
+
+fn delay_slider(ui: &imgui::Ui, label: &imgui::ImStr, delay_ms: &mut u32) {
+    let mut value = *delay_ms as i32;
+    let slider = ui.slider_int(label, &mut value, 0, MAX_SLIDER_DELAY_MS);
+    if slider.build() {
+        *delay_ms = value as u32;
+    }
+}
+
+fn percentage_slider(ui: &imgui::Ui, label: &imgui::ImStr, probability: &mut f32) {
+    let mut percentage = *probability * 100.0;
+    if ui.slider_float(label, &mut percentage, 0.0, 100.0).build() {
+        *probability = percentage / 100.0;
+    }
+}
@@ -10,23 +10,32 @@ use amethyst::{
     winit::MouseButton,
 };
 
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
 use gv_core::{
-    actions::player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+    actions::player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction, SpellId},
     ecs::components::{ClientPlayerActions, WorldPosition},
     math::Vector2,
 };
-use gv_game::ecs::system_data::GameStateHelper;
+use gv_game::ecs::{resources::ReplayPlayback, system_data::GameStateHelper};
 
 use std::collections::HashSet;
 
 use crate::ecs::resources::DisplayDebugInfoSettings;
 
+const SPELL_HOTKEYS: [(&str, SpellId); 3] = [
+    ("select_spell_missile", SpellId::Missile),
+    ("select_spell_fireball", SpellId::Fireball),
+    ("select_spell_fire_nova", SpellId::FireNova),
+];
+
 #[derive(SystemData)]
 pub struct InputSystemData<'s> {
     input: ReadExpect<'s, InputHandler<StringBindings>>,
     screen_dimensions: ReadExpect<'s, ScreenDimensions>,
     transforms: ReadStorage<'s, Transform>,
+    multiplayer_room_state: ReadExpect<'s, MultiplayerRoomState>,
     display_debug_info_settings: WriteExpect<'s, DisplayDebugInfoSettings>,
+    replay_playback: WriteExpect<'s, ReplayPlayback>,
 }
 
 #[derive(Default)]
@@ -57,10 +66,23 @@ impl<'s> System<'s> for InputSystem {
             mut input_system_data,
         ): Self::SystemData,
     ) {
+        // Replayed players are controlled by ReplaySystem, a paused replay still has to
+        // react to its controls.
+        if input_system_data.replay_playback.is_active() {
+            self.process_replay_input(&mut input_system_data);
+            return;
+        }
+
         if !game_state_helper.is_running() {
             return;
         }
 
+        // Spectators don't control any player, SpectatorCameraSystem handles their camera.
+        if input_system_data.multiplayer_room_state.is_spectator {
+            self.process_toggles(&mut input_system_data);
+            return;
+        }
+
         let (camera_entity, camera_parent, _) = (&entities, &parents, &cameras)
             .join()
             .next()
@@ -119,6 +141,7 @@ impl InputSystem {
 
         if system_data.input.mouse_button_is_down(MouseButton::Left) {
             client_player_actions.cast_action = Some(PlayerCastAction {
+                spell: client_player_actions.selected_spell,
                 cast_position: player_position,
                 target_position: mouse_world_position,
             });
@@ -145,6 +168,21 @@ impl InputSystem {
             None
         };
 
+        self.process_toggles(system_data);
+
+        for (action, spell) in SPELL_HOTKEYS.iter() {
+            if system_data.input.action_is_down(action).unwrap_or_default() {
+                client_player_actions.selected_spell = *spell;
+            }
+        }
+
+        let action = direction
+            .map(|direction| PlayerWalkAction::Walk { direction })
+            .unwrap_or(PlayerWalkAction::Stop);
+        client_player_actions.walk_action = action;
+    }
+
+    fn process_toggles(&mut self, system_data: &mut InputSystemData) {
         let display_health = &mut system_data.display_debug_info_settings.display_health;
         self.process_toggle_action(&system_data.input, "toggle_healthbars", || {
             *display_health = !*display_health;
@@ -162,11 +200,18 @@ impl InputSystem {
             log::info!("Toggling profiler");
             thread_profiler::toggle_profiler();
         });
+    }
 
-        let action = direction
-            .map(|direction| PlayerWalkAction::Walk { direction })
-            .unwrap_or(PlayerWalkAction::Stop);
-        client_player_actions.walk_action = action;
+    fn process_replay_input(&mut self, system_data: &mut InputSystemData) {
+        let replay_playback = &mut *system_data.replay_playback;
+        self.process_toggle_action(&system_data.input, "replay_toggle_pause", || {
+            replay_playback.paused = !replay_playback.paused;
+            log::info!("Replay paused: {}", replay_playback.paused);
+        });
+        self.process_toggle_action(&system_data.input, "replay_step", || {
+            replay_playback.paused = true;
+            replay_playback.step_requested = true;
+        });
     }
 
     fn process_toggle_action(
@@ -0,0 +1,133 @@
+use gv_core::progression::{experience_for_level, Stat, MAX_STAT_RANK};
+
+use super::*;
+
+const STAT_ELEMENTS: [(Stat, MenuElement, MenuElement); 4] = [
+    (
+        Stat::MaxHealth,
+        UI_CHARACTER_MAX_HEALTH_LABEL,
+        UI_CHARACTER_MAX_HEALTH_BUTTON,
+    ),
+    (
+        Stat::MoveSpeed,
+        UI_CHARACTER_MOVE_SPEED_LABEL,
+        UI_CHARACTER_MOVE_SPEED_BUTTON,
+    ),
+    (
+        Stat::CastCooldown,
+        UI_CHARACTER_CAST_COOLDOWN_LABEL,
+        UI_CHARACTER_CAST_COOLDOWN_BUTTON,
+    ),
+    (
+        Stat::MissileDamage,
+        UI_CHARACTER_MISSILE_DAMAGE_LABEL,
+        UI_CHARACTER_MISSILE_DAMAGE_BUTTON,
+    ),
+];
+
+pub struct CharacterMenuScreen;
+
+impl CharacterMenuScreen {
+    fn update_texts(system_data: &mut MenuSystemData) {
+        let profile = &*system_data.profile;
+        let level = format!(
+            "Level {} (XP {}/{})",
+            profile.level,
+            profile.experience,
+            experience_for_level(profile.level + 1)
+        );
+        let points = format!("Skill points: {}", profile.unspent_points);
+        let stats = profile.stats;
+
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_CHARACTER_LEVEL)
+            .expect("Expected a character level text component") = level;
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_CHARACTER_POINTS)
+            .expect("Expected a character points text component") = points;
+
+        for (stat, label, _) in STAT_ELEMENTS.iter() {
+            let value = match stat {
+                Stat::MaxHealth => format!("{:.0} HP", stats.max_health()),
+                Stat::MoveSpeed => format!("{:.0} px/s", stats.move_speed()),
+                Stat::CastCooldown => {
+                    format!("-{:.0}% cooldowns", (1.0 - stats.cooldown_multiplier()) * 100.0)
+                }
+                Stat::MissileDamage => format!("{:.0} damage", stats.missile_damage()),
+            };
+            *system_data
+                .ui_finder
+                .get_ui_text_mut(&mut system_data.ui_texts, label)
+                .expect("Expected a character stat text component") = format!(
+                "{}: {}/{} ({})",
+                stat_name(*stat),
+                stats.rank(*stat),
+                MAX_STAT_RANK,
+                value
+            );
+        }
+    }
+}
+
+impl MenuScreen for CharacterMenuScreen {
+    fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        let mut elements = vec![UI_CHARACTER_LEVEL, UI_CHARACTER_POINTS];
+        for (_, label, button) in STAT_ELEMENTS.iter() {
+            elements.push(*label);
+            elements.push(*button);
+        }
+        elements.push(UI_MAIN_MENU_BUTTON);
+        elements
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        Self::update_texts(system_data);
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        match button_pressed {
+            Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
+            Some(button_pressed) => {
+                let stat_element = STAT_ELEMENTS
+                    .iter()
+                    .find(|(_, _, button)| *button == button_pressed);
+                if let Some((stat, _, button)) = stat_element {
+                    if system_data.profile.spend_point(*stat) {
+                        if let Err(err) = system_data.profile.save() {
+                            log::error!("Failed to save the profile: {}", err);
+                        }
+                        Self::update_texts(system_data);
+                    }
+
+                    // Unlike the other buttons, these ones are meant to be clicked repeatedly.
+                    let button_entity = system_data
+                        .ui_finder
+                        .find(button)
+                        .expect("Expected a skill point button");
+                    system_data
+                        .ui_interactables
+                        .insert(button_entity, Interactable)
+                        .expect("Expected to insert Interactable component");
+                }
+                StateUpdate::None
+            }
+            None => StateUpdate::None,
+        }
+    }
+}
+
+fn stat_name(stat: Stat) -> &'static str {
+    match stat {
+        Stat::MaxHealth => "Max health",
+        Stat::MoveSpeed => "Move speed",
+        Stat::CastCooldown => "Cast cooldown",
+        Stat::MissileDamage => "Missile damage",
+    }
+}
@@ -19,9 +19,30 @@ impl MenuScreen for HiddenMenuScreen {
         modal_window_id: Option<&str>,
     ) -> StateUpdate {
         if system_data.game_level_state.is_over {
+            let menu_screen = if system_data.game_level_state.completed_at.is_some() {
+                GameMenuScreen::LevelResultsMenu
+            } else {
+                GameMenuScreen::RestartMenu
+            };
+            return StateUpdate::GameMenuUpdate {
+                game_engine_state: Some(GameEngineState::Menu),
+                menu_screen: Some(menu_screen),
+            };
+        }
+
+        // Dedicated servers return players to the room once a game is over.
+        if system_data.multiplayer_room_state.is_active
+            && !system_data.multiplayer_game_state.is_playing
+            && system_data
+                .multiplayer_room_state
+                .connection_status
+                .is_connected()
+        {
+            // TODO (refactor): mutating game_level_state from here is not cool.
+            system_data.game_level_state.is_over = true;
             return StateUpdate::GameMenuUpdate {
                 game_engine_state: Some(GameEngineState::Menu),
-                menu_screen: Some(GameMenuScreen::RestartMenu),
+                menu_screen: Some(GameMenuScreen::MultiplayerRoomMenu),
             };
         }
 
@@ -0,0 +1,87 @@
+use super::*;
+
+pub struct LevelResultsMenuScreen;
+
+impl LevelResultsMenuScreen {
+    fn has_next_level(system_data: &MenuSystemData) -> bool {
+        match *system_data.game_mode {
+            GameMode::Campaign { level_index } => !system_data.campaign.is_last_level(level_index),
+            GameMode::Survival => false,
+        }
+    }
+}
+
+impl MenuScreen for LevelResultsMenuScreen {
+    fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
+        let mut elements = vec![UI_LEVEL_RESULTS_TITLE, UI_LEVEL_RESULTS_STATS];
+        if Self::has_next_level(system_data) {
+            elements.push(UI_NEXT_LEVEL_BUTTON);
+        }
+        elements.push(UI_MAIN_MENU_BUTTON);
+        elements
+    }
+
+    fn elements_to_hide(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
+        vec![
+            UI_LEVEL_RESULTS_TITLE,
+            UI_LEVEL_RESULTS_STATS,
+            UI_NEXT_LEVEL_BUTTON,
+            UI_MAIN_MENU_BUTTON,
+        ]
+    }
+
+    fn show(&mut self, system_data: &mut MenuSystemData) {
+        let level_name = system_data
+            .game_mode
+            .level_definition(&system_data.campaign)
+            .map(|level_definition| level_definition.name.clone())
+            .unwrap_or_default();
+        let title = if Self::has_next_level(system_data) {
+            format!("Level \"{}\" complete", level_name)
+        } else {
+            "Campaign complete!".to_owned()
+        };
+
+        let completed_in = system_data
+            .game_level_state
+            .completed_at
+            .unwrap_or_default()
+            .as_secs();
+        let stats = format!(
+            "Time: {}:{:02}, monsters killed: {}",
+            completed_in / 60,
+            completed_in % 60,
+            system_data.game_level_state.monsters_killed
+        );
+
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_LEVEL_RESULTS_TITLE)
+            .expect("Expected a level results title text component") = title;
+        *system_data
+            .ui_finder
+            .get_ui_text_mut(&mut system_data.ui_texts, UI_LEVEL_RESULTS_STATS)
+            .expect("Expected a level results stats text component") = stats;
+    }
+
+    fn update(
+        &mut self,
+        system_data: &mut MenuSystemData,
+        button_pressed: Option<&str>,
+        _modal_window_id: Option<&str>,
+    ) -> StateUpdate {
+        match button_pressed {
+            Some(UI_NEXT_LEVEL_BUTTON) => {
+                if let GameMode::Campaign { ref mut level_index } = *system_data.game_mode {
+                    *level_index += 1;
+                }
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_MAIN_MENU_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::MainMenu),
+            _ => StateUpdate::None,
+        }
+    }
+}
@@ -22,6 +22,7 @@ impl MenuScreen for LobbyMenuScreen {
             UI_LOBBY_JOIN_IP_FIELD,
             UI_LOBBY_JOIN_IP_EDITABLE,
             UI_LOBBY_JOIN_BUTTON,
+            UI_LOBBY_SPECTATE_BUTTON,
             UI_MAIN_MENU_BUTTON,
         ]
     }
@@ -36,7 +37,10 @@ impl MenuScreen for LobbyMenuScreen {
             (Some(UI_MAIN_MENU_BUTTON), _) => {
                 StateUpdate::new_menu_screen(GameMenuScreen::MainMenu)
             }
-            (Some(b @ (UI_LOBBY_HOST_BUTTON | UI_LOBBY_JOIN_BUTTON)), _) => {
+            (
+                Some(b @ (UI_LOBBY_HOST_BUTTON | UI_LOBBY_JOIN_BUTTON | UI_LOBBY_SPECTATE_BUTTON)),
+                _,
+            ) => {
                 let is_host = b == UI_LOBBY_HOST_BUTTON;
 
                 let address_field = if is_host {
@@ -74,6 +78,7 @@ impl MenuScreen for LobbyMenuScreen {
                     system_data.ui_network_command.command = Some(UiNetworkCommand::Connect {
                         nickname,
                         server_addr,
+                        is_spectator: b == UI_LOBBY_SPECTATE_BUTTON,
                     });
                 }
                 StateUpdate::ShowModalWindow {
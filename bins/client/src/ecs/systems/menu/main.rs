@@ -6,6 +6,8 @@ impl MenuScreen for MainMenuScreen {
     fn elements_to_show(&self, _system_data: &MenuSystemData) -> Vec<MenuElement> {
         vec![
             UI_SINGLE_PLAYER_BUTTON,
+            UI_CAMPAIGN_BUTTON,
+            UI_CHARACTER_BUTTON,
             UI_MULTIPLAYER_BUTTON,
             UI_QUIT_BUTTON,
         ]
@@ -13,16 +15,42 @@ impl MenuScreen for MainMenuScreen {
 
     fn update(
         &mut self,
-        _system_data: &mut MenuSystemData,
+        system_data: &mut MenuSystemData,
         button_pressed: Option<&str>,
         _modal_window_id: Option<&str>,
     ) -> StateUpdate {
-        match button_pressed {
-            Some(UI_SINGLE_PLAYER_BUTTON) => StateUpdate::GameMenuUpdate {
+        // A replay passed with `--replay` starts right away.
+        if let Some(game_mode) = system_data.replay_playback.game_mode() {
+            *system_data.game_mode = game_mode;
+            return StateUpdate::GameMenuUpdate {
                 game_engine_state: Some(GameEngineState::Playing),
                 menu_screen: Some(GameMenuScreen::Hidden),
-            },
-            Some(UI_MULTIPLAYER_BUTTON) => StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu),
+            };
+        }
+
+        match button_pressed {
+            Some(UI_SINGLE_PLAYER_BUTTON) => {
+                *system_data.game_mode = GameMode::Survival;
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_CAMPAIGN_BUTTON) => {
+                *system_data.game_mode = GameMode::Campaign { level_index: 0 };
+                StateUpdate::GameMenuUpdate {
+                    game_engine_state: Some(GameEngineState::Playing),
+                    menu_screen: Some(GameMenuScreen::Hidden),
+                }
+            }
+            Some(UI_CHARACTER_BUTTON) => {
+                StateUpdate::new_menu_screen(GameMenuScreen::CharacterMenu)
+            }
+            Some(UI_MULTIPLAYER_BUTTON) => {
+                // Multiplayer games are survival only.
+                *system_data.game_mode = GameMode::Survival;
+                StateUpdate::new_menu_screen(GameMenuScreen::LobbyMenu)
+            }
             Some(UI_QUIT_BUTTON) => StateUpdate::new_game_engine_state(GameEngineState::Quit),
             _ => StateUpdate::None,
         }
@@ -1,4 +1,6 @@
+mod character;
 mod hidden;
+mod level_results;
 mod lobby;
 mod main;
 mod multiplayer_room;
@@ -15,17 +17,24 @@ use lazy_static::lazy_static;
 
 use std::{collections::VecDeque, time::Duration};
 
-use gv_client_shared::ecs::resources::MultiplayerRoomState;
-use gv_core::ecs::{
-    resources::{net::MultiplayerGameState, GameEngineState, GameLevelState, NewGameEngineState},
-    system_data::time::GameTimeService,
+use gv_client_shared::{ecs::resources::MultiplayerRoomState, profile::Profile};
+use gv_core::{
+    ecs::{
+        resources::{
+            net::MultiplayerGameState, GameEngineState, GameLevelState, NewGameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::MAX_PLAYERS_LIMIT,
 };
+use gv_game::ecs::resources::{Campaign, GameMode, ReplayPlayback};
 
 use crate::ecs::{
     resources::UiNetworkCommandResource,
     system_data::ui::UiFinderMut,
     systems::menu::{
-        hidden::HiddenMenuScreen, lobby::LobbyMenuScreen, main::MainMenuScreen,
+        character::CharacterMenuScreen, hidden::HiddenMenuScreen,
+        level_results::LevelResultsMenuScreen, lobby::LobbyMenuScreen, main::MainMenuScreen,
         multiplayer_room::MultiplayerRoomMenuScreen, restart::RestartMenuScreen,
     },
 };
@@ -40,12 +49,29 @@ const UI_MAIN_CONTAINER: &str = "ui_main_container";
 const UI_LOADING_LABEL: &str = "ui_loading_label";
 
 const UI_SINGLE_PLAYER_BUTTON: &str = "ui_single_player_button";
+const UI_CAMPAIGN_BUTTON: &str = "ui_campaign_button";
+const UI_CHARACTER_BUTTON: &str = "ui_character_button";
 const UI_MULTIPLAYER_BUTTON: &str = "ui_multiplayer_button";
 const UI_QUIT_BUTTON: &str = "ui_quit_button";
 
 const UI_RESTART_BUTTON: &str = "ui_restart_button";
 const UI_MAIN_MENU_BUTTON: &str = "ui_main_menu_button";
 
+const UI_LEVEL_RESULTS_TITLE: &str = "ui_level_results_title";
+const UI_LEVEL_RESULTS_STATS: &str = "ui_level_results_stats";
+const UI_NEXT_LEVEL_BUTTON: &str = "ui_next_level_button";
+
+const UI_CHARACTER_LEVEL: &str = "ui_character_level";
+const UI_CHARACTER_POINTS: &str = "ui_character_points";
+const UI_CHARACTER_MAX_HEALTH_LABEL: &str = "ui_character_max_health_label";
+const UI_CHARACTER_MAX_HEALTH_BUTTON: &str = "ui_character_max_health_button";
+const UI_CHARACTER_MOVE_SPEED_LABEL: &str = "ui_character_move_speed_label";
+const UI_CHARACTER_MOVE_SPEED_BUTTON: &str = "ui_character_move_speed_button";
+const UI_CHARACTER_CAST_COOLDOWN_LABEL: &str = "ui_character_cast_cooldown_label";
+const UI_CHARACTER_CAST_COOLDOWN_BUTTON: &str = "ui_character_cast_cooldown_button";
+const UI_CHARACTER_MISSILE_DAMAGE_LABEL: &str = "ui_character_missile_damage_label";
+const UI_CHARACTER_MISSILE_DAMAGE_BUTTON: &str = "ui_character_missile_damage_button";
+
 const UI_LOBBY_NICKNAME_LABEL: &str = "ui_lobby_nickname_label";
 const UI_LOBBY_NICKNAME_FIELD: &str = "ui_lobby_nickname_field";
 const UI_LOBBY_NICKNAME_EDITABLE: &str = "ui_lobby_nickname_editable";
@@ -55,29 +81,11 @@ const UI_LOBBY_HOST_BUTTON: &str = "ui_lobby_host_button";
 const UI_LOBBY_JOIN_IP_FIELD: &str = "ui_lobby_join_ip_field";
 const UI_LOBBY_JOIN_IP_EDITABLE: &str = "ui_lobby_join_ip_editable";
 const UI_LOBBY_JOIN_BUTTON: &str = "ui_lobby_join_button";
+const UI_LOBBY_SPECTATE_BUTTON: &str = "ui_lobby_spectate_button";
 
-const UI_MP_ROOM_START_BUTTON: &str = "ui_start_multiplayer_button";
+const UI_MP_ROOM_READY_BUTTON: &str = "ui_mp_room_ready_button";
+const UI_MP_ROOM_COUNTDOWN: &str = "ui_mp_room_countdown";
 const UI_MP_ROOM_LOBBY_BUTTON: &str = "ui_back_to_lobby_button";
-const UI_MP_ROOM_PLAYER1_CONTAINER: &str = "ui_mp_room_player1_container";
-const UI_MP_ROOM_PLAYER1_BG: &str = "ui_mp_room_player1_bg";
-const UI_MP_ROOM_PLAYER1_NUMBER: &str = "ui_mp_room_player1_number";
-const UI_MP_ROOM_PLAYER1_NICKNAME: &str = "ui_mp_room_player1_nickname";
-const UI_MP_ROOM_PLAYER1_KICK: &str = "ui_mp_room_player1_kick";
-const UI_MP_ROOM_PLAYER2_CONTAINER: &str = "ui_mp_room_player2_container";
-const UI_MP_ROOM_PLAYER2_BG: &str = "ui_mp_room_player2_bg";
-const UI_MP_ROOM_PLAYER2_NUMBER: &str = "ui_mp_room_player2_number";
-const UI_MP_ROOM_PLAYER2_NICKNAME: &str = "ui_mp_room_player2_nickname";
-const UI_MP_ROOM_PLAYER2_KICK: &str = "ui_mp_room_player2_kick";
-const UI_MP_ROOM_PLAYER3_CONTAINER: &str = "ui_mp_room_player3_container";
-const UI_MP_ROOM_PLAYER3_BG: &str = "ui_mp_room_player3_bg";
-const UI_MP_ROOM_PLAYER3_NUMBER: &str = "ui_mp_room_player3_number";
-const UI_MP_ROOM_PLAYER3_NICKNAME: &str = "ui_mp_room_player3_nickname";
-const UI_MP_ROOM_PLAYER3_KICK: &str = "ui_mp_room_player3_kick";
-const UI_MP_ROOM_PLAYER4_CONTAINER: &str = "ui_mp_room_player4_container";
-const UI_MP_ROOM_PLAYER4_BG: &str = "ui_mp_room_player4_bg";
-const UI_MP_ROOM_PLAYER4_NUMBER: &str = "ui_mp_room_player4_number";
-const UI_MP_ROOM_PLAYER4_NICKNAME: &str = "ui_mp_room_player4_nickname";
-const UI_MP_ROOM_PLAYER4_KICK: &str = "ui_mp_room_player4_kick";
 
 const UI_MODAL_BACKDROP_CONTAINER: &str = "ui_modal_backdrop_container";
 const UI_MODAL_WINDOW_BORDER_CONTAINER: &str = "ui_modal_window_border_container";
@@ -85,6 +93,40 @@ const UI_MODAL_WINDOW_CONTAINER: &str = "ui_modal_window_container";
 const UI_MODAL_TITLE: &str = "ui_modal_title";
 const UI_MODAL_CONFIRM_BUTTON: &str = "ui_modal_confirm_button";
 
+/// Elements of a room screen row, a room has a row for every player it can have.
+struct MpRoomPlayerRow {
+    container: MenuElement,
+    background: MenuElement,
+    number: MenuElement,
+    nickname: MenuElement,
+    kick: MenuElement,
+}
+
+/// Expands to the ids of the row of a player with the number `$n` in multiplayer_menu.ron.
+macro_rules! mp_room_player_row {
+    ($n:literal) => {
+        MpRoomPlayerRow {
+            container: concat!("ui_mp_room_player", $n, "_container"),
+            background: concat!("ui_mp_room_player", $n, "_bg"),
+            number: concat!("ui_mp_room_player", $n, "_number"),
+            nickname: concat!("ui_mp_room_player", $n, "_nickname"),
+            kick: concat!("ui_mp_room_player", $n, "_kick"),
+        }
+    };
+}
+
+/// Rows of the room screen, only the first `max_players` of them are shown.
+static MP_ROOM_PLAYER_ROWS: [MpRoomPlayerRow; MAX_PLAYERS_LIMIT] = [
+    mp_room_player_row!(1),
+    mp_room_player_row!(2),
+    mp_room_player_row!(3),
+    mp_room_player_row!(4),
+    mp_room_player_row!(5),
+    mp_room_player_row!(6),
+    mp_room_player_row!(7),
+    mp_room_player_row!(8),
+];
+
 trait MenuScreen {
     fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement>;
 
@@ -113,6 +155,8 @@ trait MenuScreen {
 lazy_static! {
     static ref MAIN_MENU_ELEMENTS: &'static [&'static str] = &[
         UI_SINGLE_PLAYER_BUTTON,
+        UI_CAMPAIGN_BUTTON,
+        UI_CHARACTER_BUTTON,
         UI_MULTIPLAYER_BUTTON,
         UI_QUIT_BUTTON,
     ];
@@ -128,32 +172,14 @@ lazy_static! {
         UI_LOBBY_JOIN_IP_FIELD,
         UI_LOBBY_JOIN_IP_EDITABLE,
         UI_LOBBY_JOIN_BUTTON,
+        UI_LOBBY_SPECTATE_BUTTON,
         UI_MAIN_MENU_BUTTON,
     ];
     // TODO: implement and uncomment buttons.
     static ref MP_ROOM_MENU_ELEMENTS: &'static [&'static str] = &[
-        UI_MP_ROOM_START_BUTTON,
+        UI_MP_ROOM_READY_BUTTON,
+        UI_MP_ROOM_COUNTDOWN,
         UI_MP_ROOM_LOBBY_BUTTON,
-        UI_MP_ROOM_PLAYER1_CONTAINER,
-        UI_MP_ROOM_PLAYER1_BG,
-        UI_MP_ROOM_PLAYER1_NUMBER,
-        UI_MP_ROOM_PLAYER1_NICKNAME,
-        UI_MP_ROOM_PLAYER1_KICK,
-        UI_MP_ROOM_PLAYER2_CONTAINER,
-        UI_MP_ROOM_PLAYER2_BG,
-        UI_MP_ROOM_PLAYER2_NUMBER,
-        UI_MP_ROOM_PLAYER2_NICKNAME,
-        UI_MP_ROOM_PLAYER2_KICK,
-        UI_MP_ROOM_PLAYER3_CONTAINER,
-        UI_MP_ROOM_PLAYER3_BG,
-        UI_MP_ROOM_PLAYER3_NUMBER,
-        UI_MP_ROOM_PLAYER3_NICKNAME,
-        UI_MP_ROOM_PLAYER3_KICK,
-        UI_MP_ROOM_PLAYER4_CONTAINER,
-        UI_MP_ROOM_PLAYER4_BG,
-        UI_MP_ROOM_PLAYER4_NUMBER,
-        UI_MP_ROOM_PLAYER4_NICKNAME,
-        UI_MP_ROOM_PLAYER4_KICK,
     ];
     static ref MODAL_WINDOW_ELEMENTS: &'static [&'static str] = &[
         UI_MODAL_BACKDROP_CONTAINER,
@@ -171,9 +197,13 @@ pub struct MenuSystemData<'s> {
     game_engine_state: ReadExpect<'s, GameEngineState>,
     new_game_engine_state: WriteExpect<'s, NewGameEngineState>,
     game_level_state: WriteExpect<'s, GameLevelState>,
+    game_mode: WriteExpect<'s, GameMode>,
+    campaign: ReadExpect<'s, Campaign>,
+    profile: WriteExpect<'s, Profile>,
     ui_network_command: WriteExpect<'s, UiNetworkCommandResource>,
     multiplayer_room_state: ReadExpect<'s, MultiplayerRoomState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    replay_playback: ReadExpect<'s, ReplayPlayback>,
     ui_events: Write<'s, EventChannel<UiEvent>>,
     ui_texts: WriteStorage<'s, UiText>,
     ui_images: WriteStorage<'s, UiImage>,
@@ -204,6 +234,8 @@ struct MenuScreens {
     main_menu_screen: MainMenuScreen,
     multiplayer_room_menu_screen: MultiplayerRoomMenuScreen,
     restart_menu_screen: RestartMenuScreen,
+    level_results_menu_screen: LevelResultsMenuScreen,
+    character_menu_screen: CharacterMenuScreen,
     hidden_menu_screen: HiddenMenuScreen,
 }
 
@@ -214,6 +246,8 @@ impl MenuScreens {
             GameMenuScreen::MainMenu => Some(&mut self.main_menu_screen),
             GameMenuScreen::MultiplayerRoomMenu => Some(&mut self.multiplayer_room_menu_screen),
             GameMenuScreen::RestartMenu => Some(&mut self.restart_menu_screen),
+            GameMenuScreen::LevelResultsMenu => Some(&mut self.level_results_menu_screen),
+            GameMenuScreen::CharacterMenu => Some(&mut self.character_menu_screen),
             GameMenuScreen::Hidden => Some(&mut self.hidden_menu_screen),
             GameMenuScreen::Loading => None,
         }
@@ -261,28 +295,38 @@ impl MenuSystem {
                 main_menu_screen: MainMenuScreen,
                 multiplayer_room_menu_screen: MultiplayerRoomMenuScreen::new(),
                 restart_menu_screen: RestartMenuScreen,
+                level_results_menu_screen: LevelResultsMenuScreen,
+                character_menu_screen: CharacterMenuScreen,
                 hidden_menu_screen: HiddenMenuScreen,
             },
             modal_window_id: None,
-            mouse_reactive: vec![
+            mouse_reactive: [
                 UI_SINGLE_PLAYER_BUTTON,
+                UI_CAMPAIGN_BUTTON,
+                UI_CHARACTER_BUTTON,
                 UI_MULTIPLAYER_BUTTON,
                 UI_QUIT_BUTTON,
                 UI_RESTART_BUTTON,
                 UI_MAIN_MENU_BUTTON,
+                UI_NEXT_LEVEL_BUTTON,
+                UI_CHARACTER_MAX_HEALTH_BUTTON,
+                UI_CHARACTER_MOVE_SPEED_BUTTON,
+                UI_CHARACTER_CAST_COOLDOWN_BUTTON,
+                UI_CHARACTER_MISSILE_DAMAGE_BUTTON,
                 UI_LOBBY_NICKNAME_EDITABLE,
                 UI_LOBBY_HOST_IP_EDITABLE,
                 UI_LOBBY_HOST_BUTTON,
                 UI_LOBBY_JOIN_IP_EDITABLE,
                 UI_LOBBY_JOIN_BUTTON,
-                UI_MP_ROOM_START_BUTTON,
+                UI_LOBBY_SPECTATE_BUTTON,
+                UI_MP_ROOM_READY_BUTTON,
                 UI_MP_ROOM_LOBBY_BUTTON,
-                UI_MP_ROOM_PLAYER1_KICK,
-                UI_MP_ROOM_PLAYER2_KICK,
-                UI_MP_ROOM_PLAYER3_KICK,
-                UI_MP_ROOM_PLAYER4_KICK,
                 UI_MODAL_CONFIRM_BUTTON,
-            ],
+            ]
+            .iter()
+            .copied()
+            .chain(MP_ROOM_PLAYER_ROWS.iter().map(|row| row.kick))
+            .collect(),
             menu_screen_animations: VecDeque::new(),
             event_readers: None,
             menu_screen: GameMenuScreen::Loading,
@@ -302,6 +346,8 @@ enum GameMenuScreen {
     Loading,
     MainMenu,
     RestartMenu,
+    LevelResultsMenu,
+    CharacterMenu,
     LobbyMenu,
     MultiplayerRoomMenu,
     Hidden,
@@ -2,40 +2,26 @@ use gv_client_shared::ecs::resources::ConnectionStatus;
 
 use super::*;
 use crate::{ecs::resources::UiNetworkCommand, utils::ui::disconnect_reason_title};
-use gv_core::ecs::resources::net::MultiplayerRoomPlayer;
+use gv_core::{ecs::resources::net::MultiplayerRoomPlayer, net::DEFAULT_MAX_PLAYERS};
 
 const DISCONNECTED: &str = "MP_DISCONNECTED";
 const DISCONNECTING: &str = "MP_DISCONNECTING";
 
+// Layout of a room with the default size, larger rooms get their rows squeezed into the same space.
+const ROWS_TOP_Y: f32 = 300.0;
+const ROW_SPACING: f32 = 100.0;
+const ROW_HEIGHT: f32 = 72.0;
+const ROW_FONT_SIZE: f32 = 58.0;
+
 lazy_static! {
-    static ref MP_ROOM_MENU_ELEMENTS_HOST: &'static [&'static str] = &[
-        UI_MP_ROOM_START_BUTTON,
-        UI_MP_ROOM_LOBBY_BUTTON,
-        UI_MP_ROOM_PLAYER1_CONTAINER,
-        UI_MP_ROOM_PLAYER1_BG,
-        UI_MP_ROOM_PLAYER2_CONTAINER,
-        UI_MP_ROOM_PLAYER2_BG,
-        UI_MP_ROOM_PLAYER3_CONTAINER,
-        UI_MP_ROOM_PLAYER3_BG,
-        UI_MP_ROOM_PLAYER4_CONTAINER,
-        UI_MP_ROOM_PLAYER4_BG,
-    ];
-    static ref MP_ROOM_MENU_ELEMENTS_JOIN: &'static [&'static str] = &[
-        UI_MP_ROOM_LOBBY_BUTTON,
-        UI_MP_ROOM_PLAYER1_CONTAINER,
-        UI_MP_ROOM_PLAYER1_BG,
-        UI_MP_ROOM_PLAYER2_CONTAINER,
-        UI_MP_ROOM_PLAYER2_BG,
-        UI_MP_ROOM_PLAYER3_CONTAINER,
-        UI_MP_ROOM_PLAYER3_BG,
-        UI_MP_ROOM_PLAYER4_CONTAINER,
-        UI_MP_ROOM_PLAYER4_BG,
-    ];
+    static ref MP_ROOM_MENU_ELEMENTS_SHOWN: &'static [&'static str] =
+        &[UI_MP_ROOM_READY_BUTTON, UI_MP_ROOM_LOBBY_BUTTON];
 }
 
 pub struct MultiplayerRoomMenuScreen {
     initiated_disconnecting: bool,
     players: Vec<MultiplayerRoomPlayer>,
+    lobby_countdown: Option<u64>,
 }
 
 impl MultiplayerRoomMenuScreen {
@@ -43,48 +29,44 @@ impl MultiplayerRoomMenuScreen {
         Self {
             initiated_disconnecting: false,
             players: Vec::new(),
+            lobby_countdown: None,
         }
     }
 }
 
 impl MenuScreen for MultiplayerRoomMenuScreen {
     fn elements_to_show(&self, system_data: &MenuSystemData) -> Vec<MenuElement> {
-        if system_data.multiplayer_room_state.is_host {
-            MP_ROOM_MENU_ELEMENTS_HOST.to_vec()
-        } else {
-            MP_ROOM_MENU_ELEMENTS_JOIN.to_vec()
+        let mut elements = MP_ROOM_MENU_ELEMENTS_SHOWN.to_vec();
+        for row in room_rows(system_data) {
+            elements.push(row.container);
+            elements.push(row.background);
         }
+        // Spectators don't take part in the ready check.
+        if system_data.multiplayer_room_state.is_spectator {
+            elements.retain(|element| *element != UI_MP_ROOM_READY_BUTTON);
+        }
+        elements
     }
 
     fn elements_to_hide(&self, _system_data: &MenuSystemData) -> Vec<&'static str> {
-        vec![
-            UI_MP_ROOM_START_BUTTON,
+        let mut elements = vec![
+            UI_MP_ROOM_READY_BUTTON,
+            UI_MP_ROOM_COUNTDOWN,
             UI_MP_ROOM_LOBBY_BUTTON,
-            UI_MP_ROOM_PLAYER1_CONTAINER,
-            UI_MP_ROOM_PLAYER1_BG,
-            UI_MP_ROOM_PLAYER1_NUMBER,
-            UI_MP_ROOM_PLAYER1_NICKNAME,
-            UI_MP_ROOM_PLAYER1_KICK,
-            UI_MP_ROOM_PLAYER2_CONTAINER,
-            UI_MP_ROOM_PLAYER2_BG,
-            UI_MP_ROOM_PLAYER2_NUMBER,
-            UI_MP_ROOM_PLAYER2_NICKNAME,
-            UI_MP_ROOM_PLAYER2_KICK,
-            UI_MP_ROOM_PLAYER3_CONTAINER,
-            UI_MP_ROOM_PLAYER3_BG,
-            UI_MP_ROOM_PLAYER3_NUMBER,
-            UI_MP_ROOM_PLAYER3_NICKNAME,
-            UI_MP_ROOM_PLAYER3_KICK,
-            UI_MP_ROOM_PLAYER4_CONTAINER,
-            UI_MP_ROOM_PLAYER4_BG,
-            UI_MP_ROOM_PLAYER4_NUMBER,
-            UI_MP_ROOM_PLAYER4_NICKNAME,
-            UI_MP_ROOM_PLAYER4_KICK,
-        ]
+        ];
+        for row in MP_ROOM_PLAYER_ROWS.iter() {
+            elements.push(row.container);
+            elements.push(row.background);
+            elements.push(row.number);
+            elements.push(row.nickname);
+            elements.push(row.kick);
+        }
+        elements
     }
 
-    fn show(&mut self, _system_data: &mut MenuSystemData) {
+    fn show(&mut self, system_data: &mut MenuSystemData) {
         *self = Self::new();
+        layout_room_rows(system_data);
     }
 
     fn update(
@@ -125,24 +107,13 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
         }
 
         match (button_pressed, modal_window_id) {
-            (Some(UI_MP_ROOM_PLAYER1_KICK), _) => {
+            (Some(button), _) if button_is_kick(button) => {
+                let player_number = MP_ROOM_PLAYER_ROWS
+                    .iter()
+                    .position(|row| row.kick == button)
+                    .expect("Expected a row of a kick button");
                 system_data.ui_network_command.command =
-                    Some(UiNetworkCommand::Kick { player_number: 0 });
-                StateUpdate::None
-            }
-            (Some(UI_MP_ROOM_PLAYER2_KICK), _) => {
-                system_data.ui_network_command.command =
-                    Some(UiNetworkCommand::Kick { player_number: 1 });
-                StateUpdate::None
-            }
-            (Some(UI_MP_ROOM_PLAYER3_KICK), _) => {
-                system_data.ui_network_command.command =
-                    Some(UiNetworkCommand::Kick { player_number: 2 });
-                StateUpdate::None
-            }
-            (Some(UI_MP_ROOM_PLAYER4_KICK), _) => {
-                system_data.ui_network_command.command =
-                    Some(UiNetworkCommand::Kick { player_number: 3 });
+                    Some(UiNetworkCommand::Kick { player_number });
                 StateUpdate::None
             }
             (Some(UI_MP_ROOM_LOBBY_BUTTON), _) => {
@@ -163,8 +134,8 @@ impl MenuScreen for MultiplayerRoomMenuScreen {
                     }
                 }
             }
-            (Some(UI_MP_ROOM_START_BUTTON), _) => {
-                system_data.ui_network_command.command = Some(UiNetworkCommand::Start);
+            (Some(UI_MP_ROOM_READY_BUTTON), _) => {
+                system_data.ui_network_command.command = Some(UiNetworkCommand::ToggleReady);
                 StateUpdate::None
             }
             (Some(UI_MODAL_CONFIRM_BUTTON), Some(DISCONNECTED)) => {
@@ -183,36 +154,45 @@ impl MultiplayerRoomMenuScreen {
 
         if self.players != system_data.multiplayer_game_state.players {
             self.players = system_data.multiplayer_game_state.players.clone();
-            #[rustfmt::skip]
-            let rows = [
-                (UI_MP_ROOM_PLAYER1_NUMBER, UI_MP_ROOM_PLAYER1_NICKNAME, UI_MP_ROOM_PLAYER1_KICK),
-                (UI_MP_ROOM_PLAYER2_NUMBER, UI_MP_ROOM_PLAYER2_NICKNAME, UI_MP_ROOM_PLAYER2_KICK),
-                (UI_MP_ROOM_PLAYER3_NUMBER, UI_MP_ROOM_PLAYER3_NICKNAME, UI_MP_ROOM_PLAYER3_KICK),
-                (UI_MP_ROOM_PLAYER4_NUMBER, UI_MP_ROOM_PLAYER4_NICKNAME, UI_MP_ROOM_PLAYER4_KICK),
-            ];
-            for (i, row) in rows.iter().enumerate() {
-                {
-                    if let Some(player) = self.players.get(i) {
-                        let player_nickname_text = system_data
-                            .ui_finder
-                            .get_ui_text_mut(&mut system_data.ui_texts, row.1)
-                            .expect("Expected a player nickname text component");
-                        *player_nickname_text = player.nickname.clone();
-
-                        elements_to_show.push(row.0);
-                        elements_to_show.push(row.1);
-                        if system_data.multiplayer_room_state.is_host && !player.is_host {
-                            elements_to_show.push(row.2);
-                        }
+            for (i, row) in room_rows(system_data).enumerate() {
+                if let Some(player) = self.players.get(i) {
+                    let player_nickname_text = system_data
+                        .ui_finder
+                        .get_ui_text_mut(&mut system_data.ui_texts, row.nickname)
+                        .expect("Expected a player nickname text component");
+                    *player_nickname_text = if player.is_ready {
+                        format!("{} (ready)", player.nickname)
                     } else {
-                        elements_to_hide.push(row.0);
-                        elements_to_hide.push(row.1);
-                        elements_to_hide.push(row.2);
+                        player.nickname.clone()
+                    };
+
+                    elements_to_show.push(row.number);
+                    elements_to_show.push(row.nickname);
+                    if system_data.multiplayer_room_state.is_host && !player.is_host {
+                        elements_to_show.push(row.kick);
                     }
+                } else {
+                    elements_to_hide.push(row.number);
+                    elements_to_hide.push(row.nickname);
+                    elements_to_hide.push(row.kick);
                 }
             }
         }
 
+        if self.lobby_countdown != system_data.multiplayer_room_state.lobby_countdown {
+            self.lobby_countdown = system_data.multiplayer_room_state.lobby_countdown;
+            if let Some(secs_left) = self.lobby_countdown {
+                let countdown_text = system_data
+                    .ui_finder
+                    .get_ui_text_mut(&mut system_data.ui_texts, UI_MP_ROOM_COUNTDOWN)
+                    .expect("Expected a countdown text component");
+                *countdown_text = format!("The game starts in {}...", secs_left);
+                elements_to_show.push(UI_MP_ROOM_COUNTDOWN);
+            } else {
+                elements_to_hide.push(UI_MP_ROOM_COUNTDOWN);
+            }
+        }
+
         if elements_to_hide.is_empty() && elements_to_show.is_empty() {
             StateUpdate::None
         } else {
@@ -223,3 +203,32 @@ impl MultiplayerRoomMenuScreen {
         }
     }
 }
+
+fn room_rows(system_data: &MenuSystemData) -> impl Iterator<Item = &'static MpRoomPlayerRow> {
+    MP_ROOM_PLAYER_ROWS
+        .iter()
+        .take(system_data.multiplayer_room_state.max_players)
+}
+
+fn button_is_kick(button: &str) -> bool {
+    MP_ROOM_PLAYER_ROWS.iter().any(|row| row.kick == button)
+}
+
+/// Spreads the rows of the configured room size over the space of the default one.
+fn layout_room_rows(system_data: &mut MenuSystemData) {
+    let max_players = system_data.multiplayer_room_state.max_players;
+    let scale = DEFAULT_MAX_PLAYERS as f32 / max_players.max(DEFAULT_MAX_PLAYERS) as f32;
+    for (i, row) in room_rows(system_data).enumerate() {
+        if let Some((_, transform)) = system_data.ui_finder.find_with_mut_transform(row.container) {
+            transform.local_y = ROWS_TOP_Y - ROW_SPACING * scale * i as f32;
+            transform.height = ROW_HEIGHT * scale;
+        }
+        for label in &[row.number, row.nickname] {
+            if let Some(entity) = system_data.ui_finder.find(label) {
+                if let Some(ui_text) = system_data.ui_texts.get_mut(entity) {
+                    ui_text.font_size = ROW_FONT_SIZE * scale;
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod animation;
 mod camera_translation;
 mod client_network;
 mod custom_sprite_sorting;
+mod experience;
 mod game_updates_broadcasting;
 mod hud;
 mod imgui_network_debug_info;
@@ -9,17 +10,20 @@ mod input;
 mod menu;
 mod overlay;
 mod particle;
+mod spectator_camera;
 
 pub use self::{
     animation::AnimationSystem,
     camera_translation::CameraTranslationSystem,
     client_network::ClientNetworkSystem,
     custom_sprite_sorting::{CustomSpriteSortingSystem, SpriteOrdering},
+    experience::ExperienceSystem,
     game_updates_broadcasting::GameUpdatesBroadcastingSystem,
-    hud::HealthUiSystem,
+    hud::{HealthUiSystem, SpellUiSystem},
     imgui_network_debug_info::ImguiNetworkDebugInfoSystem,
     input::InputSystem,
     menu::MenuSystem,
     overlay::OverlaySystem,
     particle::ParticleSystem,
+    spectator_camera::SpectatorCameraSystem,
 };
@@ -11,6 +11,7 @@ use crate::ecs::system_data::ui::UiFinderMut;
 pub struct OverlaySystem;
 
 const UI_WAITING_FOR_PLAYERS_BORDER_CONTAINER: &str = "ui_waiting_for_players_border_container";
+const UI_WAITING_FOR_PLAYERS_TITLE: &str = "ui_waiting_for_players_title";
 const UI_WAITING_FOR_PLAYERS_ROW_1: &str = "ui_waiting_for_players_row_1";
 const UI_WAITING_FOR_PLAYERS_ROW_2: &str = "ui_waiting_for_players_row_2";
 const UI_WAITING_FOR_PLAYERS_ROW_3: &str = "ui_waiting_for_players_row_3";
@@ -57,6 +58,14 @@ impl<'s> System<'s> for OverlaySystem {
                     .find(UI_WAITING_FOR_PLAYERS_BORDER_CONTAINER)
                     .expect("Expected ui_waiting_for_players_border_container ui element"),
             );
+            let title = ui_finder
+                .get_ui_text_mut(&mut ui_texts, UI_WAITING_FOR_PLAYERS_TITLE)
+                .expect("Expected ui_waiting_for_players_title ui element");
+            *title = if lagging_players.is_empty() && multiplayer_game_state.is_paused_by_admin {
+                "Paused by the server".to_owned()
+            } else {
+                "Waiting for players...".to_owned()
+            };
 
             let mut update_name = |ui_id: &'static str, lagging_player_index: usize| {
                 let player_text_entity = ui_finder
@@ -0,0 +1,169 @@
+use amethyst::{
+    core::transform::{Parent, Transform},
+    ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteStorage},
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+};
+
+use gv_client_shared::ecs::resources::MultiplayerRoomState;
+use gv_core::ecs::{
+    components::{EntityNetMetadata, Player},
+    resources::{GameEngineState, GameLevelState},
+    system_data::time::GameTimeService,
+};
+
+use std::collections::HashSet;
+
+const FREE_CAMERA_SPEED: f32 = 600.0;
+
+/// Lets spectators switch between the players they follow or move the camera on their own.
+#[derive(Default)]
+pub struct SpectatorCameraSystem {
+    followed_player: Option<Entity>,
+    free_camera_anchor: Option<Entity>,
+    down_actions: HashSet<String>,
+}
+
+impl<'s> System<'s> for SpectatorCameraSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        ReadExpect<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, GameEngineState>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, MultiplayerRoomState>,
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, EntityNetMetadata>,
+        WriteStorage<'s, Parent>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            input,
+            game_engine_state,
+            game_level_state,
+            multiplayer_room_state,
+            entities,
+            cameras,
+            players,
+            entity_net_metadata,
+            mut parents,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        if !multiplayer_room_state.is_spectator || *game_engine_state != GameEngineState::Playing {
+            self.followed_player = None;
+            if let Some(free_camera_anchor) = self.free_camera_anchor.take() {
+                entities
+                    .delete(free_camera_anchor)
+                    .expect("Expected to delete a free camera anchor");
+            }
+            return;
+        }
+
+        let camera_entity =
+            if let Some((camera_entity, _, _)) = (&entities, &cameras, &parents).join().next() {
+                camera_entity
+            } else {
+                return;
+            };
+        let camera_parent = parents
+            .get(camera_entity)
+            .expect("Expected a camera Parent")
+            .entity;
+        if self.free_camera_anchor != Some(camera_parent) {
+            self.followed_player = Some(camera_parent);
+        }
+
+        if self.is_toggled(&input, "spectator_next_player") {
+            let mut followed_players = (&entities, &players, &entity_net_metadata)
+                .join()
+                .map(|(entity, _, entity_net_metadata)| (entity_net_metadata.id, entity))
+                .collect::<Vec<_>>();
+            followed_players.sort_by_key(|(entity_net_id, _)| *entity_net_id);
+            let next_player_index = followed_players
+                .iter()
+                .position(|(_, entity)| Some(*entity) == self.followed_player)
+                .map_or(0, |player_index| player_index + 1);
+            let next_player = followed_players
+                .get(next_player_index)
+                .or_else(|| followed_players.first())
+                .map(|(_, entity)| *entity);
+
+            if let Some(next_player) = next_player {
+                self.follow(&entities, &mut parents, camera_entity, next_player);
+            }
+        }
+
+        if self.is_toggled(&input, "spectator_free_camera") {
+            if self.free_camera_anchor.is_some() {
+                if let Some(followed_player) = self.followed_player {
+                    self.follow(&entities, &mut parents, camera_entity, followed_player);
+                }
+            } else {
+                let anchor_transform = transforms.get(camera_parent).cloned().unwrap_or_default();
+                let free_camera_anchor = entities
+                    .build_entity()
+                    .with(anchor_transform, &mut transforms)
+                    .build();
+                parents
+                    .insert(camera_entity, Parent::new(free_camera_anchor))
+                    .expect("Expected to insert a camera Parent");
+                self.free_camera_anchor = Some(free_camera_anchor);
+            }
+        }
+
+        if let Some(free_camera_anchor) = self.free_camera_anchor {
+            let (x, y) = (
+                input.axis_value("horizontal").unwrap_or(0.0),
+                input.axis_value("vertical").unwrap_or(0.0),
+            );
+            let distance = FREE_CAMERA_SPEED * game_time_service.engine_time().delta_seconds();
+            let half_size = game_level_state.dimensions_half_size();
+
+            let anchor_transform = transforms
+                .get_mut(free_camera_anchor)
+                .expect("Expected a Transform of a free camera anchor");
+            let translation = anchor_transform.translation_mut();
+            translation.x = (translation.x + x * distance)
+                .max(-half_size.x)
+                .min(half_size.x);
+            translation.y = (translation.y + y * distance)
+                .max(-half_size.y)
+                .min(half_size.y);
+        }
+    }
+}
+
+impl SpectatorCameraSystem {
+    fn follow(
+        &mut self,
+        entities: &Entities,
+        parents: &mut WriteStorage<Parent>,
+        camera_entity: Entity,
+        player: Entity,
+    ) {
+        parents
+            .insert(camera_entity, Parent::new(player))
+            .expect("Expected to insert a camera Parent");
+        self.followed_player = Some(player);
+        if let Some(free_camera_anchor) = self.free_camera_anchor.take() {
+            entities
+                .delete(free_camera_anchor)
+                .expect("Expected to delete a free camera anchor");
+        }
+    }
+
+    fn is_toggled(&mut self, input: &InputHandler<StringBindings>, action: &str) -> bool {
+        if input.action_is_down(action).unwrap_or_default() {
+            self.down_actions.insert(action.to_owned())
+        } else {
+            self.down_actions.remove(action);
+            false
+        }
+    }
+}
@@ -13,7 +13,7 @@ use amethyst::{
         frame_limiter::FrameRateLimitStrategy, transform::TransformBundle, HideHierarchySystemDesc,
     },
     input::{InputBundle, StringBindings},
-    network::simulation::laminar::{LaminarConfig, LaminarNetworkBundle, LaminarSocket},
+    network::simulation::laminar::{LaminarConfig, LaminarSocket},
     prelude::{Application, GameDataBuilder, SystemDesc},
     renderer::{
         plugins::{RenderFlat2D, RenderToWindow},
@@ -32,15 +32,29 @@ use std::{
 };
 
 use gv_animation_prefabs::{AnimationId, GameSpriteAnimationPrefab};
-use gv_client_shared::{ecs::resources::MultiplayerRoomState, settings::Settings};
-use gv_core::ecs::resources::{
-    net::PlayersNetStatus,
-    world::{ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate},
+use gv_client_shared::{
+    ecs::resources::MultiplayerRoomState, profile::Profile, settings::Settings,
+};
+use gv_core::{
+    ecs::resources::{
+        net::PlayersNetStatus,
+        world::{ClientWorldUpdates, FramedUpdates, ReceivedServerWorldUpdate},
+    },
+    net::{world_update_encoding::WorldUpdatePositionsHistory, CLIENT_RECEIVE_BUFFER_MAX_SIZE},
+    replay::Replay,
+    FRAME_RATE,
 };
 use gv_game::{
     build_game_logic_systems,
-    ecs::systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
+    ecs::{
+        resources::{register_definitions, ReplayPlayback, ReplayRecorder},
+        systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
+    },
     states::LoadingState,
+    utils::net::{
+        simulation::NetworkConditions,
+        transport::{LaminarTransport, TransportBundle},
+    },
 };
 
 use crate::{
@@ -112,13 +126,75 @@ fn main() -> amethyst::Result<()> {
     #[cfg(feature = "profiler")]
     thread_profiler::disable_profiler();
 
-    change_to_resources_parent_dir()?;
-
-    let _cli_matches = clap::App::new("grumpy_visitors")
+    let cli_matches = clap::App::new("grumpy_visitors")
         .version("0.1")
         .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
         .about("A prototype of a top-down EvilInvasion-like 2D arcade/action")
+        .arg(
+            clap::Arg::with_name("record-replay")
+                .long("record-replay")
+                .value_name("FILE")
+                .help("Records single player games to the file")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("replay")
+                .long("replay")
+                .value_name("FILE")
+                .help("Plays a recorded game back (Space pauses, Right steps a single frame)")
+                .takes_value(true)
+                .conflicts_with("record-replay"),
+        )
+        .arg(
+            clap::Arg::with_name("simulate-network")
+                .long("simulate-network")
+                .value_name("CONDITIONS")
+                .help(
+                    "Simulates a bad network, e.g. latency=150,jitter=30,loss=5,duplication=1,\
+                     reordering=2 (milliseconds and percents)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("simulate-network-seed")
+                .long("simulate-network-seed")
+                .value_name("SEED")
+                .help("Makes --simulate-network lose and delay the same datagrams on every run")
+                .takes_value(true)
+                .requires("simulate-network"),
+        )
         .get_matches();
+    // Replay paths are relative to the original working directory.
+    let working_dir = env::current_dir()?;
+    let record_replay_path = cli_matches
+        .value_of("record-replay")
+        .map(|path| working_dir.join(path));
+    let replay_path = cli_matches
+        .value_of("replay")
+        .map(|path| working_dir.join(path));
+    let network_conditions = cli_matches
+        .value_of("simulate-network")
+        .map(|conditions| conditions.parse::<NetworkConditions>())
+        .transpose()
+        .map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid --simulate-network: {}", err),
+            )
+        })?
+        .unwrap_or_default();
+    let network_seed = cli_matches
+        .value_of("simulate-network-seed")
+        .map(|seed| seed.parse::<u64>())
+        .transpose()
+        .map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid --simulate-network-seed: {}", err),
+            )
+        })?;
+
+    change_to_resources_parent_dir()?;
 
     let socket_addr = "0.0.0.0:0";
 
@@ -139,14 +215,23 @@ fn main() -> amethyst::Result<()> {
         })
         .unwrap_or_default();
     Logger::from_config(logging_config).start();
+    if network_conditions.is_enabled() {
+        log::warn!("Simulating network conditions: {}", network_conditions);
+    }
 
     let settings = Settings::new()?;
     let display_config = settings.display().clone();
 
+    let replay = replay_path
+        .map(|path| Replay::load(&path))
+        .transpose()?;
+
     let bindings = settings.bindings().clone();
     let input_bundle = InputBundle::<StringBindings>::new().with_bindings(bindings);
 
     let mut builder = Application::build("./", LoadingState::default())?;
+    register_definitions(&mut builder.world)?;
+    builder.world.insert(Profile::load(settings.config_dir()));
     builder.world.insert(settings);
     builder.world.insert(ServerCommand::new());
 
@@ -159,20 +244,26 @@ fn main() -> amethyst::Result<()> {
     builder.world.insert(LastAcknowledgedUpdate {
         id: 0,
         frame_number: 0,
+        positions_id: 0,
+        positions: WorldUpdatePositionsHistory::default(),
     });
     builder
         .world
         .insert(FramedUpdates::<ReceivedServerWorldUpdate>::default());
 
     let laminar_config = LaminarConfig {
-        receive_buffer_max_size: 14_500,
+        receive_buffer_max_size: CLIENT_RECEIVE_BUFFER_MAX_SIZE,
         ..LaminarConfig::default()
     };
 
     let socket = LaminarSocket::bind_with_config(socket_addr, laminar_config)?;
 
     let mut game_data_builder = GameDataBuilder::default()
-        .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+        .with_bundle(
+            TransportBundle::new(LaminarTransport::new(socket)?)
+                .with_network_conditions(network_conditions)
+                .with_network_seed(network_seed),
+        )?
         .with(
             NetConnectionManagerDesc::default().build(&mut builder.world),
             "net_connection_manager_system",
@@ -188,22 +279,45 @@ fn main() -> amethyst::Result<()> {
         .with(InputSystem::default(), "mouse_system", &["input_system"])
         .with(MenuSystem::new(), "menu_system", &[]);
 
-    game_data_builder = build_game_logic_systems(game_data_builder, &mut builder.world, false)?
+    game_data_builder = build_game_logic_systems(game_data_builder, &mut builder.world, false)?;
+    if let Some(path) = record_replay_path {
+        builder.world.insert(ReplayRecorder::new(path));
+    }
+    if let Some(replay) = replay {
+        log::info!(
+            "Playing a replay of {} frames (seed {})",
+            replay.frames.len(),
+            replay.seed
+        );
+        builder.world.insert(ReplayPlayback::new(replay));
+    }
+
+    game_data_builder = game_data_builder
         .with(
             GameUpdatesBroadcastingSystem::default(),
             "game_updates_broadcasting_system",
             &["action_system"],
         )
+        .with(
+            ExperienceSystem::default(),
+            "experience_system",
+            &["level_objective_system"],
+        )
         .with(ParticleSystem, "particle_system", &["missile_dying_system"])
         .with(
             WorldPositionTransformSystem,
             "world_position_transform_system",
             &["particle_system"],
         )
+        .with(
+            SpectatorCameraSystem::default(),
+            "spectator_camera_system",
+            &["world_position_transform_system"],
+        )
         .with(
             CameraTranslationSystem,
             "camera_translation_system",
-            &["world_position_transform_system"],
+            &["spectator_camera_system"],
         )
         .with_bundle(TransformBundle::new().with_dep(&[
             "world_position_transform_system",
@@ -220,6 +334,7 @@ fn main() -> amethyst::Result<()> {
             &["parent_hierarchy_system"],
         )
         .with(HealthUiSystem, "health_ui_system", &["action_system"])
+        .with(SpellUiSystem, "spell_ui_system", &["action_system"])
         .with_bundle(UiBundle::<StringBindings>::new())?
         .with(
             AnimationSystem,
@@ -245,7 +360,9 @@ fn main() -> amethyst::Result<()> {
                 )
                 .with_plugin(RenderFlat2D::default())
                 .with_plugin(PaintMagePlugin::default())
+                .with_plugin(AreaSpellPlugin::default())
                 .with_plugin(MissilePlugin::default())
+                .with_plugin(EnemyProjectilePlugin::default())
                 .with_plugin(SpellParticlePlugin::default())
                 .with_plugin(MobHealthPlugin::default())
                 .with_plugin(HealthUiPlugin::default())
@@ -254,7 +371,7 @@ fn main() -> amethyst::Result<()> {
         )?;
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        .with_frame_limit(FrameRateLimitStrategy::Yield, FRAME_RATE)
         .build(game_data_builder)?;
 
     game.run();
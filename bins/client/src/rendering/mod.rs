@@ -1,9 +1,13 @@
+pub use area_spell::AreaSpellPlugin;
+pub use enemy_projectile::EnemyProjectilePlugin;
 pub use health_ui::HealthUiPlugin;
 pub use missile::MissilePlugin;
 pub use mob_health::MobHealthPlugin;
 pub use paint_mage::PaintMagePlugin;
 pub use spell_particle::SpellParticlePlugin;
 
+mod area_spell;
+mod enemy_projectile;
 mod health_ui;
 mod missile;
 mod mob_health;
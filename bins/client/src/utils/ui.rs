@@ -1,4 +1,7 @@
-use gv_core::net::server_message::DisconnectReason;
+use gv_core::{
+    net::{server_message::DisconnectReason, BUILD_HASH, PROTOCOL_VERSION},
+    FRAME_RATE,
+};
 
 pub fn disconnect_reason_title(disconnect_reason: DisconnectReason) -> String {
     match disconnect_reason {
@@ -10,5 +13,15 @@ pub fn disconnect_reason_title(disconnect_reason: DisconnectReason) -> String {
         DisconnectReason::ServerCrashed(exit_code) => {
             format!("The server unexpectedly closed: {}", exit_code)
         }
+        DisconnectReason::IncompatibleVersion {
+            server_protocol_version,
+        } => format!(
+            "Incompatible server version: protocol {} (yours is {}, build {}), use the same build",
+            server_protocol_version, PROTOCOL_VERSION, BUILD_HASH
+        ),
+        DisconnectReason::IncompatibleTickRate { server_tick_rate } => format!(
+            "The server runs at {} frames per second, yours runs at {}",
+            server_tick_rate, FRAME_RATE
+        ),
     }
 }
@@ -0,0 +1,607 @@
+use amethyst::Error;
+use clap::ArgMatches;
+use toml::{value::Table, Value};
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    io::ErrorKind,
+    net::SocketAddr,
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+use gv_core::{
+    ecs::resources::world::{LAG_COMPENSATION_FRAMES_LIMIT, SAVED_WORLD_STATES_LIMIT},
+    net::{
+        fragmentation::{max_message_size, DEFAULT_MTU, MAX_MTU},
+        DEFAULT_MAX_PLAYERS, MAX_PLAYERS_LIMIT,
+    },
+    FRAME_RATE,
+};
+use gv_game::ecs::resources::{
+    Campaign, GameMode, MonsterDefinitions, CAMPAIGN_PATH, MONSTER_DEFINITIONS_PATH,
+};
+
+pub const SERVER_CONFIG_PATH: &str = "server_config.toml";
+
+/// CLI arguments of the options that the admin console can change while a server is running.
+pub const RUNTIME_OPTIONS: &[&str] = &[
+    "ready-countdown-secs",
+    "start-players",
+    "start-countdown-secs",
+    "game-over-delay-secs",
+    "heartbeat-interval",
+    "report-status-interval",
+    "broadcast-interval",
+    "lag-compensation-frames",
+    "world-update-budget",
+];
+
+/// Keys that can be set in a config file, grouped by their sections ("" stands for the root).
+const CONFIG_KEYS: &[(&str, &[&str])] = &[
+    ("", &["addr", "tick_rate", "console"]),
+    ("room", &["max_players", "ready_countdown_secs"]),
+    ("game", &["mode", "campaign_level"]),
+    (
+        "dedicated",
+        &[
+            "enabled",
+            "start_players",
+            "start_countdown_secs",
+            "game_over_delay_secs",
+        ],
+    ),
+    (
+        "network",
+        &[
+            "heartbeat_interval_frames",
+            "report_players_status_interval_frames",
+            "broadcast_interval_frames",
+            "lag_compensation_frames",
+            "idle_connection_timeout_ms",
+            "receive_buffer_max_size",
+            "world_update_budget_bytes",
+            "mtu",
+        ],
+    ),
+    ("metrics", &["addr"]),
+];
+
+/// Is read from server_config.toml, every value can be overridden with a CLI argument.
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub addr: SocketAddr,
+    /// Frames simulated per second, has to match the frame rate of clients (see
+    /// `gv_core::FRAME_RATE`), as gameplay runs with a fixed timestep. Is reported in Handshake.
+    pub tick_rate: u32,
+    /// Enables the admin console that reads commands from stdin.
+    pub console: bool,
+    /// Players that can join a room, spectators aren't counted.
+    pub max_players: usize,
+    /// Gives players some time to change their minds before a game starts.
+    pub ready_countdown: Duration,
+    pub game_mode: GameMode,
+    pub dedicated: bool,
+    /// Defaults to `max_players`.
+    pub start_players: Option<usize>,
+    pub start_countdown: Option<Duration>,
+    /// Gives clients some time to see how a game has ended before returning them to the room.
+    pub game_over_delay: Duration,
+    pub heartbeat_interval_frames: u64,
+    pub report_players_status_interval_frames: u64,
+    pub broadcast_interval_frames: u64,
+    /// How many frames late client actions can be and still get applied.
+    pub lag_compensation_frames: u64,
+    pub idle_connection_timeout: Duration,
+    pub receive_buffer_max_size: usize,
+    /// World updates are split into messages of at most this many bytes of encoded updates.
+    pub world_update_budget_bytes: usize,
+    /// Messages larger than this are split into fragments.
+    pub mtu: usize,
+    /// Metrics in the Prometheus text format are served at this address if it's set.
+    pub metrics_addr: Option<SocketAddr>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:3455"
+                .parse()
+                .expect("Expected a valid default address"),
+            tick_rate: FRAME_RATE,
+            console: false,
+            max_players: DEFAULT_MAX_PLAYERS,
+            ready_countdown: Duration::from_secs(5),
+            game_mode: GameMode::Survival,
+            dedicated: false,
+            start_players: None,
+            start_countdown: None,
+            game_over_delay: Duration::from_secs(3),
+            heartbeat_interval_frames: 2,
+            report_players_status_interval_frames: 50,
+            broadcast_interval_frames: 5,
+            lag_compensation_frames: LAG_COMPENSATION_FRAMES_LIMIT as u64,
+            idle_connection_timeout: Duration::from_secs(5),
+            receive_buffer_max_size: 14_500,
+            world_update_budget_bytes: 1200,
+            mtu: DEFAULT_MTU,
+            metrics_addr: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads a config file (if it exists) and applies CLI overrides on top of it.
+    pub fn load(cli_matches: &ArgMatches) -> amethyst::Result<Self> {
+        let path = cli_matches.value_of("config").unwrap_or(SERVER_CONFIG_PATH);
+        let file = match fs::read_to_string(path) {
+            Ok(contents) => read_config_file(&contents)
+                .map_err(|err| Error::from_string(format!("Invalid {}: {}", path, err)))?,
+            Err(err) if err.kind() == ErrorKind::NotFound && !cli_matches.is_present("config") => {
+                log::info!("{} isn't found, using the defaults", path);
+                Table::new()
+            }
+            Err(err) => {
+                return Err(Error::from_string(format!(
+                    "Failed to read {}: {}",
+                    path, err
+                )))
+            }
+        };
+        let sources = ConfigSources {
+            path,
+            file,
+            cli_matches,
+        };
+
+        let mut config = Self::default();
+        sources.read(&mut config.addr, "", "addr", "addr")?;
+        sources.read(&mut config.tick_rate, "", "tick_rate", "tick-rate")?;
+        config.console = cli_matches.is_present("console");
+        if !config.console {
+            sources.read(&mut config.console, "", "console", "console")?;
+        }
+
+        sources.read(
+            &mut config.max_players,
+            "room",
+            "max_players",
+            "max-players",
+        )?;
+        sources.read_secs(
+            &mut config.ready_countdown,
+            "room",
+            "ready_countdown_secs",
+            "ready-countdown-secs",
+        )?;
+
+        let mut mode = "survival".to_owned();
+        sources.read(&mut mode, "game", "mode", "game-mode")?;
+        let campaign_level = sources.get("game", "campaign_level", "campaign-level")?;
+        config.game_mode = match (mode.as_str(), campaign_level) {
+            ("survival", None) => GameMode::Survival,
+            ("survival", Some(_)) => {
+                return Err(Error::from_string(
+                    "A campaign level can be selected only in the campaign mode",
+                ))
+            }
+            ("campaign", level_index) => GameMode::Campaign {
+                level_index: level_index.unwrap_or(0),
+            },
+            (mode, _) => {
+                return Err(Error::from_string(format!(
+                    "Unknown game mode \"{}\", expected \"survival\" or \"campaign\"",
+                    mode
+                )))
+            }
+        };
+
+        config.dedicated = cli_matches.is_present("dedicated");
+        if !config.dedicated {
+            sources.read(&mut config.dedicated, "dedicated", "enabled", "dedicated")?;
+        }
+        config.start_players = sources.get("dedicated", "start_players", "start-players")?;
+        config.start_countdown = sources
+            .get("dedicated", "start_countdown_secs", "start-countdown-secs")?
+            .map(Duration::from_secs);
+        sources.read_secs(
+            &mut config.game_over_delay,
+            "dedicated",
+            "game_over_delay_secs",
+            "game-over-delay-secs",
+        )?;
+
+        sources.read(
+            &mut config.heartbeat_interval_frames,
+            "network",
+            "heartbeat_interval_frames",
+            "heartbeat-interval",
+        )?;
+        sources.read(
+            &mut config.report_players_status_interval_frames,
+            "network",
+            "report_players_status_interval_frames",
+            "report-status-interval",
+        )?;
+        sources.read(
+            &mut config.broadcast_interval_frames,
+            "network",
+            "broadcast_interval_frames",
+            "broadcast-interval",
+        )?;
+        sources.read(
+            &mut config.lag_compensation_frames,
+            "network",
+            "lag_compensation_frames",
+            "lag-compensation-frames",
+        )?;
+        if let Some(timeout_ms) =
+            sources.get("network", "idle_connection_timeout_ms", "idle-timeout-ms")?
+        {
+            config.idle_connection_timeout = Duration::from_millis(timeout_ms);
+        }
+        sources.read(
+            &mut config.receive_buffer_max_size,
+            "network",
+            "receive_buffer_max_size",
+            "receive-buffer-size",
+        )?;
+        sources.read(
+            &mut config.world_update_budget_bytes,
+            "network",
+            "world_update_budget_bytes",
+            "world-update-budget",
+        )?;
+        sources.read(&mut config.mtu, "network", "mtu", "mtu")?;
+        config.metrics_addr = sources.get("metrics", "addr", "metrics-addr")?;
+
+        config.validate()?;
+        if let GameMode::Campaign { level_index } = config.game_mode {
+            let monster_definitions =
+                MonsterDefinitions::load(Path::new(MONSTER_DEFINITIONS_PATH))?;
+            let campaign = Campaign::load(Path::new(CAMPAIGN_PATH), &monster_definitions)?;
+            check_range(
+                "game.campaign_level",
+                level_index,
+                0,
+                campaign.levels.len() - 1,
+            )?;
+        }
+        Ok(config)
+    }
+
+    /// Changes one of the RUNTIME_OPTIONS, the config stays the same if a new value is invalid.
+    pub fn set(&mut self, option: &str, value: &str) -> amethyst::Result<()> {
+        let mut config = self.clone();
+        match option {
+            "ready-countdown-secs" => {
+                config.ready_countdown = Duration::from_secs(parse_option(option, value)?)
+            }
+            "start-players" => config.start_players = Some(parse_option(option, value)?),
+            "start-countdown-secs" => {
+                config.start_countdown = Some(Duration::from_secs(parse_option(option, value)?))
+            }
+            "game-over-delay-secs" => {
+                config.game_over_delay = Duration::from_secs(parse_option(option, value)?)
+            }
+            "heartbeat-interval" => config.heartbeat_interval_frames = parse_option(option, value)?,
+            "report-status-interval" => {
+                config.report_players_status_interval_frames = parse_option(option, value)?
+            }
+            "broadcast-interval" => config.broadcast_interval_frames = parse_option(option, value)?,
+            "lag-compensation-frames" => {
+                config.lag_compensation_frames = parse_option(option, value)?
+            }
+            "world-update-budget" => {
+                config.world_update_budget_bytes = parse_option(option, value)?
+            }
+            _ => {
+                return Err(Error::from_string(format!(
+                    "{} can't be changed while a server is running, expected one of: {}",
+                    option,
+                    RUNTIME_OPTIONS.join(", ")
+                )))
+            }
+        }
+        config.validate()?;
+        *self = config;
+        Ok(())
+    }
+
+    /// A game starts once the room has at least this many players and all of them are ready.
+    pub fn start_players(&self) -> usize {
+        self.start_players.unwrap_or(self.max_players)
+    }
+
+    /// Client actions that arrive later than this get the server paused.
+    pub fn pause_frame_threshold(&self) -> u64 {
+        self.lag_compensation_frames + self.lag_compensation_frames / 2
+    }
+
+    fn validate(&self) -> amethyst::Result<()> {
+        if self.tick_rate != FRAME_RATE {
+            return Err(Error::from_string(format!(
+                "tick_rate must match the frame rate of clients ({}), got {}",
+                FRAME_RATE, self.tick_rate
+            )));
+        }
+        check_range("room.max_players", self.max_players, 1, MAX_PLAYERS_LIMIT)?;
+        if let Some(start_players) = self.start_players {
+            if !self.dedicated {
+                return Err(Error::from_string(
+                    "dedicated.start_players can be set only for a dedicated server",
+                ));
+            }
+            check_range(
+                "dedicated.start_players",
+                start_players,
+                1,
+                self.max_players,
+            )?;
+        }
+        if self.start_countdown.is_some() && !self.dedicated {
+            return Err(Error::from_string(
+                "dedicated.start_countdown_secs can be set only for a dedicated server",
+            ));
+        }
+        check_range(
+            "network.heartbeat_interval_frames",
+            self.heartbeat_interval_frames,
+            1,
+            u64::from(self.tick_rate),
+        )?;
+        check_range(
+            "network.report_players_status_interval_frames",
+            self.report_players_status_interval_frames,
+            1,
+            u64::from(self.tick_rate) * 10,
+        )?;
+        check_range(
+            "network.broadcast_interval_frames",
+            self.broadcast_interval_frames,
+            1,
+            self.lag_compensation_frames,
+        )?;
+        // Rolling back further than the stored world states isn't possible.
+        check_range(
+            "network.lag_compensation_frames",
+            self.lag_compensation_frames,
+            1,
+            SAVED_WORLD_STATES_LIMIT as u64 / 2,
+        )?;
+        check_range(
+            "network.idle_connection_timeout_ms",
+            self.idle_connection_timeout.as_millis(),
+            1000,
+            60_000,
+        )?;
+        check_range(
+            "network.receive_buffer_max_size",
+            self.receive_buffer_max_size,
+            1452,
+            65_507,
+        )?;
+        check_range("network.mtu", self.mtu, 576, MAX_MTU)?;
+        // Leaves some space for the rest of a message, so that it's still possible to send it.
+        check_range(
+            "network.world_update_budget_bytes",
+            self.world_update_budget_bytes,
+            256,
+            max_message_size(self.mtu) - 100,
+        )?;
+        Ok(())
+    }
+}
+
+/// Is written in the format of the config file, so it can be copied into one.
+impl Display for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "addr = \"{}\"", self.addr)?;
+        writeln!(f, "tick_rate = {}", self.tick_rate)?;
+        writeln!(f, "console = {}", self.console)?;
+        writeln!(f, "\n[room]")?;
+        writeln!(f, "max_players = {}", self.max_players)?;
+        writeln!(
+            f,
+            "ready_countdown_secs = {}",
+            self.ready_countdown.as_secs()
+        )?;
+        writeln!(f, "\n[game]")?;
+        match self.game_mode {
+            GameMode::Survival => writeln!(f, "mode = \"survival\"")?,
+            GameMode::Campaign { level_index } => {
+                writeln!(f, "mode = \"campaign\"")?;
+                writeln!(f, "campaign_level = {}", level_index)?;
+            }
+        }
+        writeln!(f, "\n[dedicated]")?;
+        writeln!(f, "enabled = {}", self.dedicated)?;
+        if self.dedicated {
+            writeln!(f, "start_players = {}", self.start_players())?;
+        }
+        if let Some(start_countdown) = self.start_countdown {
+            writeln!(f, "start_countdown_secs = {}", start_countdown.as_secs())?;
+        }
+        writeln!(
+            f,
+            "game_over_delay_secs = {}",
+            self.game_over_delay.as_secs()
+        )?;
+        writeln!(f, "\n[network]")?;
+        writeln!(
+            f,
+            "heartbeat_interval_frames = {}",
+            self.heartbeat_interval_frames
+        )?;
+        writeln!(
+            f,
+            "report_players_status_interval_frames = {}",
+            self.report_players_status_interval_frames
+        )?;
+        writeln!(
+            f,
+            "broadcast_interval_frames = {}",
+            self.broadcast_interval_frames
+        )?;
+        writeln!(
+            f,
+            "lag_compensation_frames = {}",
+            self.lag_compensation_frames
+        )?;
+        writeln!(
+            f,
+            "idle_connection_timeout_ms = {}",
+            self.idle_connection_timeout.as_millis()
+        )?;
+        writeln!(
+            f,
+            "receive_buffer_max_size = {}",
+            self.receive_buffer_max_size
+        )?;
+        writeln!(
+            f,
+            "world_update_budget_bytes = {}",
+            self.world_update_budget_bytes
+        )?;
+        write!(f, "mtu = {}", self.mtu)?;
+        if let Some(metrics_addr) = self.metrics_addr {
+            write!(f, "\n\n[metrics]\naddr = \"{}\"", metrics_addr)?;
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments take precedence over the values of a config file.
+struct ConfigSources<'a> {
+    path: &'a str,
+    file: Table,
+    cli_matches: &'a ArgMatches<'a>,
+}
+
+impl<'a> ConfigSources<'a> {
+    fn get<T>(&self, section: &str, key: &str, arg: &str) -> amethyst::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = self.cli_matches.value_of(arg) {
+            return parse_option(&format!("--{}", arg), value).map(Some);
+        }
+
+        let table = if section.is_empty() {
+            Some(&self.file)
+        } else {
+            self.file.get(section).and_then(Value::as_table)
+        };
+        let value = match table.and_then(|table| table.get(key)) {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Integer(value)) => value.to_string(),
+            Some(Value::Boolean(value)) => value.to_string(),
+            Some(value) => {
+                return Err(Error::from_string(format!(
+                    "Unexpected {} value for {} in {}",
+                    value.type_str(),
+                    config_key(section, key),
+                    self.path
+                )))
+            }
+            None => return Ok(None),
+        };
+        value.parse().map(Some).map_err(|err| {
+            Error::from_string(format!(
+                "Invalid {} value \"{}\" in {}: {}",
+                config_key(section, key),
+                value,
+                self.path,
+                err
+            ))
+        })
+    }
+
+    fn read<T>(&self, value: &mut T, section: &str, key: &str, arg: &str) -> amethyst::Result<()>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(new_value) = self.get(section, key, arg)? {
+            *value = new_value;
+        }
+        Ok(())
+    }
+
+    fn read_secs(
+        &self,
+        value: &mut Duration,
+        section: &str,
+        key: &str,
+        arg: &str,
+    ) -> amethyst::Result<()> {
+        if let Some(secs) = self.get(section, key, arg)? {
+            *value = Duration::from_secs(secs);
+        }
+        Ok(())
+    }
+}
+
+/// Parses a config file, rejecting unknown sections and keys as they are likely typos.
+fn read_config_file(contents: &str) -> Result<Table, String> {
+    let file = match contents.parse::<Value>() {
+        Ok(Value::Table(file)) => file,
+        Ok(_) => return Err("expected a table".to_owned()),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    for (key, value) in &file {
+        if let Some(section) = value.as_table() {
+            let known_keys = CONFIG_KEYS
+                .iter()
+                .find(|(known_section, _)| *known_section == key.as_str())
+                .map(|(_, known_keys)| *known_keys)
+                .ok_or_else(|| format!("unknown section [{}]", key))?;
+            if let Some(unknown_key) = section
+                .keys()
+                .find(|section_key| !known_keys.contains(&section_key.as_str()))
+            {
+                return Err(format!("unknown key {}", config_key(key, unknown_key)));
+            }
+        } else if !CONFIG_KEYS[0].1.contains(&key.as_str()) {
+            return Err(format!("unknown key {}", key));
+        }
+    }
+    Ok(file)
+}
+
+fn parse_option<T>(option: &str, value: &str) -> amethyst::Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse().map_err(|err| {
+        Error::from_string(format!("Invalid {} value \"{}\": {}", option, value, err))
+    })
+}
+
+fn config_key(section: &str, key: &str) -> String {
+    if section.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{}.{}", section, key)
+    }
+}
+
+fn check_range<T: PartialOrd + Display>(
+    name: &str,
+    value: T,
+    min: T,
+    max: T,
+) -> amethyst::Result<()> {
+    if value < min || value > max {
+        return Err(Error::from_string(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, value
+        )));
+    }
+    Ok(())
+}
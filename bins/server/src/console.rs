@@ -0,0 +1,92 @@
+use std::{
+    io::{self, BufRead},
+    str::FromStr,
+    sync::mpsc,
+    thread,
+};
+
+use gv_core::net::NetIdentifier;
+
+use crate::{config::RUNTIME_OPTIONS, ecs::resources::AdminCommands};
+
+const HELP: &str = "Available commands:
+  status                  shows the state of the server
+  players                 lists the players and spectators with their connection ids
+  kick <connection id>    disconnects a player or a spectator
+  pause                   pauses a running game
+  resume                  resumes a game paused with `pause`
+  end-match               returns the players to the room (dedicated servers only)
+  set <option> <value>    changes an option of the config, see `help set`
+  help                    shows this message";
+
+#[derive(Debug)]
+pub enum AdminCommand {
+    Status,
+    Players,
+    Kick(NetIdentifier),
+    Pause,
+    Resume,
+    EndMatch,
+    Set { option: String, value: String },
+}
+
+impl FromStr for AdminCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["status"] => Ok(AdminCommand::Status),
+            ["players"] => Ok(AdminCommand::Players),
+            ["kick", connection_id] => connection_id
+                .parse()
+                .map(AdminCommand::Kick)
+                .map_err(|_| format!("Invalid connection id: {}", connection_id)),
+            ["pause"] => Ok(AdminCommand::Pause),
+            ["resume"] => Ok(AdminCommand::Resume),
+            ["end-match"] => Ok(AdminCommand::EndMatch),
+            ["set", option, value] => Ok(AdminCommand::Set {
+                option: (*option).to_owned(),
+                value: (*value).to_owned(),
+            }),
+            _ => Err(format!("Unknown command: {}", line.trim())),
+        }
+    }
+}
+
+/// Reads commands on a separate thread, so that waiting for input never blocks the game loop.
+pub fn spawn_stdin_console() -> AdminCommands {
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("admin_console".to_owned())
+        .spawn(move || {
+            log::info!("The admin console is enabled, type `help` to list the commands");
+            for line in io::stdin().lock().lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => {
+                        log::error!("Failed to read the admin console input: {:?}", err);
+                        return;
+                    }
+                };
+                match line.trim() {
+                    "" => {}
+                    "help" => log::info!("{}", HELP),
+                    "help set" => log::info!(
+                        "Options that can be changed while a server is running: {}",
+                        RUNTIME_OPTIONS.join(", ")
+                    ),
+                    _ => match line.parse() {
+                        Ok(command) => {
+                            if sender.send(command).is_err() {
+                                return;
+                            }
+                        }
+                        Err(err) => log::warn!("{}. Type `help` to list the commands", err),
+                    },
+                }
+            }
+        })
+        .expect("Expected to spawn the admin console thread");
+    AdminCommands::new(receiver)
+}
@@ -1,5 +1,251 @@
-use std::net::SocketAddr;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{mpsc::Receiver, Arc, Mutex},
+    time::Duration,
+};
+
+use gv_core::{
+    ecs::resources::net::{MultiplayerGameState, MultiplayerRoomPlayer},
+    net::NetIdentifier,
+};
+
+use crate::{config::ServerConfig, console::AdminCommand};
 
 pub struct LastBroadcastedFrame(pub u64);
 
 pub struct HostClientAddress(pub Option<SocketAddr>);
+
+/// Is `Some` if a server runs without a hosting client: it starts games on its own
+/// and returns players to the room once a game is over.
+pub struct DedicatedServer(pub Option<DedicatedServerSettings>);
+
+impl DedicatedServer {
+    pub fn from_config(server_config: &ServerConfig) -> Self {
+        if server_config.dedicated {
+            DedicatedServer(Some(DedicatedServerSettings {
+                start_players: server_config.start_players(),
+                start_countdown: server_config.start_countdown,
+            }))
+        } else {
+            DedicatedServer(None)
+        }
+    }
+
+    pub fn is_dedicated(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+pub struct DedicatedServerSettings {
+    /// A game starts once the room has at least this many players and all of them are ready.
+    pub start_players: usize,
+    /// If set, a game also starts after this much time passes since the first player joins,
+    /// even if not all the players are ready.
+    pub start_countdown: Option<Duration>,
+}
+
+/// Lets players rejoin a running game after losing a connection or restarting a client.
+#[derive(Default)]
+pub struct PlayerReconnectTokens {
+    /// Maps tokens to connection ids of the players they were issued for.
+    connections: HashMap<u64, NetIdentifier>,
+    /// Maps connection ids to their tokens.
+    tokens: HashMap<NetIdentifier, u64>,
+    /// Players that have left a running game, keyed by their tokens.
+    disconnected_players: HashMap<u64, MultiplayerRoomPlayer>,
+}
+
+impl PlayerReconnectTokens {
+    /// Returns the already issued token if a connection has one.
+    pub fn issue(&mut self, connection_id: NetIdentifier) -> u64 {
+        if let Some(token) = self.token(connection_id) {
+            return token;
+        }
+
+        let mut token = rand::random::<u64>();
+        while self.connections.contains_key(&token)
+            || self.disconnected_players.contains_key(&token)
+        {
+            token = rand::random::<u64>();
+        }
+        self.connections.insert(token, connection_id);
+        self.tokens.insert(connection_id, token);
+        token
+    }
+
+    pub fn token(&self, connection_id: NetIdentifier) -> Option<u64> {
+        self.tokens.get(&connection_id).copied()
+    }
+
+    /// Remembers a player that has left a running game, so it can reconnect later.
+    pub fn add_disconnected_player(&mut self, player: MultiplayerRoomPlayer) {
+        if let Some(token) = self.tokens.remove(&player.connection_id) {
+            self.connections.remove(&token);
+            self.disconnected_players.insert(token, player);
+        }
+    }
+
+    /// Is called for every dropped connection, the token stays valid only if its player
+    /// has been added with `add_disconnected_player`.
+    pub fn forget_connection(&mut self, connection_id: NetIdentifier) {
+        if let Some(token) = self.tokens.remove(&connection_id) {
+            self.connections.remove(&token);
+        }
+    }
+
+    pub fn disconnected_players(&self) -> impl Iterator<Item = &MultiplayerRoomPlayer> {
+        self.disconnected_players.values()
+    }
+
+    /// Gives a player back to a new connection, returns `false` if the token is unknown.
+    pub fn reconnect(
+        &mut self,
+        token: u64,
+        connection_id: NetIdentifier,
+        multiplayer_game_state: &mut MultiplayerGameState,
+    ) -> bool {
+        if let Some(mut player) = self.disconnected_players.remove(&token) {
+            player.connection_id = connection_id;
+            multiplayer_game_state.update_players().push(player);
+        } else if let Some(previous_connection_id) = self.connections.get(&token).copied() {
+            // The previous connection hasn't timed out yet.
+            let player = multiplayer_game_state
+                .update_players()
+                .iter_mut()
+                .find(|player| player.connection_id == previous_connection_id);
+            if let Some(player) = player {
+                player.connection_id = connection_id;
+            } else {
+                return false;
+            }
+            self.tokens.remove(&previous_connection_id);
+        } else {
+            return false;
+        }
+
+        self.connections.insert(token, connection_id);
+        self.tokens.insert(connection_id, token);
+        true
+    }
+
+    /// Players can't rejoin a game once it's over.
+    pub fn clear_disconnected_players(&mut self) {
+        self.disconnected_players.clear();
+    }
+}
+
+/// Connection ids of the rejoined players that need a WorldSnapshot.
+#[derive(Default)]
+pub struct PendingWorldSnapshots(pub Vec<NetIdentifier>);
+
+/// Commands typed into the admin console, ServerNetworkSystem runs them every frame.
+#[derive(Default)]
+pub struct AdminCommands(Option<Mutex<Receiver<AdminCommand>>>);
+
+impl AdminCommands {
+    pub fn new(receiver: Receiver<AdminCommand>) -> Self {
+        Self(Some(Mutex::new(receiver)))
+    }
+
+    pub fn drain(&self) -> Vec<AdminCommand> {
+        self.0.as_ref().map_or_else(Vec::new, |receiver| {
+            receiver
+                .lock()
+                .expect("Expected to lock the admin console receiver")
+                .try_iter()
+                .collect()
+        })
+    }
+}
+
+/// Counters that systems update while a server runs, MetricsSystem exports them.
+#[derive(Default)]
+pub struct ServerMetrics {
+    pub connections: HashMap<NetIdentifier, ConnectionMetrics>,
+}
+
+#[derive(Default)]
+pub struct ConnectionMetrics {
+    pub world_updates_sent: u64,
+    /// Frame updates that were broadcasted again, as a client hadn't acknowledged them.
+    pub frame_updates_resent: u64,
+    pub world_update_bytes_sent: u64,
+    pub last_world_update_bytes: u64,
+}
+
+/// The latest metrics in the Prometheus text format, is `None` if the endpoint is disabled.
+#[derive(Clone, Default)]
+pub struct MetricsExport(Option<Arc<Mutex<String>>>);
+
+impl MetricsExport {
+    pub fn enabled() -> Self {
+        Self(Some(Arc::new(Mutex::new(String::new()))))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn text(&self) -> String {
+        self.0.as_ref().map_or_else(String::new, |text| {
+            text.lock()
+                .expect("Expected to lock the exported metrics")
+                .clone()
+        })
+    }
+
+    pub fn set_text(&self, new_text: String) {
+        if let Some(text) = &self.0 {
+            *text.lock().expect("Expected to lock the exported metrics") = new_text;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gv_core::progression::PlayerStats;
+
+    fn player(connection_id: NetIdentifier) -> MultiplayerRoomPlayer {
+        MultiplayerRoomPlayer {
+            connection_id,
+            entity_net_id: 0,
+            nickname: "player".to_owned(),
+            is_host: false,
+            is_ready: true,
+            color: [1.0, 1.0, 1.0],
+            stats: PlayerStats::default(),
+        }
+    }
+
+    #[test]
+    fn reconnects_players_with_their_tokens() {
+        let mut tokens = PlayerReconnectTokens::default();
+        let mut multiplayer_game_state = MultiplayerGameState::new();
+        let token = tokens.issue(1);
+        assert_eq!(tokens.issue(1), token);
+        assert_ne!(tokens.issue(2), token);
+
+        tokens.add_disconnected_player(player(1));
+        tokens.forget_connection(1);
+        assert_eq!(tokens.token(1), None);
+        assert_eq!(tokens.disconnected_players().count(), 1);
+
+        assert!(tokens.reconnect(token, 3, &mut multiplayer_game_state));
+        assert_eq!(multiplayer_game_state.players[0].connection_id, 3);
+        assert_eq!(tokens.token(3), Some(token));
+        assert_eq!(tokens.disconnected_players().count(), 0);
+
+        // The previous connection hasn't timed out yet.
+        assert!(tokens.reconnect(token, 4, &mut multiplayer_game_state));
+        assert_eq!(multiplayer_game_state.players[0].connection_id, 4);
+        assert_eq!(tokens.token(3), None);
+        tokens.forget_connection(3);
+        assert_eq!(tokens.token(4), Some(token));
+
+        tokens.forget_connection(4);
+        assert!(!tokens.reconnect(token, 5, &mut multiplayer_game_state));
+    }
+}
@@ -0,0 +1,132 @@
+use amethyst::{
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage},
+    network::simulation::TransportResource,
+};
+
+use std::time::Instant;
+
+use gv_core::{
+    ecs::{
+        components::{Dead, NetConnectionModel, Player},
+        resources::{
+            net::MultiplayerGameState,
+            world::{FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates},
+            GameEngineState, GameLevelState, NewGameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::{
+        server_message::ServerMessagePayload, world_update_encoding::WorldUpdatePositionsHistory,
+    },
+};
+use gv_game::utils::{entities::is_dead, net::broadcast_message_reliable};
+
+use crate::{
+    config::ServerConfig,
+    ecs::resources::{DedicatedServer, LastBroadcastedFrame},
+};
+
+/// Returns players to the room once a game is over if a server is dedicated.
+#[derive(Default)]
+pub struct DedicatedServerSystem {
+    game_over_at: Option<Instant>,
+}
+
+impl<'s> System<'s> for DedicatedServerSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadExpect<'s, ServerConfig>,
+        ReadExpect<'s, DedicatedServer>,
+        ReadExpect<'s, GameEngineState>,
+        WriteExpect<'s, NewGameEngineState>,
+        WriteExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, GameLevelState>,
+        WriteExpect<'s, FramedUpdates<ReceivedClientActionUpdates>>,
+        WriteExpect<'s, ServerWorldUpdates>,
+        WriteExpect<'s, LastBroadcastedFrame>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Dead>,
+        WriteStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            entities,
+            server_config,
+            dedicated_server,
+            game_engine_state,
+            mut new_game_engine_state,
+            mut multiplayer_game_state,
+            mut game_level_state,
+            mut framed_updates,
+            mut server_world_updates,
+            mut last_broadcasted_frame,
+            players,
+            dead,
+            mut net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if !dedicated_server.is_dedicated()
+            || *game_engine_state != GameEngineState::Playing
+            || !multiplayer_game_state.is_playing
+        {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let all_players_are_dead = (&entities, &players)
+            .join()
+            .all(|(player_entity, _)| is_dead(player_entity, &dead, frame_number));
+        let is_over = game_level_state.is_over
+            || all_players_are_dead
+            || multiplayer_game_state.players.is_empty();
+        if !is_over {
+            return;
+        }
+
+        let game_over_at = *self.game_over_at.get_or_insert_with(|| {
+            log::info!("The game is over (frame {})", frame_number);
+            Instant::now()
+        });
+        if game_over_at.elapsed() < server_config.game_over_delay {
+            return;
+        }
+
+        log::info!("Returning the players to the room");
+        self.game_over_at = None;
+        broadcast_message_reliable(
+            &mut transport,
+            (&net_connection_models).join(),
+            ServerMessagePayload::EndGame,
+        );
+
+        // Frame numbers start from 0 again in the next game.
+        *framed_updates = FramedUpdates::default();
+        *server_world_updates = ServerWorldUpdates::default();
+        last_broadcasted_frame.0 = 0;
+        for net_connection_model in (&mut net_connection_models).join() {
+            net_connection_model.last_acknowledged_update = None;
+            net_connection_model.last_acknowledged_positions = 0;
+            net_connection_model.world_update_positions = WorldUpdatePositionsHistory::default();
+            net_connection_model.ping_pong_data.reset();
+        }
+
+        multiplayer_game_state.is_playing = false;
+        multiplayer_game_state.waiting_network = false;
+        multiplayer_game_state.waiting_for_players = false;
+        multiplayer_game_state.lagging_players.clear();
+        // Clients ignore room updates while playing, so they need the actual list again.
+        // Players also have to get ready for every new game.
+        for player in multiplayer_game_state.update_players() {
+            player.is_ready = false;
+        }
+        // Makes MenuState clean up the entities of the finished game.
+        game_level_state.is_over = true;
+        new_game_engine_state.0 = GameEngineState::Menu;
+    }
+}
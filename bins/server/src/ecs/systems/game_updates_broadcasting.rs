@@ -1,5 +1,5 @@
 use amethyst::{
-    ecs::{Join, ReadStorage, System, Write, WriteExpect},
+    ecs::{Join, ReadExpect, System, Write, WriteExpect, WriteStorage},
     network::simulation::TransportResource,
 };
 
@@ -8,13 +8,14 @@ use gv_core::{
         components::NetConnectionModel, resources::world::ServerWorldUpdates,
         system_data::time::GameTimeService,
     },
-    net::server_message::ServerMessagePayload,
+    net::{server_message::ServerMessagePayload, world_update_encoding::WorldUpdatesEncoder},
 };
 use gv_game::{ecs::system_data::GameStateHelper, utils::net::send_message_unreliable};
 
-use crate::ecs::resources::LastBroadcastedFrame;
-
-const BROADCAST_FRAME_INTERVAL: u64 = 5;
+use crate::{
+    config::ServerConfig,
+    ecs::resources::{LastBroadcastedFrame, ServerMetrics},
+};
 
 #[derive(Default)]
 pub struct GameUpdatesBroadcastingSystem;
@@ -23,9 +24,11 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
     type SystemData = (
         GameTimeService<'s>,
         GameStateHelper<'s>,
+        ReadExpect<'s, ServerConfig>,
         WriteExpect<'s, ServerWorldUpdates>,
         WriteExpect<'s, LastBroadcastedFrame>,
-        ReadStorage<'s, NetConnectionModel>,
+        WriteExpect<'s, ServerMetrics>,
+        WriteStorage<'s, NetConnectionModel>,
         Write<'s, TransportResource>,
     );
 
@@ -34,9 +37,11 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         (
             game_time_service,
             game_state_helper,
+            server_config,
             mut server_world_updates,
             mut last_broadcasted_frame,
-            net_connection_models,
+            mut server_metrics,
+            mut net_connection_models,
             mut transport,
         ): Self::SystemData,
     ) {
@@ -49,10 +54,11 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         let is_time_to_broadcast = game_time_service
             .game_frame_number()
             .wrapping_sub(*last_broadcasted_frame)
-            > BROADCAST_FRAME_INTERVAL;
+            > server_config.broadcast_interval_frames;
         if !is_time_to_broadcast {
             return;
         }
+        let previous_broadcasted_frame = *last_broadcasted_frame;
         *last_broadcasted_frame = game_time_service.game_frame_number();
 
         let (latest_update_number, latest_update_frame_number) = {
@@ -67,7 +73,7 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
         let mut oldest_actual_update = latest_update_number + 1;
         let mut oldest_actual_update_index = 0;
 
-        for (i, net_connection_model) in (&net_connection_models).join().enumerate() {
+        for (i, net_connection_model) in (&mut net_connection_models).join().enumerate() {
             let last_acknowledged_is_older = net_connection_model
                 .last_acknowledged_update
                 .map(|last_acknowledged| oldest_actual_update > last_acknowledged)
@@ -84,27 +90,94 @@ impl<'s> System<'s> for GameUpdatesBroadcastingSystem {
                 .iter()
                 .rev()
                 .take_while(|update| Some(update.0) > net_connection_model.last_acknowledged_update)
-                .filter_map(move |update| {
-                    let update = &update.1;
+                .filter(move |update| {
                     // We may store some repetitive updates, so we need to filter them out.
-                    if oldest_added_frame > update.frame_number {
-                        oldest_added_frame = update.frame_number;
-                        Some(update.clone())
+                    if oldest_added_frame > update.1.frame_number {
+                        oldest_added_frame = update.1.frame_number;
+                        true
                     } else {
-                        None
+                        false
                     }
                 })
                 .collect::<Vec<_>>();
-            let updates = updates.into_iter().rev().collect();
 
-            send_message_unreliable(
-                &mut transport,
-                &net_connection_model,
-                ServerMessagePayload::UpdateWorld {
-                    id: latest_update_number,
+            // The oldest updates go first, as a client can't apply the newer ones without them.
+            let base_id = net_connection_model.last_acknowledged_update.unwrap_or(0);
+            // Positions are encoded as deltas from the ones a client has acknowledged,
+            // if we haven't forgotten them yet.
+            let last_acknowledged_positions = net_connection_model.last_acknowledged_positions;
+            let positions_history = &mut net_connection_model.world_update_positions;
+            positions_history.forget_older_than(last_acknowledged_positions);
+            let knows_acknowledged_positions =
+                positions_history.get(last_acknowledged_positions).is_some();
+            let positions_base_id = if knows_acknowledged_positions {
+                last_acknowledged_positions
+            } else {
+                0
+            };
+            let positions = positions_history
+                .get(positions_base_id)
+                .expect("Expected known positions");
+            let mut chunks = vec![(
+                base_id,
+                WorldUpdatesEncoder::new(server_config.world_update_budget_bytes, positions),
+            )];
+            let mut frame_updates_resent = 0;
+            for (update_number, update) in updates.into_iter().rev() {
+                let (chunk_id, encoder) = chunks.last_mut().expect("Expected a chunk");
+                if encoder.push(update) {
+                    *chunk_id = *update_number;
+                } else {
+                    let mut encoder = WorldUpdatesEncoder::new(
+                        server_config.world_update_budget_bytes,
+                        encoder.positions(),
+                    );
+                    encoder.push(update);
+                    chunks.push((*update_number, encoder));
+                }
+
+                if update.frame_number <= previous_broadcasted_frame {
+                    frame_updates_resent += 1;
+                }
+            }
+
+            let connection_metrics = server_metrics
+                .connections
+                .entry(net_connection_model.id)
+                .or_default();
+            connection_metrics.frame_updates_resent += frame_updates_resent;
+            connection_metrics.last_world_update_bytes = 0;
+
+            let mut chunk_base_id = base_id;
+            let mut chunk_positions_base_id = positions_base_id;
+            for (chunk_id, encoder) in chunks {
+                let (updates, positions) = encoder.finish();
+                let positions_id = net_connection_model.world_update_positions.push(positions);
+                if updates.len() > server_config.world_update_budget_bytes {
+                    log::warn!(
+                        "A world update of {} bytes exceeds the budget (connection id: {})",
+                        updates.len(),
+                        net_connection_model.id
+                    );
+                }
+                let payload = ServerMessagePayload::UpdateWorld {
+                    id: chunk_id,
+                    base_id: chunk_base_id,
+                    positions_id,
+                    positions_base_id: chunk_positions_base_id,
                     updates,
-                },
-            );
+                };
+                chunk_base_id = chunk_id;
+                chunk_positions_base_id = positions_id;
+
+                let payload_bytes = bincode::serialized_size(&payload)
+                    .expect("Expected to measure the size of a world update");
+                connection_metrics.world_updates_sent += 1;
+                connection_metrics.last_world_update_bytes += payload_bytes;
+                connection_metrics.world_update_bytes_sent += payload_bytes;
+
+                send_message_unreliable(&mut transport, &net_connection_model, payload);
+            }
         }
 
         // We don't need to store these updates anymore, as clients have already acknowledged them.
@@ -0,0 +1,125 @@
+use amethyst::{
+    ecs::{Join, ReadExpect, ReadStorage, System, Write, WriteExpect},
+    network::simulation::TransportResource,
+};
+
+use std::time::Instant;
+
+use gv_core::{
+    ecs::{
+        components::NetConnectionModel,
+        resources::{net::MultiplayerGameState, GameEngineState, NewGameEngineState},
+    },
+    net::server_message::ServerMessagePayload,
+};
+use gv_game::utils::net::broadcast_message_reliable;
+
+use crate::{config::ServerConfig, ecs::resources::DedicatedServer};
+
+/// Starts a game once all the players in the room are ready and the countdown is over.
+#[derive(Default)]
+pub struct LobbySystem {
+    ready_countdown_started_at: Option<Instant>,
+    last_broadcasted_secs_left: Option<u64>,
+    first_player_joined_at: Option<Instant>,
+}
+
+impl<'s> System<'s> for LobbySystem {
+    type SystemData = (
+        ReadExpect<'s, ServerConfig>,
+        ReadExpect<'s, DedicatedServer>,
+        ReadExpect<'s, GameEngineState>,
+        WriteExpect<'s, NewGameEngineState>,
+        WriteExpect<'s, MultiplayerGameState>,
+        ReadStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            server_config,
+            dedicated_server,
+            game_engine_state,
+            mut new_game_engine_state,
+            mut multiplayer_game_state,
+            net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if *game_engine_state != GameEngineState::Menu || multiplayer_game_state.is_playing {
+            *self = Self::default();
+            return;
+        }
+
+        let players_count = multiplayer_game_state.players.len();
+        if players_count == 0 {
+            *self = Self::default();
+            return;
+        }
+
+        let start_countdown = dedicated_server
+            .0
+            .as_ref()
+            .and_then(|settings| settings.start_countdown);
+        let first_player_joined_at = *self.first_player_joined_at.get_or_insert_with(|| {
+            if let Some(start_countdown) = start_countdown {
+                log::info!("The game starts in {}s", start_countdown.as_secs());
+            }
+            Instant::now()
+        });
+        let start_countdown_is_over = start_countdown.map_or(false, |start_countdown| {
+            first_player_joined_at.elapsed() >= start_countdown
+        });
+
+        let start_players = dedicated_server
+            .0
+            .as_ref()
+            .map_or(1, |settings| settings.start_players);
+        let all_players_are_ready = players_count >= start_players
+            && multiplayer_game_state
+                .players
+                .iter()
+                .all(|player| player.is_ready);
+
+        let ready_countdown_is_over = if all_players_are_ready {
+            let ready_countdown_started_at =
+                *self.ready_countdown_started_at.get_or_insert_with(|| {
+                    log::info!("All the players are ready, starting the countdown");
+                    Instant::now()
+                });
+            let time_left = server_config
+                .ready_countdown
+                .checked_sub(ready_countdown_started_at.elapsed())
+                .unwrap_or_default();
+            let secs_left = (time_left.as_millis() as u64 + 999) / 1000;
+            if self.last_broadcasted_secs_left != Some(secs_left) && secs_left > 0 {
+                self.last_broadcasted_secs_left = Some(secs_left);
+                broadcast_message_reliable(
+                    &mut transport,
+                    (&net_connection_models).join(),
+                    ServerMessagePayload::UpdateLobbyCountdown(Some(secs_left)),
+                );
+            }
+            secs_left == 0
+        } else {
+            if self.ready_countdown_started_at.take().is_some() {
+                log::info!("Not all the players are ready, cancelling the countdown");
+                self.last_broadcasted_secs_left = None;
+                broadcast_message_reliable(
+                    &mut transport,
+                    (&net_connection_models).join(),
+                    ServerMessagePayload::UpdateLobbyCountdown(None),
+                );
+            }
+            false
+        };
+
+        if ready_countdown_is_over || start_countdown_is_over {
+            log::info!("Starting a new game with {} player(s)", players_count);
+            *self = Self::default();
+            multiplayer_game_state.is_playing = true;
+            new_game_engine_state.0 = GameEngineState::Playing;
+        }
+    }
+}
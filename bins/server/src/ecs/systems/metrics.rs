@@ -0,0 +1,326 @@
+use amethyst::ecs::{Entities, Join, ReadExpect, ReadStorage, System};
+
+use std::{
+    fmt::{Display, Write},
+    time::{Duration, Instant},
+};
+
+use gv_core::ecs::{
+    components::{Monster, NetConnectionModel, Player},
+    resources::net::MultiplayerGameState,
+    system_data::time::GameTimeService,
+};
+
+use gv_game::utils::net::{sent_message_stats, SentMessageStats};
+
+use crate::{
+    config::ServerConfig,
+    ecs::resources::{ConnectionMetrics, MetricsExport, ServerMetrics},
+};
+
+/// Renders the metrics of a server and its connections in the Prometheus text format
+/// once a second.
+#[derive(Default)]
+pub struct MetricsSystem {
+    last_export_at: Option<Instant>,
+    frames_since_export: u32,
+    max_frame_time: Duration,
+}
+
+impl<'s> System<'s> for MetricsSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadExpect<'s, ServerConfig>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, ServerMetrics>,
+        ReadExpect<'s, MetricsExport>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, NetConnectionModel>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            entities,
+            server_config,
+            multiplayer_game_state,
+            server_metrics,
+            metrics_export,
+            players,
+            monsters,
+            net_connection_models,
+        ): Self::SystemData,
+    ) {
+        if !metrics_export.is_enabled() {
+            return;
+        }
+
+        let engine_time = game_time_service.engine_time();
+        self.frames_since_export += 1;
+        self.max_frame_time = self.max_frame_time.max(engine_time.delta_real_time());
+        let last_export_at = *self.last_export_at.get_or_insert_with(Instant::now);
+        let since_export = last_export_at.elapsed();
+        if since_export < Duration::from_secs(1) {
+            return;
+        }
+
+        let mut metrics = PrometheusText::default();
+        metrics.family(
+            "gv_server_frame_time_seconds",
+            "Average time of a frame over the last second",
+            "gauge",
+        );
+        metrics.sample(
+            "gv_server_frame_time_seconds",
+            &[],
+            since_export.as_secs_f64() / f64::from(self.frames_since_export),
+        );
+        metrics.family(
+            "gv_server_frame_time_max_seconds",
+            "The longest frame over the last second",
+            "gauge",
+        );
+        metrics.sample(
+            "gv_server_frame_time_max_seconds",
+            &[],
+            self.max_frame_time.as_secs_f64(),
+        );
+        metrics.family(
+            "gv_server_tick_rate",
+            "Frames a server is configured to simulate per second",
+            "gauge",
+        );
+        metrics.sample("gv_server_tick_rate", &[], server_config.tick_rate);
+        metrics.family(
+            "gv_server_game_frame",
+            "The current frame of a running game",
+            "gauge",
+        );
+        metrics.sample(
+            "gv_server_game_frame",
+            &[],
+            game_time_service.game_frame_number(),
+        );
+        metrics.family(
+            "gv_server_is_paused",
+            "Whether a running game waits for lagging players or is paused by an admin",
+            "gauge",
+        );
+        metrics.sample(
+            "gv_server_is_paused",
+            &[],
+            multiplayer_game_state.waiting_for_players as u8,
+        );
+
+        metrics.family(
+            "gv_server_room_members",
+            "Players and spectators in the room",
+            "gauge",
+        );
+        metrics.sample(
+            "gv_server_room_members",
+            &[("role", "player")],
+            multiplayer_game_state.players.len(),
+        );
+        metrics.sample(
+            "gv_server_room_members",
+            &[("role", "spectator")],
+            multiplayer_game_state.spectators.len(),
+        );
+        metrics.family("gv_server_entities", "Entities in the world", "gauge");
+        metrics.sample(
+            "gv_server_entities",
+            &[("kind", "all")],
+            entities.join().count(),
+        );
+        metrics.sample(
+            "gv_server_entities",
+            &[("kind", "player")],
+            players.join().count(),
+        );
+        metrics.sample(
+            "gv_server_entities",
+            &[("kind", "monster")],
+            monsters.join().count(),
+        );
+
+        let connections = net_connection_models
+            .join()
+            .filter(|net_connection_model| !net_connection_model.disconnected)
+            .map(|net_connection_model| {
+                let id = net_connection_model.id;
+                let (nickname, role) =
+                    if let Some(player) = multiplayer_game_state.find_player_by_connection_id(id) {
+                        (player.nickname.clone(), "player")
+                    } else if let Some(spectator) = multiplayer_game_state
+                        .spectators
+                        .iter()
+                        .find(|spectator| spectator.connection_id == id)
+                    {
+                        (spectator.nickname.clone(), "spectator")
+                    } else {
+                        (String::new(), "joining")
+                    };
+                (net_connection_model, id.to_string(), nickname, role)
+            })
+            .collect::<Vec<_>>();
+        let connection_families: &[(&str, &str, &str, ConnectionMetric)] = &[
+            (
+                "gv_server_connection_latency_ms",
+                "Round-trip time of pings",
+                "gauge",
+                |net_connection_model, _, delta_seconds| {
+                    u64::from(
+                        net_connection_model
+                            .ping_pong_data
+                            .latency_ms(delta_seconds),
+                    )
+                },
+            ),
+            (
+                "gv_server_connection_average_lagging_behind_frames",
+                "How many frames a client lags behind a server on average",
+                "gauge",
+                |net_connection_model, _, _| {
+                    net_connection_model.ping_pong_data.average_lagging_behind()
+                },
+            ),
+            (
+                "gv_server_connection_last_stored_game_frame",
+                "The latest game frame a client has reported",
+                "gauge",
+                |net_connection_model, _, _| {
+                    net_connection_model.ping_pong_data.last_stored_game_frame()
+                },
+            ),
+            (
+                "gv_server_connection_world_updates_sent_total",
+                "World updates sent to a client",
+                "counter",
+                |_, connection_metrics, _| connection_metrics.world_updates_sent,
+            ),
+            (
+                "gv_server_connection_frame_updates_resent_total",
+                "Frame updates sent again as a client hadn't acknowledged them",
+                "counter",
+                |_, connection_metrics, _| connection_metrics.frame_updates_resent,
+            ),
+            (
+                "gv_server_connection_world_update_bytes_total",
+                "Bytes of world updates sent to a client",
+                "counter",
+                |_, connection_metrics, _| connection_metrics.world_update_bytes_sent,
+            ),
+            (
+                "gv_server_connection_last_world_update_bytes",
+                "The size of the world update messages of the latest broadcast to a client",
+                "gauge",
+                |_, connection_metrics, _| connection_metrics.last_world_update_bytes,
+            ),
+        ];
+        let no_metrics = ConnectionMetrics::default();
+        for (name, help, kind, metric) in connection_families {
+            metrics.family(name, help, kind);
+            for (net_connection_model, id, nickname, role) in &connections {
+                let connection_metrics = server_metrics
+                    .connections
+                    .get(&net_connection_model.id)
+                    .unwrap_or(&no_metrics);
+                metrics.sample(
+                    name,
+                    &[
+                        ("connection_id", id.as_str()),
+                        ("nickname", nickname.as_str()),
+                        ("role", role),
+                    ],
+                    metric(
+                        net_connection_model,
+                        connection_metrics,
+                        engine_time.delta_seconds(),
+                    ),
+                );
+            }
+        }
+
+        let sent_message_stats = sent_message_stats();
+        let message_families: &[(&str, &str, &str, MessageMetric)] = &[
+            (
+                "gv_server_messages_sent_total",
+                "Messages sent to all the connections",
+                "counter",
+                |stats| stats.count,
+            ),
+            (
+                "gv_server_message_bytes_sent_total",
+                "Bytes of messages sent to all the connections, before fragmenting them",
+                "counter",
+                |stats| stats.bytes,
+            ),
+            (
+                "gv_server_message_max_bytes",
+                "The size of the largest message sent",
+                "gauge",
+                |stats| stats.max_bytes,
+            ),
+            (
+                "gv_server_messages_fragmented_total",
+                "Messages that were split into several datagrams",
+                "counter",
+                |stats| stats.fragmented,
+            ),
+            (
+                "gv_server_messages_dropped_total",
+                "Messages that exceeded the size limit and weren't sent",
+                "counter",
+                |stats| stats.dropped,
+            ),
+        ];
+        for (name, help, kind, metric) in message_families {
+            metrics.family(name, help, kind);
+            for (message_name, stats) in &sent_message_stats {
+                metrics.sample(name, &[("message", message_name)], metric(stats));
+            }
+        }
+
+        metrics_export.set_text(metrics.0);
+        self.last_export_at = Some(Instant::now());
+        self.frames_since_export = 0;
+        self.max_frame_time = Duration::default();
+    }
+}
+
+type ConnectionMetric = fn(&NetConnectionModel, &ConnectionMetrics, f32) -> u64;
+type MessageMetric = fn(&SentMessageStats) -> u64;
+
+#[derive(Default)]
+struct PrometheusText(String);
+
+impl PrometheusText {
+    fn family(&mut self, name: &str, help: &str, kind: &str) {
+        writeln!(self.0, "# HELP {} {}", name, help).expect("Expected to write metrics");
+        writeln!(self.0, "# TYPE {} {}", name, kind).expect("Expected to write metrics");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let labels = labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            writeln!(self.0, "{} {}", name, value)
+        } else {
+            writeln!(self.0, "{}{{{}}} {}", name, labels.join(","), value)
+        }
+        .expect("Expected to write metrics");
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,6 +1,13 @@
+mod dedicated_server;
 mod game_updates_broadcasting;
+mod lobby;
+mod metrics;
 mod server_network;
+mod world_snapshot;
 
 pub use self::{
-    game_updates_broadcasting::GameUpdatesBroadcastingSystem, server_network::ServerNetworkSystem,
+    dedicated_server::DedicatedServerSystem,
+    game_updates_broadcasting::GameUpdatesBroadcastingSystem, lobby::LobbySystem,
+    metrics::MetricsSystem, server_network::ServerNetworkSystem,
+    world_snapshot::WorldSnapshotSystem,
 };
@@ -11,22 +11,24 @@ use gv_core::{
     ecs::{
         components::NetConnectionModel,
         resources::{
-            net::{ActionUpdateIdProvider, MultiplayerGameState, MultiplayerRoomPlayer},
+            net::{
+                ActionUpdateIdProvider, MultiplayerGameState, MultiplayerRoomPlayer,
+                MultiplayerRoomSpectator,
+            },
             world::{
                 FramedUpdates, ImmediatePlayerActionsUpdates, PlayerLookActionUpdates,
-                ReceivedClientActionUpdates, ServerWorldUpdates, LAG_COMPENSATION_FRAMES_LIMIT,
-                PAUSE_FRAME_THRESHOLD,
+                ReceivedClientActionUpdates, ServerWorldUpdates,
             },
-            GameEngineState, NewGameEngineState,
+            GameEngineState, GameLevelState, NewGameEngineState,
         },
         system_data::time::GameTimeService,
     },
     net::{
         client_message::{ClientMessage, ClientMessagePayload},
         server_message::{DisconnectReason, ServerMessagePayload},
-        NetEvent, NetIdentifier, NetUpdate, INTERPOLATION_FRAME_DELAY,
+        NetEvent, NetIdentifier, NetUpdate, BUILD_HASH, INTERPOLATION_FRAME_DELAY,
     },
-    PLAYER_COLORS,
+    player_color,
 };
 use gv_game::{
     ecs::resources::ConnectionEvents,
@@ -35,16 +37,21 @@ use gv_game::{
 
 use std::collections::HashSet;
 
-use crate::ecs::resources::{HostClientAddress, LastBroadcastedFrame};
+use crate::{
+    config::ServerConfig,
+    console::AdminCommand,
+    ecs::resources::{
+        AdminCommands, DedicatedServer, HostClientAddress, LastBroadcastedFrame,
+        PendingWorldSnapshots, PlayerReconnectTokens,
+    },
+};
 use gv_core::net::server_message::PlayerNetStatus;
 
-const HEARTBEAT_FRAME_INTERVAL: u64 = 2;
-const REPORT_PLAYERS_STATUS_FRAME_INTERVAL: u64 = 50;
-
 pub struct ServerNetworkSystem {
     host_connection_id: Option<NetIdentifier>,
     last_heartbeat_frame: u64,
     last_report_players_status_frame: u64,
+    is_paused_by_admin: bool,
 }
 
 impl ServerNetworkSystem {
@@ -53,6 +60,7 @@ impl ServerNetworkSystem {
             host_connection_id: None,
             last_heartbeat_frame: 0,
             last_report_players_status_frame: 0,
+            is_paused_by_admin: false,
         }
     }
 
@@ -69,6 +77,10 @@ impl<'s> System<'s> for ServerNetworkSystem {
         Entities<'s>,
         ReadExpect<'s, GameEngineState>,
         ReadExpect<'s, LastBroadcastedFrame>,
+        ReadExpect<'s, AdminCommands>,
+        WriteExpect<'s, DedicatedServer>,
+        WriteExpect<'s, ServerConfig>,
+        WriteExpect<'s, GameLevelState>,
         WriteExpect<'s, ConnectionEvents>,
         WriteExpect<'s, HostClientAddress>,
         WriteExpect<'s, MultiplayerGameState>,
@@ -76,6 +88,8 @@ impl<'s> System<'s> for ServerNetworkSystem {
         WriteExpect<'s, FramedUpdates<ReceivedClientActionUpdates>>,
         WriteExpect<'s, ServerWorldUpdates>,
         WriteExpect<'s, ActionUpdateIdProvider>,
+        WriteExpect<'s, PlayerReconnectTokens>,
+        WriteExpect<'s, PendingWorldSnapshots>,
         WriteStorage<'s, NetConnectionModel>,
         Write<'s, TransportResource>,
     );
@@ -88,6 +102,10 @@ impl<'s> System<'s> for ServerNetworkSystem {
             entities,
             game_engine_state,
             last_broadcasted_frame,
+            admin_commands,
+            mut dedicated_server,
+            mut server_config,
+            mut game_level_state,
             mut connection_events,
             mut host_client_address,
             mut multiplayer_game_state,
@@ -95,20 +113,32 @@ impl<'s> System<'s> for ServerNetworkSystem {
             mut framed_updates,
             mut server_world_updates,
             mut action_update_id_provider,
+            mut player_reconnect_tokens,
+            mut pending_world_snapshots,
             mut net_connection_models,
             mut transport,
         ): Self::SystemData,
     ) {
+        if !multiplayer_game_state.is_playing {
+            player_reconnect_tokens.clear_disconnected_players();
+            self.is_paused_by_admin = false;
+        }
+
         if let Some(host_client_address) = host_client_address.0.take() {
-            let net_connection_model = NetConnectionModel::new(0, 0, host_client_address);
+            let mut net_connection_model = NetConnectionModel::new(0, 0, host_client_address);
+            net_connection_model.mtu = server_config.mtu;
             self.host_connection_id = Some(0);
             log::info!("Sending a Handshake message to a hosting client");
             send_message_reliable(
                 &mut transport,
                 &net_connection_model,
                 ServerMessagePayload::Handshake {
+                    build_hash: BUILD_HASH.to_owned(),
                     net_id: 0,
                     is_host: true,
+                    reconnect_token: player_reconnect_tokens.issue(0),
+                    max_players: server_config.max_players as u32,
+                    tick_rate: server_config.tick_rate,
                 },
             );
             entities
@@ -140,6 +170,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     if net_connection_model.session_created_at < *sent_at {
                         net_connection_model.session_id = *session_id;
                         net_connection_model.session_created_at = *sent_at;
+                        net_connection_model.mtu = server_config.mtu;
                         // It might be the case that a player reconnects before the connection model
                         // entity is dropped, so we need to change this flag manually for previously
                         // existed connections.
@@ -148,6 +179,21 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 }
             }
 
+            // Clients of other protocol versions are rejected by NetConnectionManager.
+            if let NetEvent::Message(ClientMessage {
+                session_id: _,
+                payload: ClientMessagePayload::JoinRoom { build_hash, .. },
+            }) = &connection_event.event
+            {
+                if build_hash != BUILD_HASH {
+                    log::info!(
+                        "A client ({}) runs a different build ({}) of the same protocol version",
+                        connection_id,
+                        build_hash
+                    );
+                }
+            }
+
             // Handle ignoring messages if the game is already started.
             if multiplayer_game_state.is_playing {
                 if let NetEvent::Message(ClientMessage {
@@ -156,12 +202,60 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 }) = &connection_event.event
                 {
                     let is_ignored = match payload {
-                        ClientMessagePayload::JoinRoom { .. } => {
+                        ClientMessagePayload::JoinRoom {
+                            nickname,
+                            is_spectator: true,
+                            ..
+                        } => {
+                            join_as_spectator(
+                                &mut transport,
+                                net_connection_model,
+                                &mut multiplayer_game_state,
+                                nickname.clone(),
+                                &server_config,
+                            );
+                            pending_world_snapshots.0.push(connection_id);
+                            true
+                        }
+
+                        ClientMessagePayload::JoinRoom {
+                            reconnect_token, ..
+                        } => {
                             let player_is_in_game = multiplayer_game_state
                                 .players
                                 .iter()
                                 .any(|player| player.connection_id == connection_id);
-                            if !player_is_in_game {
+                            // A player that is still in the game may have restarted its client
+                            // before the previous connection timed out.
+                            let player_has_reconnected =
+                                reconnect_token.map_or(false, |reconnect_token| {
+                                    player_reconnect_tokens.reconnect(
+                                        reconnect_token,
+                                        connection_id,
+                                        &mut multiplayer_game_state,
+                                    )
+                                });
+                            if player_has_reconnected {
+                                log::info!(
+                                    "A client ({}) {} has rejoined the game",
+                                    connection_id,
+                                    net_connection_model.addr
+                                );
+                                send_message_reliable(
+                                    &mut transport,
+                                    net_connection_model,
+                                    ServerMessagePayload::Handshake {
+                                        build_hash: BUILD_HASH.to_owned(),
+                                        net_id: connection_id,
+                                        is_host: false,
+                                        reconnect_token: reconnect_token
+                                            .expect("Expected a reconnect token"),
+                                        max_players: server_config.max_players as u32,
+                                        tick_rate: server_config.tick_rate,
+                                    },
+                                );
+                                pending_world_snapshots.0.push(connection_id);
+                            } else if !player_is_in_game {
                                 log::warn!(
                                     "A new client ({}) {} tried to connect while the game has already started",
                                     connection_id,
@@ -179,9 +273,9 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             true
                         }
 
-                        ClientMessagePayload::StartHostedGame => {
+                        ClientMessagePayload::SetReady(_) => {
                             log::warn!(
-                                "A client ({}) {} tried to start the game while it's already started",
+                                "A client ({}) {} tried to change its readiness during the game",
                                 connection_id,
                                 net_connection_model.addr
                             );
@@ -205,14 +299,40 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 }) = &connection_event.event
                 {
                     let is_ignored = match payload {
-                        ClientMessagePayload::AcknowledgeWorldUpdate(_) => true,
+                        ClientMessagePayload::AcknowledgeWorldUpdate { .. } => true,
+                        ClientMessagePayload::WalkActions(_) => true,
+                        ClientMessagePayload::CastActions(_) => true,
+                        ClientMessagePayload::LookActions(_) => true,
+                        _ => false,
+                    };
+
+                    if is_ignored {
+                        continue;
+                    }
+                }
+            }
+
+            // Spectators can only watch.
+            if multiplayer_game_state.is_spectator(connection_id) {
+                if let NetEvent::Message(ClientMessage {
+                    session_id: _,
+                    payload,
+                }) = &connection_event.event
+                {
+                    let is_ignored = match payload {
                         ClientMessagePayload::WalkActions(_) => true,
                         ClientMessagePayload::CastActions(_) => true,
                         ClientMessagePayload::LookActions(_) => true,
+                        ClientMessagePayload::SetReady(_) => true,
+                        ClientMessagePayload::Kick { .. } => true,
                         _ => false,
                     };
 
                     if is_ignored {
+                        log::warn!(
+                            "Ignoring an unexpected message from a spectator (connection id: {})",
+                            connection_id
+                        );
                         continue;
                     }
                 }
@@ -225,9 +345,41 @@ impl<'s> System<'s> for ServerNetworkSystem {
                 }) => match payload {
                     ClientMessagePayload::JoinRoom {
                         nickname,
+                        is_spectator: true,
+                        ..
+                    } => {
+                        if multiplayer_game_state
+                            .find_player_by_connection_id(connection_id)
+                            .is_some()
+                        {
+                            if self.is_host(connection_id) {
+                                log::warn!("The host can't become a spectator");
+                                continue;
+                            }
+                            multiplayer_game_state.drop_player_by_connection_id(connection_id);
+                        }
+                        join_as_spectator(
+                            &mut transport,
+                            net_connection_model,
+                            &mut multiplayer_game_state,
+                            nickname,
+                            &server_config,
+                        );
+                    }
+
+                    ClientMessagePayload::JoinRoom {
+                        nickname,
+                        stats,
                         sent_at: _,
+                        reconnect_token: _,
+                        is_spectator: false,
                     } => {
-                        let is_host = if multiplayer_game_state.players.is_empty() {
+                        multiplayer_game_state.drop_spectator_by_connection_id(connection_id);
+                        let stats = stats.clamped();
+                        // Dedicated servers don't have hosts, they start games on their own.
+                        let is_host = if dedicated_server.is_dedicated() {
+                            false
+                        } else if multiplayer_game_state.players.is_empty() {
                             if let Some(host_connection_id) = self.host_connection_id {
                                 if host_connection_id != connection_id {
                                     send_message_reliable(
@@ -259,11 +411,14 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             .iter_mut()
                             .find(|player| player.connection_id == connection_id)
                         {
-                            log::info!("The player already existed, updating the nickname only");
+                            log::info!(
+                                "The player already existed, updating the nickname and stats"
+                            );
                             player.nickname = nickname;
+                            player.stats = stats;
                         } else {
                             let new_player_count = multiplayer_game_state.players.len();
-                            if new_player_count >= 4 {
+                            if new_player_count >= server_config.max_players {
                                 send_message_reliable(
                                     &mut transport,
                                     net_connection_model,
@@ -280,7 +435,9 @@ impl<'s> System<'s> for ServerNetworkSystem {
                                     entity_net_id: 0,
                                     nickname,
                                     is_host: self.is_host(connection_id),
-                                    color: PLAYER_COLORS[new_player_count],
+                                    is_ready: false,
+                                    color: player_color(new_player_count),
+                                    stats,
                                 });
                         }
 
@@ -289,23 +446,34 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             &mut transport,
                             net_connection_model,
                             ServerMessagePayload::Handshake {
+                                build_hash: BUILD_HASH.to_owned(),
                                 net_id: connection_id,
                                 is_host,
+                                reconnect_token: player_reconnect_tokens.issue(connection_id),
+                                max_players: server_config.max_players as u32,
+                                tick_rate: server_config.tick_rate,
                             },
                         );
                     }
 
-                    ClientMessagePayload::StartHostedGame
-                        if self.is_host(connection_id) && !multiplayer_game_state.is_playing =>
-                    {
-                        multiplayer_game_state.is_playing = true;
-                        new_game_engine_state.0 = GameEngineState::Playing;
-                    }
-                    ClientMessagePayload::StartHostedGame => {
-                        log::warn!(
-                            "Received an unexpected StartHostedGame message (connection id: {})",
-                            connection_id,
-                        );
+                    ClientMessagePayload::SetReady(is_ready) => {
+                        if let Some(player) = multiplayer_game_state
+                            .update_players()
+                            .iter_mut()
+                            .find(|player| player.connection_id == connection_id)
+                        {
+                            log::info!(
+                                "A client ({}) has changed its readiness: {}",
+                                connection_id,
+                                is_ready
+                            );
+                            player.is_ready = is_ready;
+                        } else {
+                            log::warn!(
+                                "Received SetReady from an unknown player (connection id: {})",
+                                connection_id,
+                            );
+                        }
                     }
 
                     ClientMessagePayload::WalkActions(actions) => {
@@ -318,6 +486,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             &mut *framed_updates,
                             actions,
                             game_time_service.game_frame_number(),
+                            &server_config,
                         );
 
                         if !discarded_actions.is_empty() {
@@ -339,6 +508,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             actions,
                             &mut *action_update_id_provider,
                             game_time_service.game_frame_number(),
+                            &server_config,
                         );
                     }
 
@@ -347,12 +517,15 @@ impl<'s> System<'s> for ServerNetworkSystem {
                             &mut *framed_updates,
                             actions,
                             game_time_service.game_frame_number(),
+                            &server_config,
                         );
                     }
 
-                    ClientMessagePayload::AcknowledgeWorldUpdate(frame_number) => {
+                    ClientMessagePayload::AcknowledgeWorldUpdate { id, positions_id } => {
                         net_connection_model.last_acknowledged_update =
-                            Some(frame_number).max(net_connection_model.last_acknowledged_update);
+                            Some(id).max(net_connection_model.last_acknowledged_update);
+                        net_connection_model.last_acknowledged_positions =
+                            positions_id.max(net_connection_model.last_acknowledged_positions);
                     }
 
                     ClientMessagePayload::Kick {
@@ -410,11 +583,108 @@ impl<'s> System<'s> for ServerNetworkSystem {
             }
 
             if net_connection_model.disconnected && !host_disconnected {
-                multiplayer_game_state.drop_player_by_connection_id(connection_id);
+                if multiplayer_game_state.drop_spectator_by_connection_id(connection_id) {
+                    log::info!("A spectator ({}) has left", connection_id);
+                } else {
+                    if multiplayer_game_state.is_playing {
+                        if let Some(player) =
+                            multiplayer_game_state.find_player_by_connection_id(connection_id)
+                        {
+                            player_reconnect_tokens.add_disconnected_player(player.clone());
+                        }
+                    }
+                    multiplayer_game_state.drop_player_by_connection_id(connection_id);
+                }
+                player_reconnect_tokens.forget_connection(connection_id);
             }
         }
 
-        for kicked_player_index in kicked_players.iter().cloned() {
+        for admin_command in admin_commands.drain() {
+            match admin_command {
+                AdminCommand::Status => log_status(
+                    &multiplayer_game_state,
+                    &server_config,
+                    dedicated_server.is_dedicated(),
+                    self.is_paused_by_admin,
+                    game_time_service.game_frame_number(),
+                ),
+                AdminCommand::Players => log_players(
+                    &multiplayer_game_state,
+                    &net_connection_models,
+                    game_time_service.engine_time().delta_seconds(),
+                ),
+                AdminCommand::Kick(kicked_connection_id) => {
+                    let kicked_player_index = multiplayer_game_state
+                        .players
+                        .iter()
+                        .position(|player| player.connection_id == kicked_connection_id);
+                    if self.is_host(kicked_connection_id) {
+                        log::warn!("The host can't be kicked");
+                    } else if let Some(kicked_player_index) = kicked_player_index {
+                        kicked_players.insert(kicked_player_index);
+                    } else if multiplayer_game_state
+                        .drop_spectator_by_connection_id(kicked_connection_id)
+                    {
+                        log::info!("Kicking a spectator ({})", kicked_connection_id);
+                        let net_connection_model = (&mut net_connection_models)
+                            .join()
+                            .find(|net_connection_model| {
+                                net_connection_model.id == kicked_connection_id
+                            })
+                            .expect("Expected a connection model of a kicked spectator");
+                        send_message_reliable(
+                            &mut transport,
+                            net_connection_model,
+                            ServerMessagePayload::Disconnect(DisconnectReason::Kick),
+                        );
+                        net_connection_model.disconnected = true;
+                    } else {
+                        log::warn!(
+                            "There's no player or spectator with connection id {}",
+                            kicked_connection_id
+                        );
+                    }
+                }
+                AdminCommand::Pause => {
+                    if !multiplayer_game_state.is_playing {
+                        log::warn!("There's no running game to pause");
+                    } else if !self.is_paused_by_admin {
+                        log::info!("Pausing the game");
+                        self.is_paused_by_admin = true;
+                    }
+                }
+                AdminCommand::Resume => {
+                    if self.is_paused_by_admin {
+                        log::info!("Resuming the game");
+                        self.is_paused_by_admin = false;
+                    } else {
+                        log::warn!("The game isn't paused");
+                    }
+                }
+                AdminCommand::EndMatch => {
+                    if !dedicated_server.is_dedicated() {
+                        log::warn!("Only dedicated servers can end a game, it's up to the host");
+                    } else if !multiplayer_game_state.is_playing {
+                        log::warn!("There's no running game to end");
+                    } else {
+                        log::info!("Ending the game");
+                        game_level_state.is_over = true;
+                    }
+                }
+                AdminCommand::Set { option, value } => match server_config.set(&option, &value) {
+                    Ok(()) => {
+                        log::info!("{} is set to {}", option, value);
+                        *dedicated_server = DedicatedServer::from_config(&server_config);
+                    }
+                    Err(err) => log::warn!("Failed to set {}: {}", option, err),
+                },
+            }
+        }
+
+        // Dropping players from the end, so that the rest of the indices stay valid.
+        let mut kicked_players = kicked_players.into_iter().collect::<Vec<_>>();
+        kicked_players.sort_unstable_by(|a, b| b.cmp(a));
+        for kicked_player_index in kicked_players {
             let player_connection_id =
                 multiplayer_game_state.players[kicked_player_index].connection_id;
             multiplayer_game_state.drop_player_by_index(kicked_player_index);
@@ -453,7 +723,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
         }
 
         if game_time_service.engine_time().frame_number() - self.last_heartbeat_frame
-            > HEARTBEAT_FRAME_INTERVAL
+            > server_config.heartbeat_interval_frames
         {
             self.last_heartbeat_frame = game_time_service.engine_time().frame_number();
             broadcast_message_reliable(
@@ -464,7 +734,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
         }
 
         if game_time_service.engine_time().frame_number() - self.last_report_players_status_frame
-            > REPORT_PLAYERS_STATUS_FRAME_INTERVAL
+            > server_config.report_players_status_interval_frames
         {
             self.last_report_players_status_frame = game_time_service.engine_time().frame_number();
             broadcast_message_unreliable(
@@ -506,7 +776,9 @@ impl<'s> System<'s> for ServerNetworkSystem {
         if *game_engine_state == GameEngineState::Playing && multiplayer_game_state.is_playing {
             let mut lagging_players = Vec::new();
             for net_connection_model in (&net_connection_models).join() {
-                if net_connection_model.disconnected {
+                if net_connection_model.disconnected
+                    || multiplayer_game_state.is_spectator(net_connection_model.id)
+                {
                     continue;
                 }
 
@@ -547,16 +819,19 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     average_lagging_behind
                 );
 
-                if frames_since_last_pong > PAUSE_FRAME_THRESHOLD
+                let pause_frame_threshold = server_config.pause_frame_threshold();
+                if frames_since_last_pong > pause_frame_threshold
                     || was_lagging && is_catching_up
-                    || average_lagging_behind > PAUSE_FRAME_THRESHOLD
+                    || average_lagging_behind > pause_frame_threshold
                 {
                     lagging_players.push(net_connection_model.id);
                 }
             }
 
             multiplayer_game_state.lagging_players = lagging_players.clone();
-            if !multiplayer_game_state.waiting_for_players && !lagging_players.is_empty() {
+            // Clients get an empty list of lagging players if the game is paused by an admin.
+            let is_paused = !lagging_players.is_empty() || self.is_paused_by_admin;
+            if !multiplayer_game_state.waiting_for_players && is_paused {
                 multiplayer_game_state.waiting_for_players_pause_id += 1;
                 broadcast_message_reliable(
                     &mut transport,
@@ -567,7 +842,7 @@ impl<'s> System<'s> for ServerNetworkSystem {
                     },
                 );
                 multiplayer_game_state.waiting_for_players = true;
-            } else if multiplayer_game_state.waiting_for_players && lagging_players.is_empty() {
+            } else if multiplayer_game_state.waiting_for_players && !is_paused {
                 broadcast_message_reliable(
                     &mut transport,
                     (&net_connection_models).join(),
@@ -596,11 +871,119 @@ impl<'s> System<'s> for ServerNetworkSystem {
     }
 }
 
+fn log_status(
+    multiplayer_game_state: &MultiplayerGameState,
+    server_config: &ServerConfig,
+    is_dedicated: bool,
+    is_paused_by_admin: bool,
+    frame_number: u64,
+) {
+    let game_state = if !multiplayer_game_state.is_playing {
+        "in the room".to_owned()
+    } else if is_paused_by_admin {
+        format!("paused at frame {}", frame_number)
+    } else if multiplayer_game_state.waiting_for_players {
+        format!("waiting for lagging players at frame {}", frame_number)
+    } else {
+        format!("playing, frame {}", frame_number)
+    };
+    log::info!(
+        "{} server at {}: {}, {}/{} player(s), {} spectator(s)",
+        if is_dedicated { "Dedicated" } else { "Hosted" },
+        server_config.addr,
+        game_state,
+        multiplayer_game_state.players.len(),
+        server_config.max_players,
+        multiplayer_game_state.spectators.len(),
+    );
+}
+
+fn log_players(
+    multiplayer_game_state: &MultiplayerGameState,
+    net_connection_models: &WriteStorage<NetConnectionModel>,
+    delta_seconds: f32,
+) {
+    let latency_ms = |connection_id: NetIdentifier| {
+        net_connection_models
+            .join()
+            .find(|net_connection_model| net_connection_model.id == connection_id)
+            .map_or(0, |net_connection_model| {
+                net_connection_model
+                    .ping_pong_data
+                    .latency_ms(delta_seconds)
+            })
+    };
+
+    if multiplayer_game_state.players.is_empty() && multiplayer_game_state.spectators.is_empty() {
+        log::info!("There are no players");
+    }
+    for player in &multiplayer_game_state.players {
+        log::info!(
+            "Player {}: {}{}{}, latency {}ms",
+            player.connection_id,
+            player.nickname,
+            if player.is_host { " (host)" } else { "" },
+            if player.is_ready { " (ready)" } else { "" },
+            latency_ms(player.connection_id),
+        );
+    }
+    for spectator in &multiplayer_game_state.spectators {
+        log::info!(
+            "Spectator {}: {}, latency {}ms",
+            spectator.connection_id,
+            spectator.nickname,
+            latency_ms(spectator.connection_id),
+        );
+    }
+}
+
+fn join_as_spectator(
+    transport: &mut TransportResource,
+    net_connection_model: &NetConnectionModel,
+    multiplayer_game_state: &mut MultiplayerGameState,
+    nickname: String,
+    server_config: &ServerConfig,
+) {
+    let connection_id = net_connection_model.id;
+    if !multiplayer_game_state.is_spectator(connection_id) {
+        log::info!(
+            "A client ({}) has joined as a spectator: {}",
+            connection_id,
+            nickname
+        );
+        multiplayer_game_state
+            .spectators
+            .push(MultiplayerRoomSpectator {
+                connection_id,
+                nickname,
+            });
+    }
+
+    log::info!(
+        "Sending a Handshake message to a spectator: {}",
+        connection_id
+    );
+    send_message_reliable(
+        transport,
+        net_connection_model,
+        ServerMessagePayload::Handshake {
+            build_hash: BUILD_HASH.to_owned(),
+            net_id: connection_id,
+            is_host: false,
+            // Spectators don't have players to reconnect to.
+            reconnect_token: 0,
+            max_players: server_config.max_players as u32,
+            tick_rate: server_config.tick_rate,
+        },
+    );
+}
+
 /// Returns discarded actions.
 fn add_walk_actions(
     framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
     actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>,
     frame_number: u64,
+    server_config: &ServerConfig,
 ) -> Vec<NetIdentifier> {
     let mut discarded_actions = Vec::new();
 
@@ -608,11 +991,13 @@ fn add_walk_actions(
 
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+    if added_actions_frame_number.saturating_sub(frame_number)
+        > server_config.pause_frame_threshold()
+    {
         return Vec::new();
     }
 
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame = frame_number.saturating_sub(server_config.lag_compensation_frames);
     let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
     let actual_frame = if are_lag_compensated {
         added_actions_frame_number
@@ -621,7 +1006,7 @@ fn add_walk_actions(
     };
 
     let is_badly_late = added_actions_frame_number
-        < frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64 * 2);
+        < frame_number.saturating_sub(server_config.lag_compensation_frames * 2);
     for action in actions.updates {
         let is_added = {
             if is_badly_late {
@@ -645,7 +1030,8 @@ fn add_walk_actions(
         if is_added {
             let frames_to_move = oldest_possible_frame.saturating_sub(added_actions_frame_number);
             if !is_badly_late && frames_to_move > 0 {
-                let mut moved_updates = Vec::with_capacity(LAG_COMPENSATION_FRAMES_LIMIT);
+                let mut moved_updates =
+                    Vec::with_capacity(server_config.lag_compensation_frames as usize);
                 for framed_update in framed_updates
                     .updates
                     .iter_mut()
@@ -704,6 +1090,7 @@ fn add_look_actions(
     framed_updates: &mut FramedUpdates<ReceivedClientActionUpdates>,
     actions: PlayerLookActionUpdates,
     frame_number: u64,
+    server_config: &ServerConfig,
 ) {
     let frame_to_reserve = actions
         .updates
@@ -715,7 +1102,7 @@ fn add_look_actions(
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
     let is_outdated_update = frame_to_reserve.map_or(true, |frame_to_reserve| {
-        frame_to_reserve.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD
+        frame_to_reserve.saturating_sub(frame_number) > server_config.pause_frame_threshold()
     });
     if is_outdated_update {
         return;
@@ -726,7 +1113,7 @@ fn add_look_actions(
     }
 
     let mut oldest_updated_frame = framed_updates.oldest_updated_frame;
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame = frame_number.saturating_sub(server_config.lag_compensation_frames);
     let mut framed_updates_iter = framed_updates.updates_iter_mut(oldest_possible_frame);
 
     'action_updates: for (update_frame_number, updates) in actions.updates {
@@ -783,16 +1170,19 @@ fn add_cast_actions(
     actions: ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>,
     action_update_id_provider: &mut ActionUpdateIdProvider,
     frame_number: u64,
+    server_config: &ServerConfig,
 ) {
     let added_actions_frame_number = actions.frame_number;
 
     // Just ignore these updates, most probably these are lost packages from the previous game,
     // or the client is just bonkers.
-    if added_actions_frame_number.saturating_sub(frame_number) > PAUSE_FRAME_THRESHOLD {
+    if added_actions_frame_number.saturating_sub(frame_number)
+        > server_config.pause_frame_threshold()
+    {
         return;
     }
 
-    let oldest_possible_frame = frame_number.saturating_sub(LAG_COMPENSATION_FRAMES_LIMIT as u64);
+    let oldest_possible_frame = frame_number.saturating_sub(server_config.lag_compensation_frames);
     let are_lag_compensated = added_actions_frame_number > oldest_possible_frame;
     let actual_frame = if are_lag_compensated {
         added_actions_frame_number
@@ -833,3 +1223,210 @@ fn add_cast_actions(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst::{
+        core::{bundle::SystemBundle, Time},
+        ecs::{Dispatcher, DispatcherBuilder, Read, ReaderId, World, WorldExt},
+        network::simulation::{DeliveryRequirement, NetworkSimulationEvent, UrgencyRequirement},
+        prelude::SystemDesc,
+        shrev::EventChannel,
+    };
+
+    use std::{net::SocketAddr, time::Duration};
+
+    use gv_core::{
+        ecs::resources::GameTime,
+        net::{
+            fragmentation::{whole_message, MessageReassembler},
+            server_message::ServerMessage,
+            DEFAULT_MAX_PLAYERS,
+        },
+        progression::PlayerStats,
+        FRAME_RATE,
+    };
+    use gv_game::{
+        ecs::systems::NetConnectionManagerDesc,
+        utils::net::transport::{LoopbackNetwork, TransportBundle},
+    };
+
+    #[derive(Default)]
+    struct ReceivedMessages(Vec<ServerMessage>);
+
+    /// Plays the part of a client: sends JoinRoom and collects the messages of a server.
+    struct TestClientSystem {
+        server_addr: SocketAddr,
+        reader: ReaderId<NetworkSimulationEvent>,
+        message_reassembler: MessageReassembler,
+        join_room_sent: bool,
+    }
+
+    impl<'s> System<'s> for TestClientSystem {
+        type SystemData = (
+            Read<'s, EventChannel<NetworkSimulationEvent>>,
+            Write<'s, TransportResource>,
+            Write<'s, ReceivedMessages>,
+        );
+
+        fn run(&mut self, (net_events, mut transport, mut received_messages): Self::SystemData) {
+            if !self.join_room_sent {
+                self.join_room_sent = true;
+                let message = bincode::serialize(&ClientMessage {
+                    session_id: 1,
+                    payload: ClientMessagePayload::JoinRoom {
+                        build_hash: BUILD_HASH.to_owned(),
+                        sent_at: Duration::from_secs(1),
+                        nickname: "Test client".to_owned(),
+                        stats: PlayerStats::default(),
+                        reconnect_token: None,
+                        is_spectator: false,
+                    },
+                })
+                .expect("Expected to serialize JoinRoom message");
+                transport.send_with_requirements(
+                    self.server_addr,
+                    &whole_message(&message),
+                    DeliveryRequirement::Reliable,
+                    UrgencyRequirement::Immediate,
+                );
+            }
+
+            for net_event in net_events.read(&mut self.reader) {
+                if let NetworkSimulationEvent::Message(addr, payload) = net_event {
+                    assert_eq!(*addr, self.server_addr);
+                    let message = self
+                        .message_reassembler
+                        .receive(payload)
+                        .expect("Expected a valid datagram");
+                    if let Some(message) = message {
+                        received_messages.0.push(
+                            bincode::deserialize(&message)
+                                .expect("Expected to deserialize a server message"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn build_server(
+        network: &LoopbackNetwork,
+        addr: SocketAddr,
+    ) -> (World, Dispatcher<'static, 'static>) {
+        let mut world = World::new();
+        world.insert(Time::default());
+        world.insert(GameTime::default());
+        world.insert(GameEngineState::Menu);
+        world.insert(NewGameEngineState(GameEngineState::Menu));
+        world.insert(GameLevelState::default());
+        world.insert(MultiplayerGameState::new());
+        world.insert(ConnectionEvents(Vec::new()));
+        world.insert(ServerConfig::default());
+        world.insert(DedicatedServer(None));
+        world.insert(HostClientAddress(None));
+        world.insert(AdminCommands::default());
+        world.insert(LastBroadcastedFrame(0));
+        world.insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
+        world.insert(ServerWorldUpdates::default());
+        world.insert(ActionUpdateIdProvider::default());
+        world.insert(PlayerReconnectTokens::default());
+        world.insert(PendingWorldSnapshots::default());
+
+        let mut builder = DispatcherBuilder::new();
+        TransportBundle::new(network.transport(addr))
+            .build(&mut world, &mut builder)
+            .expect("Expected to build a loopback transport");
+        builder.add(
+            NetConnectionManagerDesc::default().build(&mut world),
+            "net_connection_manager_system",
+            &[],
+        );
+        builder.add(ServerNetworkSystem::new(), "game_network_system", &[]);
+        let mut dispatcher = builder.build();
+        dispatcher.setup(&mut world);
+        (world, dispatcher)
+    }
+
+    fn build_client(
+        network: &LoopbackNetwork,
+        addr: SocketAddr,
+        server_addr: SocketAddr,
+    ) -> (World, Dispatcher<'static, 'static>) {
+        let mut world = World::new();
+        world.insert(ReceivedMessages::default());
+        world.insert(EventChannel::<NetworkSimulationEvent>::new());
+        let reader = world
+            .fetch_mut::<EventChannel<NetworkSimulationEvent>>()
+            .register_reader();
+
+        let mut builder = DispatcherBuilder::new();
+        TransportBundle::new(network.transport(addr))
+            .build(&mut world, &mut builder)
+            .expect("Expected to build a loopback transport");
+        builder.add(
+            TestClientSystem {
+                server_addr,
+                reader,
+                message_reassembler: MessageReassembler::default(),
+                join_room_sent: false,
+            },
+            "test_client_system",
+            &[],
+        );
+        let mut dispatcher = builder.build();
+        dispatcher.setup(&mut world);
+        (world, dispatcher)
+    }
+
+    #[test]
+    fn joins_room_over_loopback_network() {
+        let network = LoopbackNetwork::new();
+        let server_addr: SocketAddr = "127.0.0.1:3455".parse().unwrap();
+        let client_addr: SocketAddr = "127.0.0.1:3456".parse().unwrap();
+        let (mut server_world, mut server_dispatcher) = build_server(&network, server_addr);
+        let (mut client_world, mut client_dispatcher) =
+            build_client(&network, client_addr, server_addr);
+
+        // Transport systems send the datagrams queued on the previous frame,
+        // so it takes a few frames for JoinRoom and Handshake to get delivered.
+        for _ in 0..5 {
+            client_dispatcher.dispatch(&client_world);
+            client_world.maintain();
+            server_dispatcher.dispatch(&server_world);
+            server_world.maintain();
+        }
+
+        let received_messages = client_world.fetch::<ReceivedMessages>();
+        let handshake = received_messages
+            .0
+            .iter()
+            .find(|message| matches!(message.payload, ServerMessagePayload::Handshake { .. }))
+            .expect("Expected to receive a Handshake message");
+        assert_eq!(handshake.session_id, 1);
+        match &handshake.payload {
+            ServerMessagePayload::Handshake {
+                build_hash,
+                net_id,
+                is_host,
+                max_players,
+                tick_rate,
+                ..
+            } => {
+                assert_eq!(build_hash, BUILD_HASH);
+                assert_eq!(*net_id, 0);
+                // A server that isn't dedicated makes the first player a host.
+                assert!(*is_host);
+                assert_eq!(*max_players, DEFAULT_MAX_PLAYERS as u32);
+                assert_eq!(*tick_rate, FRAME_RATE);
+            }
+            _ => unreachable!(),
+        }
+
+        let multiplayer_game_state = server_world.fetch::<MultiplayerGameState>();
+        assert_eq!(multiplayer_game_state.players.len(), 1);
+        assert_eq!(multiplayer_game_state.players[0].nickname, "Test client");
+    }
+}
@@ -0,0 +1,284 @@
+use amethyst::{
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage},
+    network::simulation::TransportResource,
+};
+
+use gv_core::{
+    actions::Action,
+    ecs::{
+        components::{
+            area_spell::AreaSpell,
+            enemy_projectile::EnemyProjectile,
+            missile::{Missile, MissileTarget},
+            Dead, EntityNetMetadata, Monster, NetConnectionModel, Player, PlayerActions,
+            PlayerLastCastedSpells, WorldPosition,
+        },
+        resources::{
+            net::MultiplayerGameState,
+            world::{
+                AreaSpellSnapshot, EnemyProjectileSnapshot, LevelProgressSnapshot, MissileSnapshot,
+                MonsterSnapshot, PlayerSnapshot, ServerWorldUpdates, WorldSnapshot,
+            },
+            GameLevelState, GameRngs,
+        },
+        system_data::time::GameTimeService,
+    },
+    net::{
+        server_message::ServerMessagePayload, world_update_encoding::WorldUpdatePositionsHistory,
+    },
+};
+use gv_game::{ecs::resources::GameMode, utils::net::send_message_reliable};
+
+use crate::ecs::resources::{PendingWorldSnapshots, PlayerReconnectTokens};
+
+/// Sends the current state of the world to the players that have rejoined a running game.
+pub struct WorldSnapshotSystem;
+
+impl<'s> System<'s> for WorldSnapshotSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadExpect<'s, MultiplayerGameState>,
+        ReadExpect<'s, GameMode>,
+        ReadExpect<'s, GameRngs>,
+        ReadExpect<'s, GameLevelState>,
+        ReadExpect<'s, ServerWorldUpdates>,
+        ReadExpect<'s, PlayerReconnectTokens>,
+        WriteExpect<'s, PendingWorldSnapshots>,
+        ReadStorage<'s, EntityNetMetadata>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PlayerActions>,
+        ReadStorage<'s, PlayerLastCastedSpells>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Missile>,
+        ReadStorage<'s, AreaSpell>,
+        ReadStorage<'s, EnemyProjectile>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, WorldPosition>,
+        WriteStorage<'s, NetConnectionModel>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_time_service,
+            entities,
+            multiplayer_game_state,
+            game_mode,
+            game_rngs,
+            game_level_state,
+            server_world_updates,
+            player_reconnect_tokens,
+            mut pending_world_snapshots,
+            entity_net_metadata,
+            players,
+            player_actions,
+            player_last_casted_spells,
+            monsters,
+            missiles,
+            area_spells,
+            enemy_projectiles,
+            dead,
+            world_positions,
+            mut net_connection_models,
+            mut transport,
+        ): Self::SystemData,
+    ) {
+        if pending_world_snapshots.0.is_empty() {
+            return;
+        }
+        if !multiplayer_game_state.is_playing {
+            pending_world_snapshots.0.clear();
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        let update_id = server_world_updates
+            .updates
+            .back()
+            .expect("Expected at least one ServerWorldUpdate")
+            .0;
+
+        let players_snapshot = (
+            &entities,
+            &entity_net_metadata,
+            &players,
+            &player_actions,
+            &player_last_casted_spells,
+            &world_positions,
+        )
+            .join()
+            .map(
+                |(entity, player_net_metadata, player, actions, last_casted_spells, position)| {
+                    PlayerSnapshot {
+                        net_metadata: *player_net_metadata,
+                        health: player.health,
+                        mana: player.mana,
+                        velocity: player.velocity,
+                        walking_direction: player.walking_direction,
+                        looking_direction: player.looking_direction,
+                        stats: player.stats,
+                        actions: actions.clone(),
+                        last_casted_spells: last_casted_spells.frames().collect(),
+                        position: position.position,
+                        dead_since_frame: dead.get(entity).map(|dead| dead.dead_since_frame),
+                    }
+                },
+            )
+            .collect();
+
+        let monsters_snapshot = (
+            &entities,
+            &entity_net_metadata,
+            &monsters,
+            &world_positions,
+            !&dead,
+        )
+            .join()
+            .map(
+                |(_, monster_net_metadata, monster, position, _)| MonsterSnapshot {
+                    net_metadata: *monster_net_metadata,
+                    name: monster.name.clone(),
+                    health: monster.health,
+                    attack_damage: monster.attack_damage,
+                    destination: monster.destination,
+                    facing_direction: monster.facing_direction,
+                    velocity: monster.velocity,
+                    action: Action {
+                        frame_number: monster.action.frame_number,
+                        action: monster
+                            .action
+                            .action
+                            .load_entity_net_id(&entity_net_metadata),
+                    },
+                    radius: monster.radius,
+                    position: position.position,
+                },
+            )
+            .collect();
+
+        let missiles_snapshot = (&missiles, &world_positions)
+            .join()
+            .filter_map(|(missile, position)| {
+                let target = match missile.target {
+                    MissileTarget::Target(target) => {
+                        MissileTarget::Target(entity_net_metadata.get(target)?.id)
+                    }
+                    MissileTarget::Destination(destination) => {
+                        MissileTarget::Destination(destination)
+                    }
+                };
+                Some(MissileSnapshot {
+                    action_id: missile.action_id,
+                    radius: missile.radius,
+                    target,
+                    velocity: missile.velocity,
+                    frame_spawned: missile.frame_spawned,
+                    damage: missile.damage,
+                    caster: missile.caster,
+                    position: position.position,
+                })
+            })
+            .collect();
+
+        let area_spells_snapshot = (&area_spells, &world_positions, !&dead)
+            .join()
+            .map(|(area_spell, position, _)| AreaSpellSnapshot {
+                action_id: area_spell.action_id,
+                radius: area_spell.radius,
+                damage: area_spell.damage,
+                frame_spawned: area_spell.frame_spawned,
+                caster: area_spell.caster,
+                hit_monsters: area_spell
+                    .hit_monsters
+                    .iter()
+                    .filter_map(|monster| Some(entity_net_metadata.get(*monster)?.id))
+                    .collect(),
+                position: position.position,
+            })
+            .collect();
+
+        let enemy_projectiles_snapshot = (&enemy_projectiles, &world_positions, !&dead)
+            .join()
+            .filter_map(|(projectile, position, _)| {
+                if dead.contains(projectile.owner) {
+                    return None;
+                }
+                Some(EnemyProjectileSnapshot {
+                    owner: entity_net_metadata.get(projectile.owner)?.id,
+                    radius: projectile.radius,
+                    velocity: projectile.velocity,
+                    frame_spawned: projectile.frame_spawned,
+                    damage: projectile.damage,
+                    position: position.position,
+                })
+            })
+            .collect();
+
+        let chunks = WorldSnapshot {
+            frame_number,
+            update_id,
+            rngs: game_rngs.state(),
+            level_progress: LevelProgressSnapshot::new(&game_level_state),
+            players: players_snapshot,
+            monsters: monsters_snapshot,
+            missiles: missiles_snapshot,
+            area_spells: area_spells_snapshot,
+            enemy_projectiles: enemy_projectiles_snapshot,
+        }
+        .into_chunks();
+        // Players that haven't rejoined yet still have their entities in the world.
+        let room_players: Vec<_> = multiplayer_game_state
+            .players
+            .iter()
+            .chain(player_reconnect_tokens.disconnected_players())
+            .map(|player| (player.entity_net_id, player.clone()))
+            .collect();
+
+        for connection_id in pending_world_snapshots.0.drain(..) {
+            let net_connection_model = (&mut net_connection_models)
+                .join()
+                .find(|net_connection_model| net_connection_model.id == connection_id);
+            let net_connection_model = if let Some(net_connection_model) = net_connection_model {
+                net_connection_model
+            } else {
+                log::warn!(
+                    "A client ({}) has disconnected before receiving a world snapshot",
+                    connection_id
+                );
+                continue;
+            };
+
+            log::info!(
+                "Sending a world snapshot (frame {}, {} chunk(s)) to a client ({})",
+                frame_number,
+                chunks.len(),
+                connection_id
+            );
+            net_connection_model.last_acknowledged_update = Some(update_id);
+            net_connection_model.last_acknowledged_positions = 0;
+            net_connection_model.world_update_positions = WorldUpdatePositionsHistory::default();
+            send_message_reliable(
+                &mut transport,
+                net_connection_model,
+                ServerMessagePayload::ResumeGame {
+                    seed: multiplayer_game_state.game_seed,
+                    players: room_players.clone(),
+                    campaign_level: game_mode.campaign_level(),
+                },
+            );
+            for (chunk_index, chunk) in chunks.iter().enumerate() {
+                send_message_reliable(
+                    &mut transport,
+                    net_connection_model,
+                    ServerMessagePayload::WorldSnapshot {
+                        chunk_index: chunk_index as u32,
+                        chunks_count: chunks.len() as u32,
+                        chunk: chunk.clone(),
+                    },
+                );
+            }
+        }
+    }
+}
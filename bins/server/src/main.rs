@@ -1,26 +1,45 @@
 #![allow(clippy::type_complexity)]
 
+mod config;
+mod console;
 mod ecs;
+mod metrics;
 
 use amethyst::{
     core::{frame_limiter::FrameRateLimitStrategy, transform::TransformBundle},
-    network::simulation::laminar::{LaminarConfig, LaminarNetworkBundle, LaminarSocket},
+    network::simulation::laminar::{LaminarConfig, LaminarSocket},
     prelude::{Application, GameDataBuilder, SystemDesc},
-    Logger, LoggerConfig,
+    Error, Logger, LoggerConfig,
 };
 
-use gv_core::ecs::resources::world::{
-    DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
+use gv_core::{
+    ecs::resources::world::{
+        DummyFramedUpdate, FramedUpdates, ReceivedClientActionUpdates, ServerWorldUpdates,
+    },
+    net::{BUILD_HASH, PROTOCOL_VERSION},
 };
 use gv_game::{
     build_game_logic_systems,
-    ecs::systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
+    ecs::{
+        resources::register_definitions,
+        systems::{NetConnectionManagerDesc, WorldPositionTransformSystem},
+    },
     states::LoadingState,
+    utils::net::{
+        simulation::NetworkConditions,
+        transport::{LaminarTransport, TransportBundle},
+    },
 };
 
-use crate::ecs::{
-    resources::{HostClientAddress, LastBroadcastedFrame},
-    systems::*,
+use crate::{
+    config::ServerConfig,
+    ecs::{
+        resources::{
+            AdminCommands, DedicatedServer, HostClientAddress, LastBroadcastedFrame, MetricsExport,
+            PendingWorldSnapshots, PlayerReconnectTokens, ServerMetrics,
+        },
+        systems::*,
+    },
 };
 
 fn main() -> amethyst::Result<()> {
@@ -28,13 +47,19 @@ fn main() -> amethyst::Result<()> {
         .version("0.1")
         .author("Vladyslav Batyrenko <mvlabat@gmail.com>")
         .about("A prototype of a top-down EvilInvasion-like 2D arcade/action")
+        .arg(
+            clap::Arg::with_name("config")
+                .long("config")
+                .value_name("PATH")
+                .help("Specifies the config file (server_config.toml by default)")
+                .takes_value(true),
+        )
         .arg(
             clap::Arg::with_name("addr")
                 .short("a")
                 .long("addr")
                 .value_name("ADDR")
-                .help("Specifies the address for UdpSocket")
-                .default_value("127.0.0.1:3455")
+                .help("Specifies the address for UdpSocket (127.0.0.1:3455 by default)")
                 .takes_value(true),
         )
         .arg(
@@ -45,18 +70,160 @@ fn main() -> amethyst::Result<()> {
                 .help("Specifies the address of the client hosting the game")
                 .takes_value(true),
         )
+        .arg(
+            clap::Arg::with_name("tick-rate")
+                .long("tick-rate")
+                .value_name("FPS")
+                .help("Specifies how many frames a server simulates per second, clients run at 60")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("console")
+                .long("console")
+                .help("Enables the admin console that reads commands from stdin"),
+        )
+        .arg(
+            clap::Arg::with_name("metrics-addr")
+                .long("metrics-addr")
+                .value_name("ADDR")
+                .help("Serves metrics in the Prometheus text format at http://ADDR/metrics")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("max-players")
+                .long("max-players")
+                .value_name("PLAYERS")
+                .help("Specifies how many players can join a room (spectators aren't counted)")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("ready-countdown-secs")
+                .long("ready-countdown-secs")
+                .value_name("SECS")
+                .help("A game starts this long after all the players get ready")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("game-mode")
+                .long("game-mode")
+                .value_name("MODE")
+                .help("Specifies the game mode")
+                .possible_values(&["survival", "campaign"])
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("campaign-level")
+                .long("campaign-level")
+                .value_name("INDEX")
+                .help("Specifies the campaign level to play, starting from 0")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("dedicated")
+                .long("dedicated")
+                .help("Runs without a hosting client, starting games on its own")
+                .conflicts_with("host-client-addr"),
+        )
+        .arg(
+            clap::Arg::with_name("start-players")
+                .long("start-players")
+                .value_name("PLAYERS")
+                .help(
+                    "A dedicated server starts a game once this many players are ready \
+                     (defaults to --max-players)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("start-countdown-secs")
+                .long("start-countdown-secs")
+                .value_name("SECS")
+                .help("A dedicated server starts a game this long after the first player joins")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("game-over-delay-secs")
+                .long("game-over-delay-secs")
+                .value_name("SECS")
+                .help("A dedicated server returns players to the room this long after a game ends")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("heartbeat-interval")
+                .long("heartbeat-interval")
+                .value_name("FRAMES")
+                .help("Specifies how often heartbeats are sent to clients")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("report-status-interval")
+                .long("report-status-interval")
+                .value_name("FRAMES")
+                .help("Specifies how often clients get the network status of players")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("broadcast-interval")
+                .long("broadcast-interval")
+                .value_name("FRAMES")
+                .help("Specifies how often world updates are broadcasted")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("lag-compensation-frames")
+                .long("lag-compensation-frames")
+                .value_name("FRAMES")
+                .help("Specifies how late client actions can arrive and still get applied")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("idle-timeout-ms")
+                .long("idle-timeout-ms")
+                .value_name("MS")
+                .help("Clients that don't send anything for this long get disconnected")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("receive-buffer-size")
+                .long("receive-buffer-size")
+                .value_name("BYTES")
+                .help("Specifies the max size of a received packet")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("world-update-budget")
+                .long("world-update-budget")
+                .value_name("BYTES")
+                .help("Splits world updates into messages of at most this many bytes")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("mtu")
+                .long("mtu")
+                .value_name("BYTES")
+                .help("Splits larger messages into fragments")
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("simulate-network")
+                .long("simulate-network")
+                .value_name("CONDITIONS")
+                .help(
+                    "Simulates a bad network, e.g. latency=150,jitter=30,loss=5,duplication=1,\
+                     reordering=2 (milliseconds and percents)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            clap::Arg::with_name("simulate-network-seed")
+                .long("simulate-network-seed")
+                .value_name("SEED")
+                .help("Makes --simulate-network lose and delay the same datagrams on every run")
+                .takes_value(true)
+                .requires("simulate-network"),
+        )
         .get_matches();
 
-    let socket_addr = cli_matches
-        .value_of("addr")
-        .expect("Expected a default value if not passed via CLI");
-    let client_addr = cli_matches.value_of("host-client-addr");
-    let client_addr = if let Some(client_addr) = client_addr {
-        HostClientAddress(Some(client_addr.parse()?))
-    } else {
-        HostClientAddress(None)
-    };
-
     let logging_config: LoggerConfig = ::std::fs::read_to_string("server_logging_config.toml")
         .map_err(|err| {
             log::warn!(
@@ -83,7 +250,53 @@ fn main() -> amethyst::Result<()> {
     })
     .start();
 
+    let server_config = ServerConfig::load(&cli_matches)?;
+    let client_addr = cli_matches.value_of("host-client-addr");
+    let client_addr = if let Some(client_addr) = client_addr {
+        if server_config.dedicated {
+            return Err(Error::from_string(
+                "A server started by a hosting client can't be dedicated",
+            ));
+        }
+        HostClientAddress(Some(client_addr.parse()?))
+    } else {
+        HostClientAddress(None)
+    };
+    let network_conditions = cli_matches
+        .value_of("simulate-network")
+        .map(|conditions| conditions.parse::<NetworkConditions>())
+        .transpose()
+        .map_err(|err| Error::from_string(format!("Invalid --simulate-network: {}", err)))?
+        .unwrap_or_default();
+    let network_seed = cli_matches
+        .value_of("simulate-network-seed")
+        .map(|seed| seed.parse::<u64>())
+        .transpose()
+        .map_err(|err| Error::from_string(format!("Invalid --simulate-network-seed: {}", err)))?;
+    if network_conditions.is_enabled() {
+        log::warn!("Simulating network conditions: {}", network_conditions);
+    }
+    log::info!(
+        "Protocol version: {}, build: {}",
+        PROTOCOL_VERSION,
+        BUILD_HASH
+    );
+    log::info!("Effective server config:\n{}", server_config);
+
+    let dedicated_server = DedicatedServer::from_config(&server_config);
+    let admin_commands = if server_config.console {
+        console::spawn_stdin_console()
+    } else {
+        AdminCommands::default()
+    };
+    let metrics_export = if let Some(metrics_addr) = server_config.metrics_addr {
+        metrics::spawn_metrics_server(metrics_addr)?
+    } else {
+        MetricsExport::default()
+    };
+
     let mut builder = Application::build("./", LoadingState::default())?;
+    register_definitions(&mut builder.world)?;
     builder
         .world
         .insert(FramedUpdates::<DummyFramedUpdate>::default());
@@ -91,39 +304,73 @@ fn main() -> amethyst::Result<()> {
         .world
         .insert(FramedUpdates::<ReceivedClientActionUpdates>::default());
     builder.world.insert(client_addr);
+    builder.world.insert(dedicated_server);
+    builder.world.insert(admin_commands);
+    builder.world.insert(metrics_export);
+    builder.world.insert(ServerMetrics::default());
+    builder.world.insert(server_config.game_mode);
     builder.world.insert(ServerWorldUpdates::default());
     builder.world.insert(LastBroadcastedFrame(0));
+    builder.world.insert(PlayerReconnectTokens::default());
+    builder.world.insert(PendingWorldSnapshots::default());
 
     let laminar_config = LaminarConfig {
-        receive_buffer_max_size: 14_500,
+        idle_connection_timeout: server_config.idle_connection_timeout,
+        receive_buffer_max_size: server_config.receive_buffer_max_size,
         ..LaminarConfig::default()
     };
 
-    let socket = LaminarSocket::bind_with_config(socket_addr, laminar_config)?;
+    let socket = LaminarSocket::bind_with_config(server_config.addr, laminar_config)?;
+    let tick_rate = server_config.tick_rate;
+    builder.world.insert(server_config);
 
     let mut game_data_builder = GameDataBuilder::default()
-        .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
+        .with_bundle(
+            TransportBundle::new(LaminarTransport::new(socket)?)
+                .with_network_conditions(network_conditions)
+                .with_network_seed(network_seed),
+        )?
         .with(
             NetConnectionManagerDesc::default().build(&mut builder.world),
             "net_connection_manager_system",
             &[],
         )
-        .with(ServerNetworkSystem::new(), "game_network_system", &[]);
+        .with(ServerNetworkSystem::new(), "game_network_system", &[])
+        .with(
+            LobbySystem::default(),
+            "lobby_system",
+            &["game_network_system"],
+        )
+        .with(
+            DedicatedServerSystem::default(),
+            "dedicated_server_system",
+            &["game_network_system"],
+        );
     game_data_builder = build_game_logic_systems(game_data_builder, &mut builder.world, true)?
         .with(
             WorldPositionTransformSystem,
             "world_position_transform_system",
             &["action_system"],
         )
+        .with(
+            WorldSnapshotSystem,
+            "world_snapshot_system",
+            &["action_system"],
+        )
         .with(
             GameUpdatesBroadcastingSystem::default(),
             "game_updates_broadcasting_system",
-            &["action_system"],
+            &["world_snapshot_system"],
+        )
+        .with(
+            MetricsSystem::default(),
+            "metrics_system",
+            &["game_updates_broadcasting_system"],
         )
         .with_bundle(TransformBundle::new().with_dep(&["world_position_transform_system"]))?;
 
     let mut game = builder
-        .with_frame_limit(FrameRateLimitStrategy::Yield, 60)
+        .with_frame_limit(FrameRateLimitStrategy::Yield, tick_rate)
         .build(game_data_builder)?;
     game.run();
     Ok(())
@@ -0,0 +1,157 @@
+use amethyst::Error;
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::ecs::resources::MetricsExport;
+
+/// Requests (including headers) are truncated to this size.
+const MAX_REQUEST_BYTES: u64 = 8192;
+/// Connections are served one at a time, so a client can't hold one for longer than this.
+const CONNECTION_DEADLINE: Duration = Duration::from_secs(2);
+
+/// Serves the metrics that MetricsSystem renders at `http://<addr>/metrics`.
+pub fn spawn_metrics_server(addr: SocketAddr) -> amethyst::Result<MetricsExport> {
+    let listener = TcpListener::bind(addr).map_err(|err| {
+        Error::from_string(format!(
+            "Failed to bind the metrics endpoint to {}: {}",
+            addr, err
+        ))
+    })?;
+    if !addr.ip().is_loopback() {
+        log::warn!(
+            "The metrics endpoint is reachable from other hosts: {}",
+            addr
+        );
+    }
+
+    let metrics_export = MetricsExport::enabled();
+    let served_metrics = metrics_export.clone();
+    thread::Builder::new()
+        .name("metrics_server".to_owned())
+        .spawn(move || {
+            log::info!("Serving metrics at http://{}/metrics", addr);
+            for stream in listener.incoming() {
+                if let Err(err) = stream.and_then(|stream| respond(stream, &served_metrics)) {
+                    log::warn!("Failed to serve metrics: {:?}", err);
+                }
+            }
+        })
+        .expect("Expected to spawn the metrics server thread");
+    Ok(metrics_export)
+}
+
+fn respond(mut stream: TcpStream, metrics_export: &MetricsExport) -> io::Result<()> {
+    let deadline = Instant::now() + CONNECTION_DEADLINE;
+
+    let request_line = {
+        let mut reader = BufReader::new(
+            DeadlineReader {
+                stream: &stream,
+                deadline,
+            }
+            .take(MAX_REQUEST_BYTES),
+        );
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are of no interest, but a client might not expect them to stay unread.
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+            header.clear();
+        }
+        request_line
+    };
+
+    let (status, body) = if request_line.starts_with("GET /metrics ") {
+        ("200 OK", metrics_export.text())
+    } else {
+        (
+            "404 Not Found",
+            "The metrics are served at /metrics\n".to_owned(),
+        )
+    };
+    stream.set_write_timeout(Some(time_left(deadline)?))?;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Makes every read fail once the deadline passes, however slowly a client sends data.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl<'a> Read for DeadlineReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream
+            .set_read_timeout(Some(time_left(self.deadline)?))?;
+        self.stream.read(buf)
+    }
+}
+
+fn time_left(deadline: Instant) -> io::Result<Duration> {
+    deadline
+        .checked_duration_since(Instant::now())
+        .filter(|time_left| *time_left > Duration::from_secs(0))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "the connection deadline passed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serve_one(client: impl FnOnce(TcpStream) + Send + 'static) -> io::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || client(TcpStream::connect(addr).unwrap()));
+
+        let (stream, _) = listener.accept().unwrap();
+        let metrics_export = MetricsExport::enabled();
+        metrics_export.set_text("gv_players 1\n".to_owned());
+        let result = respond(stream, &metrics_export);
+        client.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn truncates_long_requests() {
+        let result = serve_one(|mut stream| {
+            // Headers never end, but the server stops reading exactly at the limit.
+            let request_line = "GET /metrics HTTP/1.1\r\nX-Padding: ";
+            let padding = "a".repeat(MAX_REQUEST_BYTES as usize - request_line.len());
+            write!(stream, "{}{}", request_line, padding).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 200 OK"));
+            assert!(response.ends_with("gv_players 1\n"));
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn drops_slow_clients() {
+        let result = serve_one(|mut stream| {
+            let started_at = Instant::now();
+            write!(stream, "GET /metrics HTTP/1.1\r\n").unwrap();
+            while started_at.elapsed() < CONNECTION_DEADLINE + Duration::from_millis(500) {
+                if write!(stream, "X").is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+        assert!(result.is_err());
+    }
+}
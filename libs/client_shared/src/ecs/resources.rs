@@ -4,10 +4,10 @@ use amethyst::{
     ui::FontHandle,
 };
 
-use std::{io, time::Instant};
+use std::{collections::HashMap, io, time::Instant};
 
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
-use gv_core::net::{server_message::DisconnectReason, NetIdentifier};
+use gv_core::net::{server_message::DisconnectReason, NetIdentifier, DEFAULT_MAX_PLAYERS};
 
 pub const HEALTH_UI_SCREEN_PADDING: f32 = 40.0;
 
@@ -18,8 +18,10 @@ pub struct DummyAssetHandles {
 #[derive(Clone)]
 pub struct AssetHandles {
     pub mage_prefab: Handle<Prefab<GameSpriteAnimationPrefab>>,
-    pub beetle_prefab: Handle<Prefab<GameSpriteAnimationPrefab>>,
-    pub landscape: Handle<SpriteSheet>,
+    /// Is keyed by monster types.
+    pub monster_prefabs: HashMap<String, Handle<Prefab<GameSpriteAnimationPrefab>>>,
+    /// Is keyed by landscape sprite sheet paths.
+    pub landscapes: HashMap<String, Handle<SpriteSheet>>,
     pub ui_font: FontHandle,
 }
 
@@ -32,8 +34,14 @@ pub struct EntityGraphics {
 pub struct MultiplayerRoomState {
     pub is_active: bool,
     pub is_host: bool,
+    /// Spectators watch a game without controlling any of the players.
+    pub is_spectator: bool,
     pub connection_status: ConnectionStatus,
     pub player_net_id: NetIdentifier,
+    /// The room size of the server we're connected to.
+    pub max_players: usize,
+    /// Seconds left before a game starts if all the players in the room are ready.
+    pub lobby_countdown: Option<u64>,
 }
 
 impl MultiplayerRoomState {
@@ -41,8 +49,11 @@ impl MultiplayerRoomState {
         Self {
             is_active: false,
             is_host: false,
+            is_spectator: false,
             connection_status: ConnectionStatus::NotConnected,
             player_net_id: 0,
+            max_players: DEFAULT_MAX_PLAYERS,
+            lobby_countdown: None,
         }
     }
 
@@ -1,4 +1,5 @@
 #![allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub mod ecs;
+pub mod profile;
 pub mod settings;
 pub mod utils;
@@ -0,0 +1,104 @@
+use ron::ser::PrettyConfig;
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use gv_core::progression::{
+    level_for_experience, PlayerStats, Stat, MAX_STAT_RANK, SKILL_POINTS_PER_LEVEL,
+};
+
+/// Character progression persisted between runs, is stored next to the other configs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(skip)]
+    path: PathBuf,
+    pub experience: u64,
+    pub level: u32,
+    pub unspent_points: u32,
+    pub stats: PlayerStats,
+    /// Is issued by the last joined server, lets a player rejoin a running game after a restart.
+    #[serde(default)]
+    pub reconnect_token: Option<ReconnectToken>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReconnectToken {
+    pub server_addr: SocketAddr,
+    pub token: u64,
+}
+
+impl Profile {
+    /// Falls back to a fresh profile if the file doesn't exist or is corrupted.
+    pub fn load(config_dir: &Path) -> Self {
+        let path = profile_path(config_dir);
+        let profile = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                ron::de::from_str::<Profile>(&contents).map_err(|err| err.to_string())
+            });
+        match profile {
+            Ok(mut profile) => {
+                profile.path = path;
+                profile.stats = profile.stats.clamped();
+                profile
+            }
+            Err(err) => {
+                log::warn!("Failed to load the profile, starting a new one: {}", err);
+                Self {
+                    path,
+                    experience: 0,
+                    level: 1,
+                    unspent_points: 0,
+                    stats: PlayerStats::default(),
+                    reconnect_token: None,
+                }
+            }
+        }
+    }
+
+    pub fn save(&self) -> amethyst::Result<()> {
+        if let Some(config_dir) = self.path.parent() {
+            fs::create_dir_all(config_dir)?;
+        }
+        fs::write(
+            &self.path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    pub fn reconnect_token(&self, server_addr: SocketAddr) -> Option<u64> {
+        self.reconnect_token
+            .filter(|reconnect_token| reconnect_token.server_addr == server_addr)
+            .map(|reconnect_token| reconnect_token.token)
+    }
+
+    /// Returns the number of levels gained.
+    pub fn add_experience(&mut self, experience: u64) -> u32 {
+        self.experience += experience;
+        let new_level = level_for_experience(self.experience);
+        let levels_gained = new_level.saturating_sub(self.level);
+        self.level = new_level.max(self.level);
+        self.unspent_points += levels_gained * SKILL_POINTS_PER_LEVEL;
+        levels_gained
+    }
+
+    /// Returns false if there are no points to spend or the stat is already maxed out.
+    pub fn spend_point(&mut self, stat: Stat) -> bool {
+        let rank = self.stats.rank_mut(stat);
+        if self.unspent_points == 0 || *rank >= MAX_STAT_RANK {
+            return false;
+        }
+        *rank += 1;
+        self.unspent_points -= 1;
+        true
+    }
+}
+
+fn profile_path(config_dir: &Path) -> PathBuf {
+    config_dir.join("profile.ron")
+}
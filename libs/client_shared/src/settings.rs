@@ -7,7 +7,10 @@ use amethyst::{
 use directories::ProjectDirs;
 use ron::ser::PrettyConfig;
 
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 static DEFAULT_BINDINGS_CONFIG_BYTES: &[u8] =
     include_bytes!("../../../resources/bindings_config.ron");
@@ -118,6 +121,10 @@ impl Settings {
         }
     }
 
+    pub fn config_dir(&self) -> &Path {
+        self.project_dirs.config_dir()
+    }
+
     pub fn display(&self) -> &DisplayConfig {
         &self.display
     }
@@ -0,0 +1,22 @@
+use std::process::Command;
+
+fn main() {
+    // Lets builds made outside of a git checkout (CI artifacts, for example) identify themselves.
+    println!("cargo:rerun-if-env-changed=GV_BUILD_HASH");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs/heads");
+
+    let build_hash = std::env::var("GV_BUILD_HASH")
+        .ok()
+        .or_else(|| {
+            Command::new("git")
+                .args(&["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+                .map(|hash| hash.trim().to_owned())
+        })
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=GV_BUILD_HASH={}", build_hash);
+}
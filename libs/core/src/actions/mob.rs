@@ -84,18 +84,27 @@ pub enum MobAttackType {
     SlowMelee {
         cooldown: f32,
     },
-    #[allow(dead_code)]
-    Range,
+    /// Fires projectiles at a target, keeping the specified distance.
+    Range {
+        cooldown: f32,
+        distance: f32,
+    },
 }
 
 impl MobAttackType {
-    pub fn randomize_params(&self, factor: f32) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn randomize_params<R: Rng + ?Sized>(&self, factor: f32, rng: &mut R) -> Self {
         match self {
             MobAttackType::SlowMelee { cooldown } => {
                 let cooldown = rng.gen_range(cooldown * (1.0 - factor), cooldown * (1.0 + factor));
                 MobAttackType::SlowMelee { cooldown }
             }
+            MobAttackType::Range { cooldown, distance } => {
+                let cooldown = rng.gen_range(cooldown * (1.0 - factor), cooldown * (1.0 + factor));
+                MobAttackType::Range {
+                    cooldown,
+                    distance: *distance,
+                }
+            }
             other => other.clone(),
         }
     }
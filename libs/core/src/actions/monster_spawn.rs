@@ -27,8 +27,12 @@ impl FramedUpdate for SpawnActions {
     }
 }
 
+/// An index of a monster definition, definitions are sorted by their names.
+pub type MonsterTypeId = u16;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpawnAction {
+    pub monster_type: MonsterTypeId,
     pub spawn_type: SpawnType,
 }
 
@@ -48,15 +48,34 @@ impl Default for PlayerLookAction {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SpellId {
+    Missile,
+    Fireball,
+    FireNova,
+}
+
+impl SpellId {
+    pub const ALL: [SpellId; 3] = [SpellId::Missile, SpellId::Fireball, SpellId::FireNova];
+}
+
+impl Default for SpellId {
+    fn default() -> Self {
+        Self::Missile
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerCastAction {
+    pub spell: SpellId,
     pub cast_position: Vector2,
     pub target_position: Vector2,
 }
 
 impl PartialEq for PlayerCastAction {
     fn eq(&self, other: &Self) -> bool {
-        (self.cast_position - other.cast_position).norm_squared() < 0.001
+        self.spell == other.spell
+            && (self.cast_position - other.cast_position).norm_squared() < 0.001
             && (self.target_position - other.target_position).norm_squared() < 0.001
     }
 }
@@ -0,0 +1,35 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+
+use crate::net::NetIdentifier;
+
+/// A blast expanding from its WorldPosition, damages every monster once when reaching it.
+#[derive(Clone, Debug, Component)]
+pub struct AreaSpell {
+    pub action_id: NetIdentifier,
+    pub radius: f32,
+    pub damage: f32,
+    pub frame_spawned: u64,
+    /// Entity net id of the caster, is None in single player.
+    pub caster: Option<NetIdentifier>,
+    /// Is a part of the saved world state, so a re-simulated frame doesn't damage monsters twice.
+    pub hit_monsters: Vec<Entity>,
+}
+
+impl AreaSpell {
+    pub fn new(
+        action_id: NetIdentifier,
+        radius: f32,
+        damage: f32,
+        frame_spawned: u64,
+        caster: Option<NetIdentifier>,
+    ) -> Self {
+        Self {
+            action_id,
+            radius,
+            damage,
+            frame_spawned,
+            caster,
+            hit_monsters: Vec::new(),
+        }
+    }
+}
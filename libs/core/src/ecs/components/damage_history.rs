@@ -1,6 +1,8 @@
 use amethyst::ecs::prelude::{Component, DenseVecStorage, FlaggedStorage};
 use serde_derive::{Deserialize, Serialize};
 
+use crate::net::NetIdentifier;
+
 pub struct DamageHistory {
     pub history: Vec<DamageHistoryEntries>,
 }
@@ -94,4 +96,7 @@ impl DamageHistoryEntries {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DamageHistoryEntry {
     pub damage: f32,
+    /// Entity net id of a player who has dealt the damage with a spell,
+    /// is None for damage dealt by monsters and in single player.
+    pub caster: Option<NetIdentifier>,
 }
@@ -0,0 +1,33 @@
+use amethyst::ecs::{Component, DenseVecStorage, Entity};
+
+use crate::math::Vector2;
+
+/// Is fired by ranged monsters, flies straight and damages the first player it hits.
+#[derive(Clone, Debug, Component)]
+pub struct EnemyProjectile {
+    /// A monster that fired the projectile, together with `frame_spawned` identifies
+    /// the projectile when re-simulating frames.
+    pub owner: Entity,
+    pub radius: f32,
+    pub velocity: Vector2,
+    pub frame_spawned: u64,
+    pub damage: f32,
+}
+
+impl EnemyProjectile {
+    pub fn new(
+        owner: Entity,
+        radius: f32,
+        velocity: Vector2,
+        frame_spawned: u64,
+        damage: f32,
+    ) -> Self {
+        Self {
+            owner,
+            radius,
+            velocity,
+            frame_spawned,
+            damage,
+        }
+    }
+}
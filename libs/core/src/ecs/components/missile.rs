@@ -1,4 +1,5 @@
 use amethyst::ecs::{Component, DenseVecStorage, Entity};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::{math::Vector2, net::NetIdentifier};
 
@@ -10,6 +11,8 @@ pub struct Missile {
     pub velocity: Vector2,
     pub frame_spawned: u64,
     pub damage: f32,
+    /// Entity net id of the caster, is None in single player.
+    pub caster: Option<NetIdentifier>,
 }
 
 impl Missile {
@@ -19,6 +22,8 @@ impl Missile {
         target: MissileTarget<Entity>,
         velocity: Vector2,
         frame_spawned: u64,
+        damage: f32,
+        caster: Option<NetIdentifier>,
     ) -> Self {
         Self {
             action_id,
@@ -26,12 +31,13 @@ impl Missile {
             target,
             velocity,
             frame_spawned,
-            damage: 50.0,
+            damage,
+            caster,
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MissileTarget<T> {
     Target(T),
     Destination(Vector2),
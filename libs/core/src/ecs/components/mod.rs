@@ -1,4 +1,6 @@
+pub mod area_spell;
 pub mod damage_history;
+pub mod enemy_projectile;
 pub mod missile;
 
 use amethyst::ecs::{Component, DenseVecStorage, Entity, VecStorage};
@@ -14,11 +16,15 @@ use std::{
 use crate::{
     actions::{
         mob::MobAction,
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction, SpellId},
         Action,
     },
     math::{Vector2, ZeroVector},
-    net::NetIdentifier,
+    net::{
+        fragmentation::DEFAULT_MTU, world_update_encoding::WorldUpdatePositionsHistory,
+        NetIdentifier,
+    },
+    progression::PlayerStats,
 };
 
 const PING_PONG_STORAGE_LIMIT: usize = 20;
@@ -64,27 +70,31 @@ impl From<WorldPosition> for NetWorldPosition {
 #[derive(Clone, Debug, Component)]
 pub struct Player {
     pub health: f32,
+    pub mana: f32,
     pub velocity: Vector2,
     pub walking_direction: Vector2,
     pub looking_direction: Vector2,
     pub radius: f32,
+    pub stats: PlayerStats,
 }
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(stats: PlayerStats) -> Self {
         Self {
-            health: 100.0,
+            health: stats.max_health(),
+            mana: stats.max_mana(),
             velocity: Vector2::zero(),
             walking_direction: Vector2::new(0.0, 1.0),
             looking_direction: Vector2::new(0.0, 1.0),
             radius: 20.0,
+            stats,
         }
     }
 }
 
 impl Default for Player {
     fn default() -> Self {
-        Self::new()
+        Self::new(PlayerStats::default())
     }
 }
 
@@ -103,12 +113,30 @@ pub struct ClientPlayerActions {
     pub walk_action: PlayerWalkAction,
     pub look_action: PlayerLookAction,
     pub cast_action: Option<PlayerCastAction>,
+    pub selected_spell: SpellId,
 }
 
-/// Stores frame numbers.
+/// Stores frame numbers of the last casts of each spell, indexed by `SpellId`.
 #[derive(Clone, Default, Component)]
 pub struct PlayerLastCastedSpells {
-    pub missile: u64,
+    frames: [u64; SpellId::ALL.len()],
+}
+
+impl PlayerLastCastedSpells {
+    /// Returns 0 for the spells that haven't been casted yet.
+    pub fn frame(&self, spell: SpellId) -> u64 {
+        self.frames[spell as usize]
+    }
+
+    pub fn set_frame(&mut self, spell: SpellId, frame_number: u64) {
+        self.frames[spell as usize] = frame_number;
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = (SpellId, u64)> + '_ {
+        SpellId::ALL
+            .iter()
+            .map(move |spell| (*spell, self.frame(*spell)))
+    }
 }
 
 #[derive(Clone, Debug, Component)]
@@ -131,6 +159,8 @@ pub struct Dead {
     /// This field reflects a client's game_frame_number, when a death is acknowledged, so it can be
     /// properly processed.
     pub frame_acknowledged: u64,
+    /// Entity net id of a player who has dealt the killing blow, is None in single player.
+    pub killer: Option<NetIdentifier>,
 }
 
 impl Dead {
@@ -138,6 +168,7 @@ impl Dead {
         Self {
             dead_since_frame,
             frame_acknowledged,
+            killer: None,
         }
     }
 
@@ -152,10 +183,15 @@ pub struct NetConnectionModel {
     pub addr: SocketAddr,
     pub created_at: Instant,
     pub last_acknowledged_update: Option<u64>,
+    pub last_acknowledged_positions: u64,
+    /// Positions that world updates sent to a client were encoded with.
+    pub world_update_positions: WorldUpdatePositionsHistory,
     pub ping_pong_data: PingPongData,
     pub disconnected: bool,
     pub session_created_at: Duration,
     pub session_id: NetIdentifier,
+    /// Larger messages are split into fragments, see `net::fragmentation`.
+    pub mtu: usize,
 }
 
 impl NetConnectionModel {
@@ -165,10 +201,13 @@ impl NetConnectionModel {
             addr,
             created_at: Instant::now(),
             last_acknowledged_update: None,
+            last_acknowledged_positions: 0,
+            world_update_positions: WorldUpdatePositionsHistory::default(),
             ping_pong_data: PingPongData::new(),
             disconnected: false,
             session_created_at: Duration::new(0, 0),
             session_id,
+            mtu: DEFAULT_MTU,
         }
     }
 }
@@ -300,9 +339,31 @@ struct Pong {
     estimated_peer_frame_number: u64,
 }
 
-#[derive(Clone, Copy, Debug, Component)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Component)]
 #[storage(VecStorage)]
 pub struct EntityNetMetadata {
     pub id: NetIdentifier,
     pub spawned_frame_number: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_last_casted_spells_in_spell_order() {
+        let mut last_casted_spells = PlayerLastCastedSpells::default();
+        last_casted_spells.set_frame(SpellId::FireNova, 30);
+        last_casted_spells.set_frame(SpellId::Missile, 10);
+
+        assert_eq!(last_casted_spells.frame(SpellId::Fireball), 0);
+        assert_eq!(
+            last_casted_spells.frames().collect::<Vec<_>>(),
+            vec![
+                (SpellId::Missile, 10),
+                (SpellId::Fireball, 0),
+                (SpellId::FireNova, 30),
+            ]
+        );
+    }
+}
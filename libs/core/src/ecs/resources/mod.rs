@@ -1,13 +1,26 @@
 pub mod net;
 pub mod world;
 
-use std::time::{Duration, Instant};
+use amethyst::ecs::Entity;
+use rand::{Error, Rng, RngCore};
+use rand_chacha::{
+    rand_core::{RngCore as _, SeedableRng},
+    ChaCha20Rng,
+};
+use serde_derive::{Deserialize, Serialize};
 
-use crate::math::Vector2;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crate::{math::Vector2, net::NetIdentifier};
 
 pub struct GameTime {
     pub level_started_at: Duration,
     pub started_at_frame_number: u64,
+    /// Is non-zero only for clients that join a running game.
+    pub started_at_game_frame_number: u64,
     pub frames_skipped: u64,
 }
 
@@ -16,21 +29,162 @@ impl Default for GameTime {
         Self {
             level_started_at: Duration::new(0, 0),
             started_at_frame_number: 0,
+            started_at_game_frame_number: 0,
             frames_skipped: 0,
         }
     }
 }
 
+/// A seeded random number generator for everything that affects gameplay.
+/// Clients and a server share the seed, so the same inputs produce the same simulation.
+/// ChaCha20 is named explicitly (unlike StdRng, which may change between rand versions),
+/// so that replays and peers with different builds draw the same values.
+#[derive(Clone)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha20Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng_seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+        Self {
+            seed,
+            rng: ChaCha20Rng::from_seed(rng_seed),
+        }
+    }
+
+    pub fn from_state(state: GameRngState) -> Self {
+        let mut game_rng = Self::new(state.seed);
+        game_rng.rng.set_word_pos(u128::from(state.draws));
+        game_rng
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> GameRngState {
+        GameRngState {
+            seed: self.seed,
+            draws: self.rng.get_word_pos() as u64,
+        }
+    }
+
+    /// Creates an independent generator, seeded from this one.
+    pub fn split(&mut self) -> Self {
+        Self::new(self.rng.gen())
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Independent streams of gameplay randomness. A stream is drawn from either by every peer
+/// or by a server only, so that peers advance shared streams the same way.
+#[derive(Clone)]
+pub struct GameRngs {
+    /// Retargets missiles, is drawn from by clients and a server alike.
+    pub missiles: GameRng,
+    /// Picks monster actions, is drawn from only by a server (clients receive monster actions).
+    pub monsters: GameRng,
+}
+
+impl GameRngs {
+    pub fn split_from(game_rng: &mut GameRng) -> Self {
+        Self {
+            missiles: game_rng.split(),
+            monsters: game_rng.split(),
+        }
+    }
+
+    pub fn from_state(state: GameRngsState) -> Self {
+        Self {
+            missiles: GameRng::from_state(state.missiles),
+            monsters: GameRng::from_state(state.monsters),
+        }
+    }
+
+    pub fn state(&self) -> GameRngsState {
+        GameRngsState {
+            missiles: self.missiles.state(),
+            monsters: self.monsters.state(),
+        }
+    }
+}
+
+impl Default for GameRngs {
+    fn default() -> Self {
+        Self::split_from(&mut GameRng::default())
+    }
+}
+
+/// Is sent to clients rejoining a running game, see `GameRng::from_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameRngState {
+    pub seed: u64,
+    /// The number of 32-bit words drawn, a ChaCha generator can jump to any of them.
+    pub draws: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameRngsState {
+    pub missiles: GameRngState,
+    pub monsters: GameRngState,
+}
+
+/// rand_chacha implements a newer RngCore than the rand version we use, so we delegate to it.
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
 pub struct GameLevelState {
     pub dimensions: Vector2,
     pub is_over: bool,
+    pub monsters_killed: u32,
+    /// Is counted in multiplayer only, keyed by entity net ids of players.
+    pub monsters_killed_by_players: HashMap<NetIdentifier, u32>,
+    /// Dead monsters that have been counted already. Re-simulating a kill acknowledges it again,
+    /// so LevelObjectiveSystem keeps them until the entities get deleted.
+    pub counted_kills: HashSet<Entity>,
+    /// Level duration at the moment a campaign level objective got completed.
+    pub completed_at: Option<Duration>,
     pub spawn_level: usize,
     pub spawn_level_started: Duration,
-    pub last_borderline_spawn: Duration,
-    pub last_random_spawn: Duration,
+    /// Level durations of the last spawns, indexed the same way as waves in a wave script.
+    pub waves_last_spawned_at: Vec<Option<Duration>>,
+    /// Is used only by SpawnDirectorSystem, which doesn't re-run frames,
+    /// so its state isn't stored in SavedWorldState (unlike the GameRngs resource).
+    pub spawn_rng: GameRng,
 }
 
 impl GameLevelState {
+    pub fn new(spawn_rng: GameRng) -> Self {
+        Self {
+            spawn_rng,
+            ..Self::default()
+        }
+    }
+
     pub fn dimensions_half_size(&self) -> Vector2 {
         self.dimensions / 2.0
     }
@@ -41,10 +195,14 @@ impl Default for GameLevelState {
         Self {
             dimensions: Vector2::new(4096.0, 4096.0),
             is_over: false,
+            monsters_killed: 0,
+            monsters_killed_by_players: HashMap::new(),
+            counted_kills: HashSet::new(),
+            completed_at: None,
             spawn_level: 1,
             spawn_level_started: Duration::new(0, 0),
-            last_borderline_spawn: Duration::new(0, 0),
-            last_random_spawn: Duration::new(0, 0),
+            waves_last_spawned_at: Vec::new(),
+            spawn_rng: GameRng::default(),
         }
     }
 }
@@ -74,3 +232,78 @@ impl NewGameEngineState {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand_chacha::rand_core::RngCore as _;
+
+    fn draw(rng: &mut impl RngCore) -> (u32, u64, f32, [u8; 7]) {
+        let mut bytes = [0; 7];
+        rng.fill_bytes(&mut bytes);
+        (
+            rng.next_u32(),
+            rng.next_u64(),
+            rng.gen_range(0.0, 100.0),
+            bytes,
+        )
+    }
+
+    #[test]
+    fn restores_game_rng_from_state() {
+        let mut game_rng = GameRng::new(42);
+        for _ in 0..100 {
+            draw(&mut game_rng);
+        }
+
+        let mut restored_game_rng = GameRng::from_state(game_rng.state());
+        assert_eq!(restored_game_rng.state(), game_rng.state());
+        for _ in 0..100 {
+            assert_eq!(draw(&mut restored_game_rng), draw(&mut game_rng));
+        }
+    }
+
+    #[test]
+    fn restores_game_rngs_from_state() {
+        let mut game_rngs = GameRngs::split_from(&mut GameRng::new(42));
+        game_rngs.missiles.gen::<u64>();
+        for _ in 0..10 {
+            game_rngs.monsters.gen::<f32>();
+        }
+
+        let mut restored_game_rngs = GameRngs::from_state(game_rngs.state());
+        assert_eq!(restored_game_rngs.state(), game_rngs.state());
+        assert_eq!(
+            restored_game_rngs.missiles.gen::<u64>(),
+            game_rngs.missiles.gen::<u64>()
+        );
+        assert_eq!(
+            restored_game_rngs.monsters.gen::<u64>(),
+            game_rngs.monsters.gen::<u64>()
+        );
+    }
+
+    #[test]
+    fn counts_drawn_words() {
+        let mut game_rng = GameRng::new(42);
+        game_rng.next_u32();
+        game_rng.next_u64();
+        game_rng.fill_bytes(&mut [0; 7]);
+        assert_eq!(game_rng.state().draws, 5);
+    }
+
+    /// Recorded replays depend on GameRng drawing exactly what ChaCha20 does.
+    #[test]
+    fn draws_the_same_values_as_chacha20() {
+        let mut game_rng = GameRng::new(42);
+        let mut rng_seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng_seed[..8].copy_from_slice(&42u64.to_le_bytes());
+        let mut chacha_rng = ChaCha20Rng::from_seed(rng_seed);
+
+        for _ in 0..100 {
+            assert_eq!(game_rng.next_u32(), chacha_rng.next_u32());
+            assert_eq!(game_rng.next_u64(), chacha_rng.next_u64());
+        }
+    }
+}
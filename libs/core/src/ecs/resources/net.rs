@@ -6,8 +6,11 @@ use std::{collections::HashMap, ops::Range};
 
 use crate::{
     actions::{player::PlayerCastAction, IdentifiableAction},
+    ecs::resources::world::WorldSnapshot,
+    math::Vector2,
     net::{server_message::PlayerNetStatus, NetIdentifier},
-    PLAYER_COLORS,
+    player_color,
+    progression::PlayerStats,
 };
 
 #[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
@@ -17,22 +20,39 @@ pub struct MultiplayerRoomPlayer {
     pub entity_net_id: NetIdentifier,
     pub nickname: String,
     pub is_host: bool,
+    /// Games start only when every player in the room is ready.
+    pub is_ready: bool,
     #[derivative(PartialEq = "ignore")]
     pub color: [f32; 3],
+    pub stats: PlayerStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiplayerRoomSpectator {
+    pub connection_id: NetIdentifier,
+    pub nickname: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
 pub struct MultiplayerGameState {
     pub is_playing: bool,
     pub players: Vec<MultiplayerRoomPlayer>,
+    /// Spectators don't count towards the player limit and are never waited for.
+    pub spectators: Vec<MultiplayerRoomSpectator>,
     pub waiting_network: bool,
     pub waiting_for_players: bool,
+    /// Is set on client if a server operator has paused a game, there are no lagging players then.
+    pub is_paused_by_admin: bool,
     /// This is used on client to make sure that we do not unpause before pausing.
     pub waiting_for_players_pause_id: u64,
     /// To help keep the track of outdated status reports (they use unreliable channel).
     pub players_status_id: u64,
     pub lagging_players: Vec<NetIdentifier>,
     pub is_disconnected: bool,
+    /// Is generated by a server and is shared with clients in StartGame message.
+    pub game_seed: u64,
+    /// Is set on client when rejoining a running game, PlayingState creates the world from it.
+    pub world_snapshot: Option<WorldSnapshot>,
     players_updated: bool,
 }
 
@@ -41,12 +61,16 @@ impl MultiplayerGameState {
         Self {
             is_playing: false,
             players: Vec::new(),
+            spectators: Vec::new(),
             waiting_network: false,
             waiting_for_players: false,
+            is_paused_by_admin: false,
             waiting_for_players_pause_id: 0,
             players_status_id: 0,
             lagging_players: Vec::new(),
             is_disconnected: false,
+            game_seed: 0,
+            world_snapshot: None,
             players_updated: false,
         }
     }
@@ -88,7 +112,7 @@ impl MultiplayerGameState {
         self.players_updated = true;
         self.players.remove(player_index);
         for (player_index, player) in self.players.iter_mut().enumerate().skip(player_index) {
-            player.color = PLAYER_COLORS[player_index];
+            player.color = player_color(player_index);
         }
     }
 
@@ -100,6 +124,19 @@ impl MultiplayerGameState {
             .iter()
             .find(|player| player.connection_id == player_connection_id)
     }
+
+    pub fn is_spectator(&self, connection_id: NetIdentifier) -> bool {
+        self.spectators
+            .iter()
+            .any(|spectator| spectator.connection_id == connection_id)
+    }
+
+    pub fn drop_spectator_by_connection_id(&mut self, connection_id: NetIdentifier) -> bool {
+        let spectators_count = self.spectators.len();
+        self.spectators
+            .retain(|spectator| spectator.connection_id != connection_id);
+        self.spectators.len() != spectators_count
+    }
 }
 
 pub struct EntityNetMetadataStorage {
@@ -165,7 +202,27 @@ impl ActionUpdateIdProvider {
 
 #[derive(Default)]
 pub struct CastActionsToExecute {
-    pub actions: Vec<IdentifiableAction<PlayerCastAction>>,
+    pub actions: Vec<CastActionToExecute>,
+}
+
+pub struct CastActionToExecute {
+    pub action: IdentifiableAction<PlayerCastAction>,
+    /// Depends on caster's stats.
+    pub damage: f32,
+    /// Entity net id of the caster, is None in single player.
+    pub caster: Option<NetIdentifier>,
+}
+
+#[derive(Default)]
+pub struct RangedAttacksToExecute {
+    pub attacks: Vec<RangedAttackToExecute>,
+}
+
+pub struct RangedAttackToExecute {
+    pub monster: Entity,
+    pub position: Vector2,
+    pub target_position: Vector2,
+    pub damage: f32,
 }
 
 #[derive(Default)]
@@ -1,23 +1,38 @@
 use amethyst::ecs::{Component, Entities, Entity, Join, ReadStorage, WriteStorage};
 use serde_derive::{Deserialize, Serialize};
 
-use std::{collections::VecDeque, iter::FromIterator};
+use std::{
+    collections::{HashMap, VecDeque},
+    iter::FromIterator,
+    time::Duration,
+};
 
 use crate::{
     actions::{
         mob::MobAction,
         monster_spawn::SpawnAction,
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
-        ClientActionUpdate, IdentifiableAction,
+        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction, SpellId},
+        Action, ClientActionUpdate, IdentifiableAction,
     },
-    ecs::components::{
-        damage_history::DamageHistoryEntries, missile::Missile, Dead, Monster, Player,
-        PlayerActions, PlayerLastCastedSpells, WorldPosition,
+    ecs::{
+        components::{
+            area_spell::AreaSpell,
+            damage_history::DamageHistoryEntries,
+            enemy_projectile::EnemyProjectile,
+            missile::{Missile, MissileTarget},
+            Dead, EntityNetMetadata, Monster, Player, PlayerActions, PlayerLastCastedSpells,
+            WorldPosition,
+        },
+        resources::{GameLevelState, GameRngs, GameRngsState},
     },
+    math::Vector2,
     net::{NetIdentifier, NetUpdate, NetUpdateWithPosition},
+    progression::PlayerStats,
 };
 
 pub const SAVED_WORLD_STATES_LIMIT: usize = 600;
+/// Limits the number of entities in a single WorldSnapshot message, so it fits into a packet.
+pub const WORLD_SNAPSHOT_CHUNK_ENTITIES: usize = 32;
 pub const LAG_COMPENSATION_FRAMES_LIMIT: usize = 20;
 pub const PAUSE_FRAME_THRESHOLD: u64 =
     (LAG_COMPENSATION_FRAMES_LIMIT + LAG_COMPENSATION_FRAMES_LIMIT / 2) as u64;
@@ -33,6 +48,13 @@ pub struct WorldStates {
 }
 
 impl WorldStates {
+    /// Clients joining a running game don't have the states of earlier frames.
+    pub fn starting_from(frame_number: u64) -> Self {
+        let mut world_states = Self::default();
+        world_states.world_states[0].frame_number = frame_number;
+        world_states
+    }
+
     pub fn add_world_state(&mut self, mut world_state: SavedWorldState) {
         if self.world_states.is_empty() {
             world_state.frame_number = 0;
@@ -115,8 +137,12 @@ pub struct SavedWorldState {
     pub player_last_casted_spells: Vec<(Entity, PlayerLastCastedSpells)>,
     pub monsters: Vec<(Entity, Monster)>,
     pub missiles: Vec<(Entity, Missile)>,
+    pub area_spells: Vec<(Entity, AreaSpell)>,
+    pub enemy_projectiles: Vec<(Entity, EnemyProjectile)>,
     pub world_positions: Vec<(Entity, WorldPosition)>,
     pub dead: Vec<(Entity, Dead)>,
+    /// Is `None` only for the initial world state, so loading it keeps the session seed.
+    pub rngs: Option<GameRngs>,
 }
 
 impl SavedWorldState {
@@ -163,6 +189,16 @@ pub struct FramedUpdates<T> {
 }
 
 impl<T: FramedUpdate + ::std::fmt::Debug> FramedUpdates<T> {
+    /// Clients joining a running game don't have the updates of earlier frames.
+    pub fn starting_from(frame_number: u64) -> Self {
+        let mut framed_updates = Self::default();
+        framed_updates.oldest_updated_frame = frame_number;
+        framed_updates
+            .updates
+            .push_back(T::new_update(frame_number));
+        framed_updates
+    }
+
     pub fn reserve_updates(&mut self, frame_number: u64) {
         if frame_number == 0 && self.updates.is_empty() {
             self.add_update();
@@ -450,6 +486,243 @@ impl ServerWorldUpdate {
     }
 }
 
+/// Is sent by server to players rejoining a running game, contains the world state
+/// after simulating `frame_number`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub frame_number: u64,
+    /// The latest ServerWorldUpdate id, a client receives only newer updates after the snapshot.
+    pub update_id: u64,
+    /// Clients draw from the shared streams too, so they have to continue from the same state.
+    pub rngs: GameRngsState,
+    pub level_progress: LevelProgressSnapshot,
+    pub players: Vec<PlayerSnapshot>,
+    pub monsters: Vec<MonsterSnapshot>,
+    pub missiles: Vec<MissileSnapshot>,
+    pub area_spells: Vec<AreaSpellSnapshot>,
+    pub enemy_projectiles: Vec<EnemyProjectileSnapshot>,
+}
+
+impl WorldSnapshot {
+    /// Splits the snapshot into chunks of at most `WORLD_SNAPSHOT_CHUNK_ENTITIES` entities,
+    /// there's always at least one chunk.
+    pub fn into_chunks(self) -> Vec<WorldSnapshot> {
+        let WorldSnapshot {
+            frame_number,
+            update_id,
+            rngs,
+            level_progress,
+            players,
+            monsters,
+            missiles,
+            area_spells,
+            enemy_projectiles,
+        } = self;
+        let mut chunks = vec![WorldSnapshot {
+            frame_number,
+            update_id,
+            rngs,
+            level_progress,
+            players: Vec::new(),
+            monsters: Vec::new(),
+            missiles: Vec::new(),
+            area_spells: Vec::new(),
+            enemy_projectiles: Vec::new(),
+        }];
+
+        for player in players {
+            Self::last_chunk(&mut chunks).players.push(player);
+        }
+        for monster in monsters {
+            Self::last_chunk(&mut chunks).monsters.push(monster);
+        }
+        for missile in missiles {
+            Self::last_chunk(&mut chunks).missiles.push(missile);
+        }
+        for area_spell in area_spells {
+            Self::last_chunk(&mut chunks).area_spells.push(area_spell);
+        }
+        for enemy_projectile in enemy_projectiles {
+            Self::last_chunk(&mut chunks)
+                .enemy_projectiles
+                .push(enemy_projectile);
+        }
+        chunks
+    }
+
+    /// Adds a new chunk if the last one is full.
+    fn last_chunk(chunks: &mut Vec<WorldSnapshot>) -> &mut WorldSnapshot {
+        let last_chunk = chunks.last().expect("Expected at least one chunk");
+        if last_chunk.entities_count() == WORLD_SNAPSHOT_CHUNK_ENTITIES {
+            let new_chunk = WorldSnapshot {
+                frame_number: last_chunk.frame_number,
+                update_id: last_chunk.update_id,
+                rngs: last_chunk.rngs,
+                level_progress: last_chunk.level_progress.clone(),
+                players: Vec::new(),
+                monsters: Vec::new(),
+                missiles: Vec::new(),
+                area_spells: Vec::new(),
+                enemy_projectiles: Vec::new(),
+            };
+            chunks.push(new_chunk);
+        }
+        chunks.last_mut().unwrap()
+    }
+
+    fn entities_count(&self) -> usize {
+        self.players.len()
+            + self.monsters.len()
+            + self.missiles.len()
+            + self.area_spells.len()
+            + self.enemy_projectiles.len()
+    }
+
+    fn merge(&mut self, chunk: WorldSnapshot) {
+        self.players.extend(chunk.players);
+        self.monsters.extend(chunk.monsters);
+        self.missiles.extend(chunk.missiles);
+        self.area_spells.extend(chunk.area_spells);
+        self.enemy_projectiles.extend(chunk.enemy_projectiles);
+    }
+}
+
+/// Collects the chunks of a WorldSnapshot, which may arrive in any order.
+#[derive(Default)]
+pub struct WorldSnapshotChunks {
+    frame_number: u64,
+    chunks: Vec<Option<WorldSnapshot>>,
+}
+
+impl WorldSnapshotChunks {
+    /// Returns the whole snapshot once all its chunks are received. Chunks of an older snapshot
+    /// are discarded if a newer one starts arriving.
+    pub fn add_chunk(
+        &mut self,
+        chunk_index: usize,
+        chunks_count: usize,
+        chunk: WorldSnapshot,
+    ) -> Option<WorldSnapshot> {
+        if chunk_index >= chunks_count {
+            log::warn!(
+                "Ignoring an invalid WorldSnapshot chunk ({} of {})",
+                chunk_index,
+                chunks_count
+            );
+            return None;
+        }
+        if self.frame_number != chunk.frame_number || self.chunks.len() != chunks_count {
+            self.frame_number = chunk.frame_number;
+            self.chunks = vec![None; chunks_count];
+        }
+        self.chunks[chunk_index] = Some(chunk);
+
+        if self.chunks.iter().any(Option::is_none) {
+            return None;
+        }
+        let mut chunks = self.chunks.drain(..).map(Option::unwrap);
+        let mut world_snapshot = chunks.next().unwrap();
+        for chunk in chunks {
+            world_snapshot.merge(chunk);
+        }
+        Some(world_snapshot)
+    }
+
+    pub fn reset(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+/// Kill counts and objective progress of a level, see `GameLevelState`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelProgressSnapshot {
+    pub monsters_killed: u32,
+    pub monsters_killed_by_players: HashMap<NetIdentifier, u32>,
+    pub completed_at: Option<Duration>,
+}
+
+impl LevelProgressSnapshot {
+    pub fn new(game_level_state: &GameLevelState) -> Self {
+        Self {
+            monsters_killed: game_level_state.monsters_killed,
+            monsters_killed_by_players: game_level_state.monsters_killed_by_players.clone(),
+            completed_at: game_level_state.completed_at,
+        }
+    }
+
+    pub fn apply(&self, game_level_state: &mut GameLevelState) {
+        game_level_state.monsters_killed = self.monsters_killed;
+        game_level_state.monsters_killed_by_players = self.monsters_killed_by_players.clone();
+        game_level_state.completed_at = self.completed_at;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub net_metadata: EntityNetMetadata,
+    pub health: f32,
+    pub mana: f32,
+    pub velocity: Vector2,
+    pub walking_direction: Vector2,
+    pub looking_direction: Vector2,
+    pub stats: PlayerStats,
+    pub actions: PlayerActions,
+    pub last_casted_spells: Vec<(SpellId, u64)>,
+    pub position: Vector2,
+    pub dead_since_frame: Option<u64>,
+}
+
+/// Dead monsters aren't included into snapshots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonsterSnapshot {
+    pub net_metadata: EntityNetMetadata,
+    pub name: String,
+    pub health: f32,
+    pub attack_damage: f32,
+    pub destination: Vector2,
+    pub facing_direction: Vector2,
+    pub velocity: Vector2,
+    pub action: Action<MobAction<NetIdentifier>>,
+    pub radius: f32,
+    pub position: Vector2,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissileSnapshot {
+    pub action_id: NetIdentifier,
+    pub radius: f32,
+    pub target: MissileTarget<NetIdentifier>,
+    pub velocity: Vector2,
+    pub frame_spawned: u64,
+    pub damage: f32,
+    pub caster: Option<NetIdentifier>,
+    pub position: Vector2,
+}
+
+/// Expanded area spells aren't included into snapshots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AreaSpellSnapshot {
+    pub action_id: NetIdentifier,
+    pub radius: f32,
+    pub damage: f32,
+    pub frame_spawned: u64,
+    pub caster: Option<NetIdentifier>,
+    pub hit_monsters: Vec<NetIdentifier>,
+    pub position: Vector2,
+}
+
+/// Projectiles are identified by their owners, so the ones fired by dead monsters
+/// aren't included into snapshots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemyProjectileSnapshot {
+    pub owner: NetIdentifier,
+    pub radius: f32,
+    pub velocity: Vector2,
+    pub frame_spawned: u64,
+    pub damage: f32,
+    pub position: Vector2,
+}
+
 /// I hate this struct name.
 #[derive(Debug, Clone)]
 pub struct ReceivedServerWorldUpdate {
@@ -511,3 +784,92 @@ impl FramedUpdate for DummyFramedUpdate {
         self.frame_number
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::math::ZeroVector;
+
+    fn area_spell_snapshot(action_id: NetIdentifier) -> AreaSpellSnapshot {
+        AreaSpellSnapshot {
+            action_id,
+            radius: 100.0,
+            damage: 50.0,
+            frame_spawned: 10,
+            caster: Some(1),
+            hit_monsters: vec![2, 3],
+            position: Vector2::zero(),
+        }
+    }
+
+    fn enemy_projectile_snapshot(owner: NetIdentifier) -> EnemyProjectileSnapshot {
+        EnemyProjectileSnapshot {
+            owner,
+            radius: 5.0,
+            velocity: Vector2::new(1.0, 0.0),
+            frame_spawned: 10,
+            damage: 10.0,
+            position: Vector2::zero(),
+        }
+    }
+
+    #[test]
+    fn splits_and_merges_world_snapshots() {
+        let rngs = GameRngs::default().state();
+        let game_level_state = GameLevelState {
+            monsters_killed: 3,
+            monsters_killed_by_players: vec![(1, 3)].into_iter().collect(),
+            ..GameLevelState::default()
+        };
+        let level_progress = LevelProgressSnapshot::new(&game_level_state);
+        let world_snapshot = WorldSnapshot {
+            frame_number: 20,
+            update_id: 5,
+            rngs,
+            level_progress: level_progress.clone(),
+            players: Vec::new(),
+            monsters: Vec::new(),
+            missiles: Vec::new(),
+            area_spells: (0..WORLD_SNAPSHOT_CHUNK_ENTITIES as u64)
+                .map(area_spell_snapshot)
+                .collect(),
+            enemy_projectiles: (0..10).map(enemy_projectile_snapshot).collect(),
+        };
+
+        let chunks = world_snapshot.into_chunks();
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|chunk| chunk.rngs == rngs));
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.level_progress == level_progress));
+
+        let mut world_snapshot_chunks = WorldSnapshotChunks::default();
+        let chunks_count = chunks.len();
+        let mut chunks = chunks.into_iter().enumerate().rev();
+        let (chunk_index, chunk) = chunks.next().unwrap();
+        assert!(world_snapshot_chunks
+            .add_chunk(chunk_index, chunks_count, chunk)
+            .is_none());
+        let (chunk_index, chunk) = chunks.next().unwrap();
+        let world_snapshot = world_snapshot_chunks
+            .add_chunk(chunk_index, chunks_count, chunk)
+            .expect("Expected a merged WorldSnapshot");
+
+        assert_eq!(world_snapshot.frame_number, 20);
+        assert_eq!(world_snapshot.rngs, rngs);
+        let mut resumed_game_level_state = GameLevelState::default();
+        world_snapshot
+            .level_progress
+            .apply(&mut resumed_game_level_state);
+        assert_eq!(resumed_game_level_state.monsters_killed, 3);
+        assert_eq!(resumed_game_level_state.monsters_killed_by_players[&1], 3);
+        assert_eq!(
+            world_snapshot.area_spells.len(),
+            WORLD_SNAPSHOT_CHUNK_ENTITIES
+        );
+        assert_eq!(world_snapshot.area_spells[0].hit_monsters, vec![2, 3]);
+        assert_eq!(world_snapshot.enemy_projectiles.len(), 10);
+        assert_eq!(world_snapshot.enemy_projectiles[9].owner, 9);
+    }
+}
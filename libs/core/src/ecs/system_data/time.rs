@@ -18,9 +18,16 @@ impl<'a> GameTimeService<'a> {
     pub fn set_game_start_time(&mut self) {
         self.game_time.level_started_at = self.engine_time.absolute_time();
         self.game_time.started_at_frame_number = self.engine_time.frame_number();
+        self.game_time.started_at_game_frame_number = 0;
         self.game_time.frames_skipped = 0;
     }
 
+    /// Is used by clients joining a running game, which starts for them from `game_frame_number`.
+    pub fn set_game_start_time_at_frame(&mut self, game_frame_number: u64) {
+        self.set_game_start_time();
+        self.game_time.started_at_game_frame_number = game_frame_number;
+    }
+
     pub fn engine_time(&self) -> &Time {
         &self.engine_time
     }
@@ -32,12 +39,13 @@ impl<'a> GameTimeService<'a> {
     }
 
     pub fn game_frame_number(&self) -> u64 {
-        (self.engine_time.frame_number() - self.game_time.started_at_frame_number)
+        self.game_frame_number_absolute()
             .saturating_sub(self.game_time.frames_skipped)
     }
 
     pub fn game_frame_number_absolute(&self) -> u64 {
         self.engine_time.frame_number() - self.game_time.started_at_frame_number
+            + self.game_time.started_at_game_frame_number
     }
 
     pub fn seconds_to_frame(&self, game_frame_number: u64) -> f32 {
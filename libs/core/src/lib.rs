@@ -2,15 +2,31 @@ pub mod actions;
 pub mod ecs;
 pub mod math;
 pub mod net;
+pub mod progression;
+pub mod replay;
 
-pub static PLAYER_COLORS: [[f32; 3]; 5] = [
+/// Frames simulated per second by clients and servers. Gameplay runs with Amethyst's default
+/// fixed timestep, so peers with different frame rates would simulate the game at different speeds.
+pub const FRAME_RATE: u32 = 60;
+
+/// Colours of multiplayer players, they get reused if a room has more players than colours.
+pub static PLAYER_COLORS: [[f32; 3]; 8] = [
     [0.64, 0.12, 0.11],
     [0.04, 0.45, 0.69],
     [0.0, 0.49, 0.26],
     [0.40, 0.3, 0.55],
-    [0.57, 0.57, 0.57],
+    [0.78, 0.42, 0.05],
+    [0.0, 0.52, 0.52],
+    [0.71, 0.27, 0.5],
+    [0.6, 0.56, 0.09],
 ];
 
+pub static SINGLE_PLAYER_COLOR: [f32; 3] = [0.57, 0.57, 0.57];
+
+pub fn player_color(player_index: usize) -> [f32; 3] {
+    PLAYER_COLORS[player_index % PLAYER_COLORS.len()]
+}
+
 #[macro_export]
 macro_rules! profile_scope {
     ($string:expr) => {
@@ -9,6 +9,7 @@ use crate::{
     },
     ecs::resources::world::{ImmediatePlayerActionsUpdates, PlayerLookActionUpdates},
     net::NetIdentifier,
+    progression::PlayerStats,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,14 +22,27 @@ pub struct ClientMessage {
 pub enum ClientMessagePayload {
     Heartbeat,
     JoinRoom {
+        /// See `net::BUILD_HASH`, a server only logs it, as versions are checked by
+        /// the datagram header.
+        build_hash: String,
         // As the server stores session id coming with this message
         // (see `ServerMessage::session_id`), `sent_at` is used to filter out outdated handshakes
         // in case there are duplicates of reliable messages.
         sent_at: Duration,
         nickname: String,
+        stats: PlayerStats,
+        /// Lets a player rejoin a running game, see `ServerMessagePayload::Handshake`.
+        reconnect_token: Option<u64>,
+        /// Spectators receive world updates but don't take a player slot and can't send actions.
+        is_spectator: bool,
+    },
+    SetReady(bool),
+    /// Contains the ids of the last applied update and its positions,
+    /// see `ServerMessagePayload::UpdateWorld`.
+    AcknowledgeWorldUpdate {
+        id: u64,
+        positions_id: u64,
     },
-    StartHostedGame,
-    AcknowledgeWorldUpdate(u64),
     WalkActions(ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerWalkAction>>),
     CastActions(ImmediatePlayerActionsUpdates<ClientActionUpdate<PlayerCastAction>>),
     LookActions(PlayerLookActionUpdates),
@@ -48,4 +62,21 @@ impl ClientMessagePayload {
     pub fn is_heartbeat(&self) -> bool {
         matches!(*self, Self::Heartbeat)
     }
+
+    /// Identifies messages in logs and metrics without printing their contents.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Heartbeat => "Heartbeat",
+            Self::JoinRoom { .. } => "JoinRoom",
+            Self::SetReady(_) => "SetReady",
+            Self::AcknowledgeWorldUpdate { .. } => "AcknowledgeWorldUpdate",
+            Self::WalkActions(_) => "WalkActions",
+            Self::CastActions(_) => "CastActions",
+            Self::LookActions(_) => "LookActions",
+            Self::Ping(_) => "Ping",
+            Self::Pong { .. } => "Pong",
+            Self::Kick { .. } => "Kick",
+            Self::Disconnect => "Disconnect",
+        }
+    }
 }
@@ -0,0 +1,467 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use crate::net::PROTOCOL_VERSION;
+
+/// Every datagram starts with these bytes followed by the protocol version (u32) of a sender,
+/// so that peers of incompatible versions are told apart before any reassembly or decoding.
+/// This header must never change.
+const MAGIC: [u8; 4] = *b"GVIS";
+const VERSION_HEADER_LEN: usize = 4 + 4;
+
+/// A datagram that carries a whole message.
+const WHOLE_MESSAGE: u8 = 0;
+/// A datagram that carries a fragment of a message, the header contains a message id (u32),
+/// a fragment index and a count of fragments (u16).
+const MESSAGE_FRAGMENT: u8 = 1;
+const FRAGMENT_HEADER_LEN: usize = 1 + 4 + 2 + 2;
+/// The largest part of a message that fits into a datagram along with the headers.
+fn max_fragment_size(mtu: usize) -> usize {
+    mtu - VERSION_HEADER_LEN - FRAGMENT_HEADER_LEN
+}
+
+/// Messages larger than `max_message_size(mtu)` can't be sent.
+pub const MAX_FRAGMENTS: usize = 64;
+/// Datagrams that laminar sends without fragmenting them on its own.
+pub const MAX_MTU: usize = 1400;
+/// Clients don't have a configurable MTU, a server can be configured with a different one.
+pub const DEFAULT_MTU: usize = 1200;
+
+/// Incomplete messages are dropped if their fragments don't arrive in time.
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_INCOMPLETE_MESSAGES: usize = 16;
+
+#[derive(Debug)]
+pub enum FragmentationError {
+    MessageIsTooLarge {
+        size: usize,
+        max_size: usize,
+    },
+    MalformedDatagram(&'static str),
+    /// Is returned for datagrams sent by peers built before the version header was introduced.
+    MissingVersionHeader,
+    IncompatibleVersion {
+        protocol_version: u32,
+    },
+}
+
+impl Display for FragmentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FragmentationError::MessageIsTooLarge { size, max_size } => write!(
+                f,
+                "a message of {} bytes exceeds the limit of {} bytes",
+                size, max_size
+            ),
+            FragmentationError::MalformedDatagram(reason) => {
+                write!(f, "malformed datagram: {}", reason)
+            }
+            FragmentationError::MissingVersionHeader => write!(f, "missing version header"),
+            FragmentationError::IncompatibleVersion { protocol_version } => write!(
+                f,
+                "protocol version {} is incompatible with {}",
+                protocol_version, PROTOCOL_VERSION
+            ),
+        }
+    }
+}
+
+pub fn max_message_size(mtu: usize) -> usize {
+    max_fragment_size(mtu) * MAX_FRAGMENTS
+}
+
+/// Frames a message that is known to be small enough for a single datagram.
+pub fn whole_message(message: &[u8]) -> Vec<u8> {
+    let mut datagram = Vec::with_capacity(VERSION_HEADER_LEN + 1 + message.len());
+    write_version_header(&mut datagram);
+    datagram.push(WHOLE_MESSAGE);
+    datagram.extend_from_slice(message);
+    datagram
+}
+
+/// Splits an encoded message into datagrams of at most `mtu` bytes,
+/// messages that fit into a single datagram aren't fragmented.
+pub fn split_message(
+    message_id: u32,
+    message: &[u8],
+    mtu: usize,
+) -> Result<Vec<Vec<u8>>, FragmentationError> {
+    if VERSION_HEADER_LEN + 1 + message.len() <= mtu {
+        return Ok(vec![whole_message(message)]);
+    }
+
+    if message.len() > max_message_size(mtu) {
+        return Err(FragmentationError::MessageIsTooLarge {
+            size: message.len(),
+            max_size: max_message_size(mtu),
+        });
+    }
+    let fragments = message.chunks(max_fragment_size(mtu));
+    let fragments_count = fragments.len() as u16;
+    Ok(fragments
+        .enumerate()
+        .map(|(fragment_index, fragment)| {
+            let mut datagram =
+                Vec::with_capacity(VERSION_HEADER_LEN + FRAGMENT_HEADER_LEN + fragment.len());
+            write_version_header(&mut datagram);
+            datagram.push(MESSAGE_FRAGMENT);
+            datagram.extend_from_slice(&message_id.to_le_bytes());
+            datagram.extend_from_slice(&(fragment_index as u16).to_le_bytes());
+            datagram.extend_from_slice(&fragments_count.to_le_bytes());
+            datagram.extend_from_slice(fragment);
+            datagram
+        })
+        .collect())
+}
+
+fn write_version_header(datagram: &mut Vec<u8>) {
+    datagram.extend_from_slice(&MAGIC);
+    datagram.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+}
+
+/// Checks the version header before anything else is read from a datagram.
+fn read_version_header(datagram: &[u8]) -> Result<&[u8], FragmentationError> {
+    if datagram.len() < VERSION_HEADER_LEN || datagram[..MAGIC.len()] != MAGIC {
+        return Err(FragmentationError::MissingVersionHeader);
+    }
+    let protocol_version = u32::from_le_bytes([datagram[4], datagram[5], datagram[6], datagram[7]]);
+    if protocol_version != PROTOCOL_VERSION {
+        return Err(FragmentationError::IncompatibleVersion { protocol_version });
+    }
+    Ok(&datagram[VERSION_HEADER_LEN..])
+}
+
+/// Collects fragments of messages coming from a single peer.
+#[derive(Default)]
+pub struct MessageReassembler {
+    incomplete_messages: HashMap<u32, IncompleteMessage>,
+}
+
+struct IncompleteMessage {
+    fragments: Vec<Option<Vec<u8>>>,
+    received_fragments: usize,
+    first_received_at: Instant,
+}
+
+impl MessageReassembler {
+    /// Returns a message once all of its fragments are received.
+    pub fn receive<'a>(
+        &mut self,
+        datagram: &'a [u8],
+    ) -> Result<Option<Cow<'a, [u8]>>, FragmentationError> {
+        let datagram = read_version_header(datagram)?;
+        match datagram.first() {
+            Some(&WHOLE_MESSAGE) => return Ok(Some(Cow::Borrowed(&datagram[1..]))),
+            Some(&MESSAGE_FRAGMENT) if datagram.len() > FRAGMENT_HEADER_LEN => {}
+            Some(&MESSAGE_FRAGMENT) => {
+                return Err(FragmentationError::MalformedDatagram(
+                    "a fragment is too short",
+                ))
+            }
+            _ => {
+                return Err(FragmentationError::MalformedDatagram(
+                    "unknown datagram kind",
+                ))
+            }
+        }
+
+        let message_id = u32::from_le_bytes([datagram[1], datagram[2], datagram[3], datagram[4]]);
+        let fragment_index = u16::from_le_bytes([datagram[5], datagram[6]]) as usize;
+        let fragments_count = u16::from_le_bytes([datagram[7], datagram[8]]) as usize;
+        if fragments_count > MAX_FRAGMENTS || fragment_index >= fragments_count {
+            return Err(FragmentationError::MalformedDatagram(
+                "invalid fragment index or count",
+            ));
+        }
+
+        self.drop_expired_messages();
+        if !self.incomplete_messages.contains_key(&message_id)
+            && self.incomplete_messages.len() >= MAX_INCOMPLETE_MESSAGES
+        {
+            self.drop_oldest_message();
+        }
+        let incomplete_message = self
+            .incomplete_messages
+            .entry(message_id)
+            .or_insert_with(|| IncompleteMessage {
+                fragments: vec![None; fragments_count],
+                received_fragments: 0,
+                first_received_at: Instant::now(),
+            });
+        if incomplete_message.fragments.len() != fragments_count {
+            return Err(FragmentationError::MalformedDatagram(
+                "fragments of a message have different counts",
+            ));
+        }
+        let fragment = &mut incomplete_message.fragments[fragment_index];
+        if fragment.is_none() {
+            *fragment = Some(datagram[FRAGMENT_HEADER_LEN..].to_vec());
+            incomplete_message.received_fragments += 1;
+        }
+        if incomplete_message.received_fragments < fragments_count {
+            return Ok(None);
+        }
+
+        let incomplete_message = self
+            .incomplete_messages
+            .remove(&message_id)
+            .expect("Expected an incomplete message");
+        Ok(Some(Cow::Owned(
+            incomplete_message
+                .fragments
+                .into_iter()
+                .flat_map(|fragment| fragment.expect("Expected a received fragment"))
+                .collect(),
+        )))
+    }
+
+    fn drop_oldest_message(&mut self) {
+        if let Some(oldest_message_id) = self
+            .incomplete_messages
+            .iter()
+            .min_by_key(|(_, message)| message.first_received_at)
+            .map(|(message_id, _)| *message_id)
+        {
+            log::warn!(
+                "Dropping an incomplete message {}, too many messages are being received",
+                oldest_message_id
+            );
+            self.incomplete_messages.remove(&oldest_message_id);
+        }
+    }
+
+    fn drop_expired_messages(&mut self) {
+        self.incomplete_messages.retain(|message_id, message| {
+            let is_expired = message.first_received_at.elapsed() > REASSEMBLY_TIMEOUT;
+            if is_expired {
+                log::warn!(
+                    "Dropping an incomplete message {} ({}/{} fragments received)",
+                    message_id,
+                    message.received_fragments,
+                    message.fragments.len()
+                );
+            }
+            !is_expired
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MTU: usize = 576;
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn reassemble(
+        reassembler: &mut MessageReassembler,
+        datagrams: &[Vec<u8>],
+    ) -> Vec<Option<Vec<u8>>> {
+        datagrams
+            .iter()
+            .map(|datagram| {
+                reassembler
+                    .receive(datagram)
+                    .expect("Expected a valid datagram")
+                    .map(|message| message.into_owned())
+            })
+            .collect()
+    }
+
+    fn make_expired(reassembler: &mut MessageReassembler, message_id: u32) {
+        reassembler
+            .incomplete_messages
+            .get_mut(&message_id)
+            .expect("Expected an incomplete message")
+            .first_received_at = Instant::now()
+            .checked_sub(REASSEMBLY_TIMEOUT + Duration::from_millis(1))
+            .expect("Expected a valid instant");
+    }
+
+    #[test]
+    fn splits_and_reassembles_messages() {
+        let mut reassembler = MessageReassembler::default();
+
+        let small_message = message(MTU - VERSION_HEADER_LEN - 1);
+        let datagrams = split_message(0, &small_message, MTU).unwrap();
+        assert_eq!(datagrams.len(), 1);
+        assert_eq!(datagrams[0].len(), MTU);
+        assert_eq!(
+            reassemble(&mut reassembler, &datagrams),
+            vec![Some(small_message)]
+        );
+
+        let large_message = message(MTU * 3);
+        let datagrams = split_message(1, &large_message, MTU).unwrap();
+        assert_eq!(datagrams.len(), 4);
+        assert!(datagrams.iter().all(|datagram| datagram.len() <= MTU));
+        let mut messages = reassemble(&mut reassembler, &datagrams);
+        assert_eq!(messages.pop(), Some(Some(large_message)));
+        assert!(messages.iter().all(Option::is_none));
+        assert!(reassembler.incomplete_messages.is_empty());
+    }
+
+    #[test]
+    fn reassembles_out_of_order_and_duplicate_fragments() {
+        let mut reassembler = MessageReassembler::default();
+        let large_message = message(MTU * 3);
+        let datagrams = split_message(1, &large_message, MTU).unwrap();
+
+        let shuffled_datagrams = vec![
+            datagrams[2].clone(),
+            datagrams[0].clone(),
+            datagrams[2].clone(),
+            datagrams[3].clone(),
+            datagrams[0].clone(),
+            datagrams[1].clone(),
+        ];
+        let mut messages = reassemble(&mut reassembler, &shuffled_datagrams);
+        assert_eq!(messages.pop(), Some(Some(large_message)));
+        assert!(messages.iter().all(Option::is_none));
+
+        // A late duplicate can't complete the message again.
+        assert_eq!(reassemble(&mut reassembler, &datagrams[1..2]), vec![None]);
+    }
+
+    #[test]
+    fn rejects_mismatched_fragment_counts() {
+        let mut reassembler = MessageReassembler::default();
+        let datagrams = split_message(1, &message(MTU * 3), MTU).unwrap();
+        let other_datagrams = split_message(1, &message(MTU * 5), MTU).unwrap();
+
+        assert_eq!(reassemble(&mut reassembler, &datagrams[..1]), vec![None]);
+        assert!(matches!(
+            reassembler.receive(&other_datagrams[1]),
+            Err(FragmentationError::MalformedDatagram(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_datagrams() {
+        let mut reassembler = MessageReassembler::default();
+        let mut datagram = split_message(1, &message(MTU * 3), MTU).unwrap().remove(0);
+
+        // The fragment count is set to 0.
+        datagram[VERSION_HEADER_LEN + 7] = 0;
+        datagram[VERSION_HEADER_LEN + 8] = 0;
+        assert!(matches!(
+            reassembler.receive(&datagram),
+            Err(FragmentationError::MalformedDatagram(_))
+        ));
+        assert!(matches!(
+            reassembler.receive(&datagram[..VERSION_HEADER_LEN + FRAGMENT_HEADER_LEN]),
+            Err(FragmentationError::MalformedDatagram(_))
+        ));
+
+        datagram[VERSION_HEADER_LEN] = 2;
+        assert!(matches!(
+            reassembler.receive(&datagram),
+            Err(FragmentationError::MalformedDatagram(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_datagrams_of_other_versions() {
+        let mut reassembler = MessageReassembler::default();
+        let mut datagram = whole_message(&message(10));
+
+        datagram[MAGIC.len()..VERSION_HEADER_LEN]
+            .copy_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            reassembler.receive(&datagram),
+            Err(FragmentationError::IncompatibleVersion { protocol_version })
+                if protocol_version == PROTOCOL_VERSION + 1
+        ));
+
+        assert!(matches!(
+            reassembler.receive(&message(20)),
+            Err(FragmentationError::MissingVersionHeader)
+        ));
+        assert!(matches!(
+            reassembler.receive(&MAGIC),
+            Err(FragmentationError::MissingVersionHeader)
+        ));
+    }
+
+    #[test]
+    fn drops_expired_messages() {
+        let mut reassembler = MessageReassembler::default();
+        let large_message = message(MTU * 3);
+        let datagrams = split_message(1, &large_message, MTU).unwrap();
+        let other_datagrams = split_message(2, &large_message, MTU).unwrap();
+
+        assert_eq!(reassemble(&mut reassembler, &datagrams[..2]), vec![None; 2]);
+        make_expired(&mut reassembler, 1);
+        assert_eq!(
+            reassemble(&mut reassembler, &other_datagrams[..1]),
+            vec![None]
+        );
+        assert!(!reassembler.incomplete_messages.contains_key(&1));
+
+        // The fragments received before the timeout are lost.
+        assert_eq!(reassemble(&mut reassembler, &datagrams[2..]), vec![None; 2]);
+        assert_eq!(
+            reassemble(&mut reassembler, &datagrams[..2]),
+            vec![None, Some(large_message)]
+        );
+    }
+
+    #[test]
+    fn evicts_oldest_incomplete_messages() {
+        let mut reassembler = MessageReassembler::default();
+        let large_message = message(MTU * 3);
+        let datagrams = (0..=MAX_INCOMPLETE_MESSAGES as u32)
+            .map(|message_id| split_message(message_id, &large_message, MTU).unwrap())
+            .collect::<Vec<_>>();
+
+        for message_datagrams in &datagrams[..MAX_INCOMPLETE_MESSAGES] {
+            assert_eq!(
+                reassemble(&mut reassembler, &message_datagrams[..1]),
+                vec![None]
+            );
+        }
+        // Makes the first message the oldest one without waiting for it to expire.
+        reassembler
+            .incomplete_messages
+            .get_mut(&0)
+            .unwrap()
+            .first_received_at -= Duration::from_millis(1);
+
+        let last_datagrams = &datagrams[MAX_INCOMPLETE_MESSAGES];
+        assert_eq!(
+            reassemble(&mut reassembler, &last_datagrams[..1]),
+            vec![None]
+        );
+        assert_eq!(
+            reassembler.incomplete_messages.len(),
+            MAX_INCOMPLETE_MESSAGES
+        );
+        assert!(!reassembler.incomplete_messages.contains_key(&0));
+
+        assert_eq!(
+            reassemble(&mut reassembler, &datagrams[1][1..]).pop(),
+            Some(Some(large_message))
+        );
+    }
+
+    #[test]
+    fn rejects_too_large_messages() {
+        let max_size = max_message_size(MTU);
+        let datagrams = split_message(1, &message(max_size), MTU).unwrap();
+        assert_eq!(datagrams.len(), MAX_FRAGMENTS);
+        assert!(datagrams.iter().all(|datagram| datagram.len() <= MTU));
+
+        assert!(matches!(
+            split_message(1, &message(max_size + 1), MTU),
+            Err(FragmentationError::MessageIsTooLarge { size, max_size: error_max_size })
+                if size == max_size + 1 && error_max_size == max_size
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 use serde_derive::{Deserialize, Serialize};
 
 pub mod client_message;
+pub mod fragmentation;
 pub mod server_message;
+pub mod world_update_encoding;
 
 use amethyst::network::Bytes;
 
@@ -11,6 +13,17 @@ pub type EncodedMessage = Bytes;
 pub type NetIdentifier = u64;
 
 pub const INTERPOLATION_FRAME_DELAY: u64 = 10;
+/// Room size of a server if it isn't configured.
+pub const DEFAULT_MAX_PLAYERS: usize = 4;
+/// The largest room size a server can be configured with.
+pub const MAX_PLAYERS_LIMIT: usize = 8;
+/// Clients can't receive datagrams larger than this.
+pub const CLIENT_RECEIVE_BUFFER_MAX_SIZE: usize = 14_500;
+/// Has to be bumped whenever `ClientMessage` or `ServerMessage` change, so that clients
+/// and servers built from incompatible commits refuse to play together.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// The commit a binary was built from, is reported along with `PROTOCOL_VERSION`.
+pub const BUILD_HASH: &str = env!("GV_BUILD_HASH");
 
 pub struct ConnectionNetEvent<T> {
     pub connection_id: NetIdentifier,
@@ -1,8 +1,8 @@
 use serde_derive::{Deserialize, Serialize};
 
 use crate::{
-    ecs::resources::{net::MultiplayerRoomPlayer, world::ServerWorldUpdate},
-    net::NetIdentifier,
+    ecs::resources::{net::MultiplayerRoomPlayer, world::WorldSnapshot},
+    net::{world_update_encoding::EncodedWorldUpdates, NetIdentifier},
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,15 +15,56 @@ pub struct ServerMessage {
 pub enum ServerMessagePayload {
     Heartbeat,
     UpdateRoomPlayers(Vec<MultiplayerRoomPlayer>),
-    /// Contains pairs of server (entity) ids and their corresponding players.
-    StartGame(Vec<(NetIdentifier, MultiplayerRoomPlayer)>),
+    StartGame {
+        /// Seeds GameRngs, so clients draw the same missile randomness as a server does.
+        seed: u64,
+        /// Contains pairs of server (entity) ids and their corresponding players.
+        players: Vec<(NetIdentifier, MultiplayerRoomPlayer)>,
+        /// Is `None` for the survival mode.
+        campaign_level: Option<usize>,
+    },
+    /// Contains seconds left before a game starts or `None` if the countdown is cancelled.
+    UpdateLobbyCountdown(Option<u64>),
+    /// Is sent instead of StartGame to players that rejoin a running game,
+    /// they resume the game once they receive all the chunks of a WorldSnapshot.
+    ResumeGame {
+        seed: u64,
+        players: Vec<(NetIdentifier, MultiplayerRoomPlayer)>,
+        campaign_level: Option<usize>,
+    },
+    /// Contains the state of all the entities in the world, is split into chunks
+    /// to fit into packets.
+    WorldSnapshot {
+        chunk_index: u32,
+        chunks_count: u32,
+        chunk: WorldSnapshot,
+    },
+    /// Is sent by dedicated servers when a game is over and players return to the room.
+    EndGame,
     Handshake {
+        /// See `net::BUILD_HASH`, a client only logs it, as versions are checked by
+        /// the datagram header.
+        build_hash: String,
         net_id: NetIdentifier,
         is_host: bool,
+        /// A client sends it with JoinRoom to get its player back after losing a connection.
+        reconnect_token: u64,
+        /// The room size the server is configured with.
+        max_players: u32,
+        /// Frames the server simulates per second, clients that run at a different frame rate
+        /// leave the room.
+        tick_rate: u32,
     },
+    /// Updates that exceed the size budget of a server are split into several messages,
+    /// a client applies a message only if it has acknowledged `base_id`.
+    /// Positions are encoded as deltas from the ones with `positions_base_id`,
+    /// see `WorldUpdatePositionsHistory`.
     UpdateWorld {
         id: u64,
-        updates: Vec<ServerWorldUpdate>,
+        base_id: u64,
+        positions_id: u64,
+        positions_base_id: u64,
+        updates: EncodedWorldUpdates,
     },
     DiscardWalkActions(Vec<NetIdentifier>),
     Ping(NetIdentifier),
@@ -62,10 +103,41 @@ pub enum DisconnectReason {
     Kick,
     Closed,
     ServerCrashed(i32),
+    /// A client and a server were built with different versions of `ClientMessage`
+    /// and `ServerMessage`.
+    IncompatibleVersion {
+        server_protocol_version: u32,
+    },
+    /// A client runs at a different frame rate than the tick rate of a server.
+    IncompatibleTickRate {
+        server_tick_rate: u32,
+    },
 }
 
 impl ServerMessagePayload {
     pub fn is_heartbeat(&self) -> bool {
         matches!(*self, Self::Heartbeat)
     }
+
+    /// Identifies messages in logs and metrics without printing their contents.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Heartbeat => "Heartbeat",
+            Self::UpdateRoomPlayers(_) => "UpdateRoomPlayers",
+            Self::StartGame { .. } => "StartGame",
+            Self::UpdateLobbyCountdown(_) => "UpdateLobbyCountdown",
+            Self::ResumeGame { .. } => "ResumeGame",
+            Self::WorldSnapshot { .. } => "WorldSnapshot",
+            Self::EndGame => "EndGame",
+            Self::Handshake { .. } => "Handshake",
+            Self::UpdateWorld { .. } => "UpdateWorld",
+            Self::DiscardWalkActions(_) => "DiscardWalkActions",
+            Self::Ping(_) => "Ping",
+            Self::Pong { .. } => "Pong",
+            Self::ReportPlayersNetStatus { .. } => "ReportPlayersNetStatus",
+            Self::PauseWaitingForPlayers { .. } => "PauseWaitingForPlayers",
+            Self::UnpauseWaitingForPlayers(_) => "UnpauseWaitingForPlayers",
+            Self::Disconnect(_) => "Disconnect",
+        }
+    }
 }
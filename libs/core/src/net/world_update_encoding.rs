@@ -0,0 +1,686 @@
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_derive::{Deserialize, Serialize};
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    ecs::{components::WorldPosition, resources::world::ServerWorldUpdate},
+    math::Vector2,
+    net::{NetIdentifier, NetUpdate, NetUpdateWithPosition},
+};
+
+/// Positions are sent with the precision of 1/64 of a unit.
+const POSITION_SCALE: f32 = 64.0;
+
+const PLAYER_WALK_ACTIONS: u8 = 1;
+const PLAYER_LOOK_ACTIONS: u8 = 1 << 1;
+const PLAYER_CAST_ACTIONS: u8 = 1 << 2;
+const MOB_ACTIONS: u8 = 1 << 3;
+const DAMAGE_HISTORIES: u8 = 1 << 4;
+const SPAWN_ACTIONS: u8 = 1 << 5;
+
+/// Positions of connections that haven't acknowledged anything for this long are sent whole.
+const POSITIONS_HISTORY_LIMIT: usize = 64;
+/// Entities that haven't been updated for this many frames (dead or despawned ones, mostly)
+/// are forgotten, the next update of such an entity carries its whole position.
+const POSITION_TTL_FRAMES: u64 = 300;
+
+type QuantizedPosition = (i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct KnownPosition {
+    position: QuantizedPosition,
+    /// The frame of the last update of an entity.
+    frame_number: u64,
+}
+
+/// The last known positions of entities, world updates are encoded as deltas from them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorldUpdatePositions(HashMap<NetIdentifier, KnownPosition>);
+
+impl WorldUpdatePositions {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Is called for every encoded and decoded update, so that a server and a client forget
+    /// the same entities. Returns the forgotten positions.
+    fn forget_stale(&mut self, frame_number: u64) -> Vec<(NetIdentifier, KnownPosition)> {
+        let stale_entities = self
+            .0
+            .iter()
+            .filter(|(_, known_position)| {
+                known_position.frame_number + POSITION_TTL_FRAMES < frame_number
+            })
+            .map(|(entity_net_id, _)| *entity_net_id)
+            .collect::<Vec<_>>();
+        stale_entities
+            .into_iter()
+            .filter_map(|entity_net_id| {
+                self.0
+                    .remove(&entity_net_id)
+                    .map(|known_position| (entity_net_id, known_position))
+            })
+            .collect()
+    }
+}
+
+/// Positions that a server has encoded chunks of world updates with (or a client has decoded
+/// them with), so that the next chunks can use the positions acknowledged by a client.
+///
+/// Ids are assigned by a server for each of its connections, 0 stands for no known positions.
+#[derive(Default)]
+pub struct WorldUpdatePositionsHistory {
+    last_id: u64,
+    positions: VecDeque<(u64, WorldUpdatePositions)>,
+    /// Is returned for the id 0.
+    no_positions: WorldUpdatePositions,
+}
+
+impl WorldUpdatePositionsHistory {
+    pub fn get(&self, id: u64) -> Option<&WorldUpdatePositions> {
+        if id == 0 {
+            return Some(&self.no_positions);
+        }
+        self.positions
+            .iter()
+            .find(|(positions_id, _)| *positions_id == id)
+            .map(|(_, positions)| positions)
+    }
+
+    /// Is used by a server, returns the id assigned to the positions.
+    pub fn push(&mut self, positions: WorldUpdatePositions) -> u64 {
+        self.last_id += 1;
+        self.insert(self.last_id, positions);
+        self.last_id
+    }
+
+    /// Is used by clients, to store the positions under the id a server has assigned to them.
+    pub fn insert(&mut self, id: u64, positions: WorldUpdatePositions) {
+        if self.positions.len() == POSITIONS_HISTORY_LIMIT {
+            self.positions.pop_front();
+        }
+        self.positions.push_back((id, positions));
+    }
+
+    /// Positions older than the acknowledged ones won't be used as a base anymore.
+    pub fn forget_older_than(&mut self, id: u64) {
+        self.positions
+            .retain(|(positions_id, _)| *positions_id >= id);
+    }
+}
+
+/// ServerWorldUpdates encoded by WorldUpdatesEncoder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodedWorldUpdates(Vec<u8>);
+
+impl EncodedWorldUpdates {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Accepts the positions the updates were encoded with, returns the positions
+    /// the next chunk of updates can be decoded with.
+    pub fn decode(
+        &self,
+        positions: &WorldUpdatePositions,
+    ) -> bincode::Result<(Vec<ServerWorldUpdate>, WorldUpdatePositions)> {
+        let mut decoder = WorldUpdatesDecoder {
+            bytes: &self.0,
+            previous_frame_number: 0,
+            positions: positions.clone(),
+        };
+        let mut updates = Vec::new();
+        while !decoder.bytes.is_empty() {
+            updates.push(decoder.read_update()?);
+        }
+        Ok((updates, decoder.positions))
+    }
+}
+
+/// Encodes updates (in the order of their frame numbers) with varint integers, frame numbers
+/// as offsets from the previous update, positions quantized and as deltas from the previous
+/// position of the same entity. Empty lists and unchanged positions are omitted.
+///
+/// Previous positions start with the ones a client has acknowledged, see
+/// `WorldUpdatePositionsHistory`, as clients may lose any chunk of encoded updates.
+pub struct WorldUpdatesEncoder {
+    max_bytes: usize,
+    bytes: Vec<u8>,
+    updates_count: usize,
+    previous_frame_number: u64,
+    positions: WorldUpdatePositions,
+    /// Positions that the last pushed update has overwritten or forgotten, to roll it back.
+    replaced_positions: Vec<(NetIdentifier, Option<KnownPosition>)>,
+}
+
+impl WorldUpdatesEncoder {
+    pub fn new(max_bytes: usize, positions: &WorldUpdatePositions) -> Self {
+        Self {
+            max_bytes,
+            bytes: Vec::new(),
+            updates_count: 0,
+            previous_frame_number: 0,
+            positions: positions.clone(),
+            replaced_positions: Vec::new(),
+        }
+    }
+
+    /// Returns false if the update doesn't fit into `max_bytes`, the encoder stays unchanged then.
+    /// An empty encoder accepts an update of any size, so that no update is left unsent.
+    pub fn push(&mut self, update: &ServerWorldUpdate) -> bool {
+        let bytes_len = self.bytes.len();
+        let previous_frame_number = self.previous_frame_number;
+        self.replaced_positions.clear();
+
+        self.write_update(update);
+        if self.bytes.len() > self.max_bytes && self.updates_count > 0 {
+            self.bytes.truncate(bytes_len);
+            self.previous_frame_number = previous_frame_number;
+            for (entity_net_id, known_position) in self.replaced_positions.drain(..).rev() {
+                if let Some(known_position) = known_position {
+                    self.positions.0.insert(entity_net_id, known_position);
+                } else {
+                    self.positions.0.remove(&entity_net_id);
+                }
+            }
+            return false;
+        }
+        self.updates_count += 1;
+        true
+    }
+
+    /// Returns the positions after the pushed updates.
+    pub fn positions(&self) -> &WorldUpdatePositions {
+        &self.positions
+    }
+
+    pub fn finish(self) -> (EncodedWorldUpdates, WorldUpdatePositions) {
+        (EncodedWorldUpdates(self.bytes), self.positions)
+    }
+
+    fn write_update(&mut self, update: &ServerWorldUpdate) {
+        assert!(
+            update.frame_number >= self.previous_frame_number,
+            "Expected updates in the order of their frame numbers"
+        );
+        self.write(&(update.frame_number - self.previous_frame_number));
+        self.previous_frame_number = update.frame_number;
+        let forgotten_positions = self.positions.forget_stale(update.frame_number);
+        self.replaced_positions.extend(
+            forgotten_positions
+                .into_iter()
+                .map(|(entity_net_id, known_position)| (entity_net_id, Some(known_position))),
+        );
+
+        let lists = [
+            (
+                PLAYER_WALK_ACTIONS,
+                update.player_walk_actions_updates.is_empty(),
+            ),
+            (
+                PLAYER_LOOK_ACTIONS,
+                update.player_look_actions_updates.is_empty(),
+            ),
+            (
+                PLAYER_CAST_ACTIONS,
+                update.player_cast_actions_updates.is_empty(),
+            ),
+            (MOB_ACTIONS, update.mob_actions_updates.is_empty()),
+            (DAMAGE_HISTORIES, update.damage_histories_updates.is_empty()),
+            (SPAWN_ACTIONS, update.spawn_actions.is_empty()),
+        ];
+        let present_lists = lists
+            .iter()
+            .filter(|(_, is_empty)| !is_empty)
+            .fold(0u8, |present_lists, (list, _)| present_lists | list);
+        self.write(&present_lists);
+
+        self.write_list(
+            &update.player_walk_actions_updates,
+            Self::write_net_update_with_position,
+        );
+        self.write_list(&update.player_look_actions_updates, Self::write_net_update);
+        self.write_list(&update.player_cast_actions_updates, Self::write_net_update);
+        self.write_list(
+            &update.mob_actions_updates,
+            Self::write_net_update_with_position,
+        );
+        self.write_list(&update.damage_histories_updates, Self::write_net_update);
+        self.write_list(&update.spawn_actions, |encoder, spawn_action| {
+            encoder.write(spawn_action)
+        });
+    }
+
+    fn write_list<T>(&mut self, list: &[T], write_item: impl Fn(&mut Self, &T)) {
+        if list.is_empty() {
+            return;
+        }
+        self.write(&(list.len() as u64));
+        for item in list {
+            write_item(self, item);
+        }
+    }
+
+    fn write_net_update<T: Serialize>(&mut self, update: &NetUpdate<T>) {
+        self.write(&update.entity_net_id);
+        self.write(&update.data);
+    }
+
+    fn write_net_update_with_position<T: Serialize>(&mut self, update: &NetUpdateWithPosition<T>) {
+        self.write(&update.entity_net_id);
+        let position = quantize(&update.position);
+        let previous_position = self.positions.0.insert(
+            update.entity_net_id,
+            KnownPosition {
+                position,
+                frame_number: self.previous_frame_number,
+            },
+        );
+        self.replaced_positions
+            .push((update.entity_net_id, previous_position));
+        let delta = match previous_position.map(|known_position| known_position.position) {
+            Some(previous_position) if previous_position == position => None,
+            Some(previous_position) => Some((
+                position.0.wrapping_sub(previous_position.0),
+                position.1.wrapping_sub(previous_position.1),
+            )),
+            None => Some(position),
+        };
+        self.write(&delta);
+        self.write(&update.data);
+    }
+
+    fn write<T: Serialize + ?Sized>(&mut self, value: &T) {
+        options()
+            .serialize_into(&mut self.bytes, value)
+            .expect("Expected to encode a world update");
+    }
+}
+
+struct WorldUpdatesDecoder<'a> {
+    bytes: &'a [u8],
+    previous_frame_number: u64,
+    positions: WorldUpdatePositions,
+}
+
+impl<'a> WorldUpdatesDecoder<'a> {
+    fn read_update(&mut self) -> bincode::Result<ServerWorldUpdate> {
+        let frame_offset: u64 = self.read()?;
+        let frame_number = self.previous_frame_number + frame_offset;
+        self.previous_frame_number = frame_number;
+        self.positions.forget_stale(frame_number);
+
+        let present_lists: u8 = self.read()?;
+        let mut update = ServerWorldUpdate::new(frame_number);
+        if present_lists & PLAYER_WALK_ACTIONS != 0 {
+            update.player_walk_actions_updates =
+                self.read_list(Self::read_net_update_with_position)?;
+        }
+        if present_lists & PLAYER_LOOK_ACTIONS != 0 {
+            update.player_look_actions_updates = self.read_list(Self::read_net_update)?;
+        }
+        if present_lists & PLAYER_CAST_ACTIONS != 0 {
+            update.player_cast_actions_updates = self.read_list(Self::read_net_update)?;
+        }
+        if present_lists & MOB_ACTIONS != 0 {
+            update.mob_actions_updates = self.read_list(Self::read_net_update_with_position)?;
+        }
+        if present_lists & DAMAGE_HISTORIES != 0 {
+            update.damage_histories_updates = self.read_list(Self::read_net_update)?;
+        }
+        if present_lists & SPAWN_ACTIONS != 0 {
+            update.spawn_actions = self.read_list(Self::read)?;
+        }
+        Ok(update)
+    }
+
+    fn read_list<T>(
+        &mut self,
+        read_item: impl Fn(&mut Self) -> bincode::Result<T>,
+    ) -> bincode::Result<Vec<T>> {
+        let len: u64 = self.read()?;
+        (0..len).map(|_| read_item(self)).collect()
+    }
+
+    fn read_net_update<T: DeserializeOwned>(&mut self) -> bincode::Result<NetUpdate<T>> {
+        Ok(NetUpdate {
+            entity_net_id: self.read()?,
+            data: self.read()?,
+        })
+    }
+
+    fn read_net_update_with_position<T: DeserializeOwned>(
+        &mut self,
+    ) -> bincode::Result<NetUpdateWithPosition<T>> {
+        let entity_net_id = self.read()?;
+        let delta: Option<QuantizedPosition> = self.read()?;
+        let previous_position = self
+            .positions
+            .0
+            .get(&entity_net_id)
+            .map(|known_position| known_position.position);
+        let position = match (delta, previous_position) {
+            (None, Some(previous_position)) => previous_position,
+            (None, None) => {
+                return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                    "Missing the position of an entity {}",
+                    entity_net_id
+                ))))
+            }
+            (Some(delta), Some(previous_position)) => (
+                previous_position.0.wrapping_add(delta.0),
+                previous_position.1.wrapping_add(delta.1),
+            ),
+            (Some(position), None) => position,
+        };
+        self.positions.0.insert(
+            entity_net_id,
+            KnownPosition {
+                position,
+                frame_number: self.previous_frame_number,
+            },
+        );
+
+        Ok(NetUpdateWithPosition {
+            entity_net_id,
+            position: dequantize(position),
+            data: self.read()?,
+        })
+    }
+
+    fn read<T: DeserializeOwned>(&mut self) -> bincode::Result<T> {
+        options().deserialize_from(&mut self.bytes)
+    }
+}
+
+fn options() -> impl Options {
+    bincode::DefaultOptions::new().with_varint_encoding()
+}
+
+fn quantize(position: &WorldPosition) -> QuantizedPosition {
+    (
+        (position.x * POSITION_SCALE).round() as i32,
+        (position.y * POSITION_SCALE).round() as i32,
+    )
+}
+
+fn dequantize(position: QuantizedPosition) -> WorldPosition {
+    WorldPosition::new(Vector2::new(
+        position.0 as f32 / POSITION_SCALE,
+        position.1 as f32 / POSITION_SCALE,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::actions::{mob::MobAction, player::PlayerWalkAction, ClientActionUpdate};
+
+    fn update(frame_number: u64, positions: &[(NetIdentifier, f32, f32)]) -> ServerWorldUpdate {
+        let mut update = ServerWorldUpdate::new(frame_number);
+        update.mob_actions_updates = positions
+            .iter()
+            .map(|(entity_net_id, x, y)| NetUpdateWithPosition {
+                entity_net_id: *entity_net_id,
+                position: WorldPosition::new(Vector2::new(*x, *y)),
+                data: MobAction::Idle,
+            })
+            .collect();
+        update
+    }
+
+    fn encode(
+        updates: &[ServerWorldUpdate],
+        positions: &WorldUpdatePositions,
+    ) -> (EncodedWorldUpdates, WorldUpdatePositions) {
+        let mut encoder = WorldUpdatesEncoder::new(usize::MAX, positions);
+        for update in updates {
+            assert!(encoder.push(update));
+        }
+        encoder.finish()
+    }
+
+    fn assert_positions_eq(expected: &[ServerWorldUpdate], actual: &[ServerWorldUpdate]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert_eq!(expected.frame_number, actual.frame_number);
+            assert_eq!(
+                expected.mob_actions_updates.len(),
+                actual.mob_actions_updates.len()
+            );
+            let mob_actions_updates = expected
+                .mob_actions_updates
+                .iter()
+                .zip(&actual.mob_actions_updates);
+            for (expected, actual) in mob_actions_updates {
+                assert_eq!(expected.entity_net_id, actual.entity_net_id);
+                let error = (*expected.position - *actual.position).abs();
+                assert!(
+                    error.x <= 1.0 / POSITION_SCALE && error.y <= 1.0 / POSITION_SCALE,
+                    "Expected {:?} to be quantized within 1/64, got {:?}",
+                    *expected.position,
+                    *actual.position
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_world_updates() {
+        let mut updates = vec![
+            update(3, &[(1, 0.0, 0.0), (2, 10.5, -3.25)]),
+            update(4, &[(1, 1.0, 0.5)]),
+            update(4, &[]),
+            update(7, &[(1, 1.0, 0.5), (2, -200.0, 300.0)]),
+        ];
+        updates[1].player_walk_actions_updates = vec![NetUpdateWithPosition {
+            entity_net_id: 5,
+            position: WorldPosition::new(Vector2::new(2.0, 2.0)),
+            data: ClientActionUpdate {
+                client_action_id: 9,
+                action: PlayerWalkAction::Walk {
+                    direction: Vector2::new(1.0, 0.0),
+                },
+            },
+        }];
+
+        let (encoded_updates, positions) = encode(&updates, &WorldUpdatePositions::default());
+        let (decoded_updates, decoded_positions) = encoded_updates
+            .decode(&WorldUpdatePositions::default())
+            .expect("Expected to decode world updates");
+
+        assert_positions_eq(&updates, &decoded_updates);
+        assert_eq!(positions, decoded_positions);
+        let walk_update = &decoded_updates[1].player_walk_actions_updates[0];
+        assert_eq!(walk_update.entity_net_id, 5);
+        assert_eq!(*walk_update.position, Vector2::new(2.0, 2.0));
+        assert_eq!(walk_update.data.client_action_id, 9);
+        assert_eq!(
+            walk_update.data.action,
+            PlayerWalkAction::Walk {
+                direction: Vector2::new(1.0, 0.0),
+            }
+        );
+        assert!(decoded_updates[2].mob_actions_updates.is_empty());
+    }
+
+    #[test]
+    fn quantizes_positions_within_1_64() {
+        let updates = (0..100)
+            .map(|i| {
+                let coordinate = i as f32 * 12.345 - 500.0;
+                update(
+                    i,
+                    &[
+                        (1, coordinate, -coordinate / 3.0),
+                        (2, 0.007 * i as f32, 0.0),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (encoded_updates, _) = encode(&updates, &WorldUpdatePositions::default());
+        let (decoded_updates, _) = encoded_updates
+            .decode(&WorldUpdatePositions::default())
+            .expect("Expected to decode world updates");
+
+        assert_positions_eq(&updates, &decoded_updates);
+    }
+
+    #[test]
+    fn splits_world_updates_by_budget() {
+        let updates = (0..10)
+            .map(|i| update(i, &[(1, i as f32, 0.0), (2, 5.0, 5.0)]))
+            .collect::<Vec<_>>();
+        let (encoded_update, _) = encode(&updates[..1], &WorldUpdatePositions::default());
+        let max_bytes = encoded_update.len() * 3;
+
+        // Mirrors how a server splits updates into chunks.
+        let mut chunks = vec![WorldUpdatesEncoder::new(
+            max_bytes,
+            &WorldUpdatePositions::default(),
+        )];
+        for update in &updates {
+            let encoder = chunks.last_mut().unwrap();
+            if !encoder.push(update) {
+                let mut encoder = WorldUpdatesEncoder::new(max_bytes, encoder.positions());
+                assert!(encoder.push(update));
+                chunks.push(encoder);
+            }
+        }
+        assert!(chunks.len() > 1);
+
+        let mut positions = WorldUpdatePositions::default();
+        let mut decoded_updates = Vec::new();
+        for (i, encoder) in chunks.into_iter().enumerate() {
+            let (encoded_updates, encoder_positions) = encoder.finish();
+            assert!(encoded_updates.len() <= max_bytes);
+            if i > 0 {
+                // Unchanged positions are omitted, so a chunk can't be decoded on its own.
+                assert!(encoded_updates
+                    .decode(&WorldUpdatePositions::default())
+                    .is_err());
+            }
+
+            let (chunk_updates, chunk_positions) = encoded_updates
+                .decode(&positions)
+                .expect("Expected to decode world updates");
+            assert_eq!(chunk_positions, encoder_positions);
+            positions = chunk_positions;
+            decoded_updates.extend(chunk_updates);
+        }
+
+        assert_positions_eq(&updates, &decoded_updates);
+    }
+
+    #[test]
+    fn accepts_updates_exceeding_budget_if_empty() {
+        let updates = vec![update(1, &[(1, 1.0, 1.0)]), update(2, &[(1, 2.0, 2.0)])];
+
+        let mut encoder = WorldUpdatesEncoder::new(1, &WorldUpdatePositions::default());
+        assert!(encoder.push(&updates[0]));
+        let positions = encoder.positions().clone();
+        assert!(!encoder.push(&updates[1]));
+        assert_eq!(encoder.positions(), &positions);
+        let (encoded_updates, _) = encoder.finish();
+        let (decoded_updates, _) = encoded_updates
+            .decode(&WorldUpdatePositions::default())
+            .expect("Expected to decode world updates");
+
+        assert_positions_eq(&updates[..1], &decoded_updates);
+    }
+
+    #[test]
+    fn forgets_positions_of_entities_without_updates() {
+        let updates = vec![
+            update(1, &[(1, 1.0, 1.0), (2, 2.0, 2.0)]),
+            update(POSITION_TTL_FRAMES, &[(1, 1.0, 1.0)]),
+            update(POSITION_TTL_FRAMES + 2, &[(1, 1.5, 1.0)]),
+        ];
+        let (encoded_updates, positions) = encode(&updates, &WorldUpdatePositions::default());
+        assert_eq!(positions.len(), 1);
+
+        let (decoded_updates, decoded_positions) = encoded_updates
+            .decode(&WorldUpdatePositions::default())
+            .expect("Expected to decode world updates");
+        assert_positions_eq(&updates, &decoded_updates);
+        assert_eq!(positions, decoded_positions);
+
+        // A forgotten entity gets its whole position sent again.
+        let updates = vec![update(POSITION_TTL_FRAMES + 3, &[(2, 2.0, 2.0)])];
+        let (encoded_updates, _) = encode(&updates, &positions);
+        let (decoded_updates, _) = encoded_updates
+            .decode(&decoded_positions)
+            .expect("Expected to decode world updates");
+        assert_positions_eq(&updates, &decoded_updates);
+    }
+
+    #[test]
+    fn rolls_back_positions_of_rejected_updates() {
+        let updates = vec![
+            update(1, &[(1, 1.0, 1.0), (2, 2.0, 2.0)]),
+            update(POSITION_TTL_FRAMES + 2, &[(1, 3.0, 3.0), (3, 4.0, 4.0)]),
+        ];
+        let (encoded_update, positions) = encode(&updates[..1], &WorldUpdatePositions::default());
+
+        let mut encoder = WorldUpdatesEncoder::new(encoded_update.len(), &Default::default());
+        assert!(encoder.push(&updates[0]));
+        // Overwrites the position of 1, forgets 2 and adds 3, but doesn't fit.
+        assert!(!encoder.push(&updates[1]));
+        assert_eq!(encoder.positions(), &positions);
+    }
+
+    #[test]
+    fn encodes_positions_as_deltas_from_acknowledged_ones() {
+        let acknowledged_updates = vec![update(1, &[(1, 100.0, 100.0), (2, -100.0, 50.0)])];
+        let (_, acknowledged_positions) =
+            encode(&acknowledged_updates, &WorldUpdatePositions::default());
+
+        let updates = vec![update(2, &[(1, 100.0, 100.0), (2, -99.5, 50.0)])];
+        let (whole_updates, _) = encode(&updates, &WorldUpdatePositions::default());
+        let (encoded_updates, positions) = encode(&updates, &acknowledged_positions);
+        assert!(encoded_updates.len() < whole_updates.len());
+
+        let (decoded_updates, decoded_positions) = encoded_updates
+            .decode(&acknowledged_positions)
+            .expect("Expected to decode world updates");
+        assert_positions_eq(&updates, &decoded_updates);
+        assert_eq!(positions, decoded_positions);
+        assert!(encoded_updates
+            .decode(&WorldUpdatePositions::default())
+            .is_err());
+    }
+
+    #[test]
+    fn keeps_world_update_positions_history() {
+        let (_, positions) = encode(&[update(1, &[(1, 1.0, 1.0)])], &Default::default());
+        let mut history = WorldUpdatePositionsHistory::default();
+        assert_eq!(history.get(0), Some(&WorldUpdatePositions::default()));
+        assert_eq!(history.get(1), None);
+
+        assert_eq!(history.push(WorldUpdatePositions::default()), 1);
+        assert_eq!(history.push(positions.clone()), 2);
+        assert_eq!(history.get(2), Some(&positions));
+
+        history.forget_older_than(2);
+        assert_eq!(history.get(1), None);
+        assert!(history.get(2).is_some());
+
+        for _ in 0..POSITIONS_HISTORY_LIMIT {
+            history.push(WorldUpdatePositions::default());
+        }
+        assert_eq!(history.get(2), None);
+        assert!(history.get(POSITIONS_HISTORY_LIMIT as u64 + 2).is_some());
+    }
+}
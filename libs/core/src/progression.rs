@@ -0,0 +1,188 @@
+use serde_derive::{Deserialize, Serialize};
+
+pub const EXPERIENCE_PER_KILL: u64 = 10;
+pub const SKILL_POINTS_PER_LEVEL: u32 = 1;
+pub const MAX_STAT_RANK: u8 = 10;
+pub const MANA_REGENERATION_PER_SEC: f32 = 8.0;
+
+const BASE_PLAYER_HEALTH: f32 = 100.0;
+const BASE_PLAYER_MANA: f32 = 100.0;
+const BASE_PLAYER_SPEED: f32 = 200.0;
+const BASE_MISSILE_DAMAGE: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    MaxHealth,
+    MoveSpeed,
+    CastCooldown,
+    MissileDamage,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 4] = [
+        Stat::MaxHealth,
+        Stat::MoveSpeed,
+        Stat::CastCooldown,
+        Stat::MissileDamage,
+    ];
+}
+
+/// Ranks of the stats players spend their skill points on.
+/// Is a part of a player's profile and gets sent to a server when joining a room,
+/// so every peer simulates players with the same stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub max_health: u8,
+    pub move_speed: u8,
+    pub cast_cooldown: u8,
+    pub missile_damage: u8,
+}
+
+impl PlayerStats {
+    pub fn rank(&self, stat: Stat) -> u8 {
+        match stat {
+            Stat::MaxHealth => self.max_health,
+            Stat::MoveSpeed => self.move_speed,
+            Stat::CastCooldown => self.cast_cooldown,
+            Stat::MissileDamage => self.missile_damage,
+        }
+    }
+
+    pub fn rank_mut(&mut self, stat: Stat) -> &mut u8 {
+        match stat {
+            Stat::MaxHealth => &mut self.max_health,
+            Stat::MoveSpeed => &mut self.move_speed,
+            Stat::CastCooldown => &mut self.cast_cooldown,
+            Stat::MissileDamage => &mut self.missile_damage,
+        }
+    }
+
+    /// Stats received from the network aren't trusted, so we cap them.
+    pub fn clamped(self) -> Self {
+        Self {
+            max_health: self.max_health.min(MAX_STAT_RANK),
+            move_speed: self.move_speed.min(MAX_STAT_RANK),
+            cast_cooldown: self.cast_cooldown.min(MAX_STAT_RANK),
+            missile_damage: self.missile_damage.min(MAX_STAT_RANK),
+        }
+    }
+
+    pub fn max_health(&self) -> f32 {
+        BASE_PLAYER_HEALTH + 10.0 * f32::from(self.max_health)
+    }
+
+    pub fn max_mana(&self) -> f32 {
+        BASE_PLAYER_MANA
+    }
+
+    pub fn move_speed(&self) -> f32 {
+        BASE_PLAYER_SPEED * (1.0 + 0.05 * f32::from(self.move_speed))
+    }
+
+    /// Is applied to the cooldowns of all spells.
+    pub fn cooldown_multiplier(&self) -> f32 {
+        0.95f32.powi(i32::from(self.cast_cooldown))
+    }
+
+    /// Spells define their damage relative to this value.
+    pub fn missile_damage(&self) -> f32 {
+        BASE_MISSILE_DAMAGE * (1.0 + 0.1 * f32::from(self.missile_damage))
+    }
+}
+
+/// Total experience required to reach `level` (levels start with 1).
+pub fn experience_for_level(level: u32) -> u64 {
+    let level = u64::from(level.saturating_sub(1));
+    50 * level * (level + 1)
+}
+
+pub fn level_for_experience(experience: u64) -> u32 {
+    let mut level = 1;
+    while experience_for_level(level + 1) <= experience {
+        level += 1;
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_experience_required_for_levels() {
+        assert_eq!(experience_for_level(0), 0);
+        assert_eq!(experience_for_level(1), 0);
+        assert_eq!(experience_for_level(2), 100);
+        assert_eq!(experience_for_level(3), 300);
+        assert_eq!(experience_for_level(10), 4500);
+    }
+
+    #[test]
+    fn finds_levels_for_experience() {
+        assert_eq!(level_for_experience(0), 1);
+        assert_eq!(level_for_experience(99), 1);
+        assert_eq!(level_for_experience(100), 2);
+        assert_eq!(level_for_experience(299), 2);
+        assert_eq!(level_for_experience(300), 3);
+        for level in 1..50 {
+            assert_eq!(level_for_experience(experience_for_level(level)), level);
+            assert_eq!(
+                level_for_experience(experience_for_level(level + 1) - 1),
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn clamps_stat_ranks() {
+        let stats = PlayerStats {
+            max_health: MAX_STAT_RANK + 1,
+            move_speed: u8::max_value(),
+            cast_cooldown: MAX_STAT_RANK,
+            missile_damage: 3,
+        }
+        .clamped();
+        assert_eq!(
+            stats,
+            PlayerStats {
+                max_health: MAX_STAT_RANK,
+                move_speed: MAX_STAT_RANK,
+                cast_cooldown: MAX_STAT_RANK,
+                missile_damage: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn applies_stat_ranks() {
+        let base_stats = PlayerStats::default();
+        assert_eq!(base_stats.max_health(), BASE_PLAYER_HEALTH);
+        assert_eq!(base_stats.max_mana(), BASE_PLAYER_MANA);
+        assert_eq!(base_stats.move_speed(), BASE_PLAYER_SPEED);
+        assert_eq!(base_stats.cooldown_multiplier(), 1.0);
+        assert_eq!(base_stats.missile_damage(), BASE_MISSILE_DAMAGE);
+
+        let stats = PlayerStats {
+            max_health: 2,
+            move_speed: 4,
+            cast_cooldown: 2,
+            missile_damage: 5,
+        };
+        assert_eq!(stats.max_health(), 120.0);
+        assert!((stats.move_speed() - 240.0).abs() < 1e-3);
+        assert!((stats.cooldown_multiplier() - 0.9025).abs() < 1e-6);
+        assert!((stats.missile_damage() - 75.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn maps_stats_to_ranks() {
+        let mut stats = PlayerStats::default();
+        for (rank, stat) in Stat::ALL.iter().enumerate() {
+            *stats.rank_mut(*stat) = rank as u8 + 1;
+        }
+        for (rank, stat) in Stat::ALL.iter().enumerate() {
+            assert_eq!(stats.rank(*stat), rank as u8 + 1);
+        }
+        assert_eq!(stats.missile_damage, 4);
+    }
+}
@@ -0,0 +1,166 @@
+use amethyst::Error;
+use serde_derive::{Deserialize, Serialize};
+
+use std::{fs, path::Path};
+
+use crate::{
+    actions::monster_spawn::SpawnAction, ecs::components::PlayerActions, net::BUILD_HASH,
+    progression::PlayerStats,
+};
+
+/// Has to be bumped whenever `Replay` or anything it stores changes the way it's serialized.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+/// Precedes a serialized `Replay`, so that replays recorded by other builds are rejected
+/// before their contents are read: they are unlikely to be re-simulated identically.
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplayHeader {
+    format_version: u32,
+    build_hash: String,
+}
+
+/// A recorded single player session: the seed and the inputs of every game frame, which is
+/// enough to re-simulate it. Is stored with bincode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    /// Is `None` for survival games.
+    pub campaign_level: Option<usize>,
+    pub players: Vec<PlayerStats>,
+    /// Indexed by game frame numbers.
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Is indexed the same way as `Replay::players`.
+    pub player_actions: Vec<PlayerActions>,
+    pub spawn_actions: Vec<SpawnAction>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, campaign_level: Option<usize>, players: Vec<PlayerStats>) -> Self {
+        Self {
+            seed,
+            campaign_level,
+            players,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> amethyst::Result<Self> {
+        let contents = fs::read(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        Self::from_bytes(&contents)
+            .map_err(|err| Error::from_string(format!("{} ({})", err, path.display())))
+    }
+
+    pub fn save(&self, path: &Path) -> amethyst::Result<()> {
+        let contents = self
+            .to_bytes()
+            .map_err(|err| Error::from_string(format!("couldn't serialize a replay: {}", err)))?;
+        fs::write(path, contents).map_err(|err| {
+            Error::from_string(format!("couldn't write {}: {}", path.display(), err))
+        })?;
+        Ok(())
+    }
+
+    fn from_bytes(mut contents: &[u8]) -> Result<Self, String> {
+        let header: ReplayHeader = bincode::deserialize_from(&mut contents)
+            .map_err(|err| format!("invalid replay header: {}", err))?;
+        if header.format_version != REPLAY_FORMAT_VERSION {
+            return Err(format!(
+                "unsupported replay format version: {} (expected {})",
+                header.format_version, REPLAY_FORMAT_VERSION
+            ));
+        }
+        if header.build_hash != BUILD_HASH {
+            return Err(format!(
+                "the replay was recorded by another build: {} (expected {})",
+                header.build_hash, BUILD_HASH
+            ));
+        }
+
+        let replay: Self =
+            bincode::deserialize(contents).map_err(|err| format!("invalid replay: {}", err))?;
+        if let Some(frame_number) = replay
+            .frames
+            .iter()
+            .position(|frame| frame.player_actions.len() != replay.players.len())
+        {
+            return Err(format!(
+                "frame {} doesn't match the number of players",
+                frame_number
+            ));
+        }
+        Ok(replay)
+    }
+
+    fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        let mut contents = bincode::serialize(&ReplayHeader {
+            format_version: REPLAY_FORMAT_VERSION,
+            build_hash: BUILD_HASH.to_owned(),
+        })?;
+        bincode::serialize_into(&mut contents, self)?;
+        Ok(contents)
+    }
+
+    pub fn frame(&self, frame_number: u64) -> Option<&ReplayFrame> {
+        self.frames.get(frame_number as usize)
+    }
+
+    /// Adds empty frames up to `frame_number` if they haven't been recorded yet.
+    pub fn frame_mut(&mut self, frame_number: u64) -> &mut ReplayFrame {
+        let players_count = self.players.len();
+        let frame_index = frame_number as usize;
+        if self.frames.len() <= frame_index {
+            self.frames.resize_with(frame_index + 1, || ReplayFrame {
+                player_actions: vec![PlayerActions::default(); players_count],
+                spawn_actions: Vec::new(),
+            });
+        }
+        &mut self.frames[frame_index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_header(format_version: u32, build_hash: &str) -> Vec<u8> {
+        bincode::serialize(&ReplayHeader {
+            format_version,
+            build_hash: build_hash.to_owned(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_saved_replays() {
+        let mut replay = Replay::new(42, Some(1), Vec::new());
+        replay.frame_mut(2);
+
+        let loaded_replay = Replay::from_bytes(&replay.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded_replay.seed, 42);
+        assert_eq!(loaded_replay.campaign_level, Some(1));
+        assert_eq!(loaded_replay.frames.len(), 3);
+    }
+
+    #[test]
+    fn rejects_replays_of_other_versions() {
+        let replay = bincode::serialize(&Replay::new(42, None, Vec::new())).unwrap();
+
+        let mut other_format = serialized_header(REPLAY_FORMAT_VERSION + 1, BUILD_HASH);
+        other_format.extend_from_slice(&replay);
+        assert!(Replay::from_bytes(&other_format).is_err());
+
+        let mut other_build = serialized_header(REPLAY_FORMAT_VERSION, "other");
+        other_build.extend_from_slice(&replay);
+        assert!(Replay::from_bytes(&other_build).is_err());
+
+        let mut same_build = serialized_header(REPLAY_FORMAT_VERSION, BUILD_HASH);
+        same_build.extend_from_slice(&replay);
+        assert!(Replay::from_bytes(&same_build).is_ok());
+    }
+}
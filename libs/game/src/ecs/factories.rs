@@ -24,6 +24,7 @@ use gv_core::{
         tags::*,
     },
     math::{Vector2, ZeroVector},
+    progression::PlayerStats,
 };
 
 use crate::ecs::resources::MonsterDefinition;
@@ -41,7 +42,7 @@ pub struct PlayerFactory<'s> {
 }
 
 impl<'s> PlayerFactory<'s> {
-    pub fn create(&mut self) -> Entity {
+    pub fn create(&mut self, stats: PlayerStats) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_z(10.0);
 
@@ -57,7 +58,7 @@ impl<'s> PlayerFactory<'s> {
                 NetWorldPosition::new(Vector2::zero()),
                 &mut self.net_world_positions,
             )
-            .with(Player::new(), &mut self.players)
+            .with(Player::new(stats), &mut self.players)
             .with(
                 PlayerLastCastedSpells::default(),
                 &mut self.player_last_casted_spells,
@@ -79,9 +80,15 @@ pub struct LandscapeFactory<'s> {
 }
 
 impl<'s> LandscapeFactory<'s> {
+    /// `landscape` is a landscape sprite sheet path, see LandscapeDefinition.
     #[cfg(feature = "client")]
-    pub fn create(&mut self) -> Entity {
-        let AssetHandles { landscape, .. } = self.asset_handles.clone();
+    pub fn create(&mut self, landscape: &str) -> Entity {
+        let landscape = self
+            .asset_handles
+            .landscapes
+            .get(landscape)
+            .unwrap_or_else(|| panic!("Expected a loaded landscape: {}", landscape))
+            .clone();
 
         let mut transform = Transform::default();
         transform.set_translation_z(-1.0);
@@ -101,7 +108,7 @@ impl<'s> LandscapeFactory<'s> {
     }
 
     #[cfg(not(feature = "client"))]
-    pub fn create(&mut self) -> Entity {
+    pub fn create(&mut self, _landscape: &str) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_z(-1.0);
 
@@ -147,7 +154,14 @@ impl<'s> MonsterFactory<'s> {
             collision_radius: radius,
             ..
         } = definition;
-        let beetle_prefab = self.asset_handles.as_ref().unwrap().beetle_prefab.clone();
+        let monster_prefab = self
+            .asset_handles
+            .as_ref()
+            .unwrap()
+            .monster_prefabs
+            .get(&name)
+            .unwrap_or_else(|| panic!("Expected a loaded prefab for {}", name))
+            .clone();
 
         let facing_direction = destination - position;
         let facing_direction = if facing_direction.norm_squared() > 0.0 {
@@ -158,7 +172,7 @@ impl<'s> MonsterFactory<'s> {
 
         self.entities
             .build_entity()
-            .with(beetle_prefab, &mut self.sprite_animation_handles)
+            .with(monster_prefab, &mut self.sprite_animation_handles)
             .with(transform, &mut self.transforms)
             .with(WorldPosition::new(position), &mut self.world_positions)
             .with(
@@ -1,14 +1,43 @@
-use amethyst::ecs::World;
+use amethyst::{
+    ecs::{Entity, World},
+    Error,
+};
+use rand::Rng;
+use serde_derive::Deserialize;
 
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs, iter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[cfg(not(feature = "client"))]
 use gv_core::net::client_message::ClientMessage;
 #[cfg(feature = "client")]
 use gv_core::net::server_message::ServerMessage;
-use gv_core::{actions::mob::MobAttackType, net::ConnectionNetEvent};
+use gv_core::{
+    actions::{
+        mob::MobAttackType,
+        monster_spawn::{MonsterTypeId, Side},
+        player::SpellId,
+    },
+    net::ConnectionNetEvent,
+    progression::PlayerStats,
+    replay::{Replay, ReplayFrame},
+};
 
-#[derive(Clone)]
+pub const MONSTER_DEFINITIONS_PATH: &str = "resources/monsters.ron";
+pub const SPELL_DEFINITIONS_PATH: &str = "resources/spells.ron";
+pub const SURVIVAL_WAVE_SCRIPT_PATH: &str = "resources/waves/survival.ron";
+pub const CAMPAIGN_PATH: &str = "resources/campaign.ron";
+pub static DEFAULT_LANDSCAPE: LandscapeDefinition = LandscapeDefinition {
+    texture: Cow::Borrowed("resources/assets/desert_level.png"),
+    sprite_sheet: Cow::Borrowed("resources/levels/desert.ron"),
+};
+
+#[derive(Clone, Debug, Deserialize)]
 pub struct MonsterDefinition {
     pub name: String,
     pub base_health: f32,
@@ -16,43 +45,793 @@ pub struct MonsterDefinition {
     pub base_attack_damage: f32,
     pub attack_type: MobAttackType,
     pub collision_radius: f32,
+    /// A path to a GameSpriteAnimationPrefab, is loaded by clients only.
+    pub prefab: String,
+    /// Relative chance of the monster being picked when spawning.
+    pub spawn_weight: u32,
+}
+
+impl MonsterDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("name must not be empty".to_owned());
+        }
+        if self.base_health <= 0.0 {
+            return Err(format!(
+                "base_health must be positive, got {}",
+                self.base_health
+            ));
+        }
+        if self.base_speed < 0.0 {
+            return Err(format!(
+                "base_speed must not be negative, got {}",
+                self.base_speed
+            ));
+        }
+        if self.base_attack_damage < 0.0 {
+            return Err(format!(
+                "base_attack_damage must not be negative, got {}",
+                self.base_attack_damage
+            ));
+        }
+        if self.collision_radius <= 0.0 {
+            return Err(format!(
+                "collision_radius must be positive, got {}",
+                self.collision_radius
+            ));
+        }
+        match self.attack_type {
+            MobAttackType::SlowMelee { cooldown } if cooldown <= 0.0 => {
+                return Err(format!(
+                    "SlowMelee cooldown must be positive, got {}",
+                    cooldown
+                ));
+            }
+            MobAttackType::Range { cooldown, .. } if cooldown <= 0.0 => {
+                return Err(format!("Range cooldown must be positive, got {}", cooldown));
+            }
+            MobAttackType::Range { distance, .. } if distance <= self.collision_radius => {
+                return Err(format!(
+                    "Range distance must exceed collision_radius, got {}",
+                    distance
+                ));
+            }
+            _ => {}
+        }
+        if self.prefab.is_empty() {
+            return Err("prefab must not be empty".to_owned());
+        }
+        Ok(())
+    }
 }
 
-pub struct MonsterDefinitions(pub HashMap<String, MonsterDefinition>);
+/// Is sorted by monster names, indices of definitions serve as monster type ids in SpawnAction.
+/// The stable order also makes picking a random monster type reproducible.
+pub struct MonsterDefinitions(Vec<MonsterDefinition>);
 
 impl MonsterDefinitions {
-    #[cfg(feature = "client")]
-    pub fn register(world: &mut World) {
-        let mut map = HashMap::new();
-        map.insert(
-            "Ghoul".to_owned(),
-            MonsterDefinition {
-                name: "Ghoul".to_owned(),
-                base_health: 100.0,
-                base_speed: 180.0,
-                base_attack_damage: 15.0,
-                attack_type: MobAttackType::SlowMelee { cooldown: 0.75 },
-                collision_radius: 12.0,
-            },
-        );
-        world.insert(Self(map))
-    }
-
-    #[cfg(not(feature = "client"))]
-    pub fn register(world: &mut World) {
-        let mut map = HashMap::new();
-        map.insert(
-            "Ghoul".to_owned(),
-            MonsterDefinition {
-                name: "Ghoul".to_owned(),
-                base_health: 100.0,
-                base_speed: 180.0,
-                base_attack_damage: 15.0,
-                attack_type: MobAttackType::SlowMelee { cooldown: 0.75 },
-                collision_radius: 12.0,
-            },
-        );
-        world.insert(Self(map))
+    pub fn register(world: &mut World) -> amethyst::Result<()> {
+        let monster_definitions = Self::load(Path::new(MONSTER_DEFINITIONS_PATH))?;
+        world.insert(monster_definitions);
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> amethyst::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        Self::from_ron(&contents)
+            .map_err(|err| Error::from_string(format!("{} ({})", err, path.display())))
+    }
+
+    pub fn from_ron(contents: &str) -> amethyst::Result<Self> {
+        let mut definitions: Vec<MonsterDefinition> = ron::de::from_str(contents)
+            .map_err(|err| Error::from_string(format!("invalid RON: {}", err)))?;
+
+        for definition in &definitions {
+            definition.validate().map_err(|err| {
+                Error::from_string(format!("invalid monster \"{}\": {}", definition.name, err))
+            })?;
+        }
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(duplicates) = definitions
+            .windows(2)
+            .find(|definitions| definitions[0].name == definitions[1].name)
+        {
+            return Err(Error::from_string(format!(
+                "monster \"{}\" is defined more than once",
+                duplicates[0].name
+            )));
+        }
+
+        if definitions
+            .iter()
+            .all(|definition| definition.spawn_weight == 0)
+        {
+            return Err(Error::from_string(
+                "at least one monster must have a positive spawn_weight".to_owned(),
+            ));
+        }
+        if definitions.len() > MonsterTypeId::max_value() as usize + 1 {
+            return Err(Error::from_string(format!(
+                "there can't be more than {} monsters",
+                MonsterTypeId::max_value() as usize + 1
+            )));
+        }
+        Ok(Self(definitions))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MonsterDefinition> {
+        self.0.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MonsterDefinition> {
+        self.type_id(name)
+            .map(|monster_type| self.by_type_id(monster_type))
+    }
+
+    pub fn type_id(&self, name: &str) -> Option<MonsterTypeId> {
+        self.0
+            .binary_search_by(|definition| definition.name.as_str().cmp(name))
+            .ok()
+            .map(|index| index as MonsterTypeId)
+    }
+
+    /// Panics if there's no such monster type, ids coming from the network
+    /// are expected to be checked with `contains_type_id`.
+    pub fn by_type_id(&self, monster_type: MonsterTypeId) -> &MonsterDefinition {
+        &self.0[monster_type as usize]
+    }
+
+    pub fn contains_type_id(&self, monster_type: MonsterTypeId) -> bool {
+        (monster_type as usize) < self.0.len()
+    }
+
+    /// Picks a monster type according to spawn weights.
+    pub fn random_monster_type<R: Rng + ?Sized>(&self, rng: &mut R) -> MonsterTypeId {
+        let total_weight: u32 = self
+            .0
+            .iter()
+            .map(|definition| definition.spawn_weight)
+            .sum();
+        let mut roll = rng.gen_range(0, total_weight);
+        for (monster_type, definition) in self.0.iter().enumerate() {
+            if roll < definition.spawn_weight {
+                return monster_type as MonsterTypeId;
+            }
+            roll -= definition.spawn_weight;
+        }
+        unreachable!("Expected to pick a monster type")
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpellDefinition {
+    pub id: SpellId,
+    pub name: String,
+    /// Gets reduced by the caster's cooldown stat.
+    pub cooldown_secs: f32,
+    pub mana_cost: f32,
+    pub behaviour: SpellBehaviour,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum SpellBehaviour {
+    /// A homing missile flying to the monster closest to the target position.
+    Projectile {
+        radius: f32,
+        /// Is multiplied by the caster's missile damage.
+        damage_multiplier: f32,
+    },
+    /// A blast expanding from the caster, damages every monster within the radius.
+    Area {
+        radius: f32,
+        /// Is multiplied by the caster's missile damage.
+        damage_multiplier: f32,
+    },
+}
+
+impl SpellDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("name must not be empty".to_owned());
+        }
+        if self.cooldown_secs < 0.0 {
+            return Err(format!(
+                "cooldown_secs must not be negative, got {}",
+                self.cooldown_secs
+            ));
+        }
+        if self.mana_cost < 0.0 {
+            return Err(format!(
+                "mana_cost must not be negative, got {}",
+                self.mana_cost
+            ));
+        }
+        let (radius, damage_multiplier) = match self.behaviour {
+            SpellBehaviour::Projectile {
+                radius,
+                damage_multiplier,
+            }
+            | SpellBehaviour::Area {
+                radius,
+                damage_multiplier,
+            } => (radius, damage_multiplier),
+        };
+        if radius <= 0.0 {
+            return Err(format!("radius must be positive, got {}", radius));
+        }
+        if damage_multiplier < 0.0 {
+            return Err(format!(
+                "damage_multiplier must not be negative, got {}",
+                damage_multiplier
+            ));
+        }
+        Ok(())
+    }
+
+    /// The cooldown with the caster's stats applied.
+    pub fn cooldown_secs(&self, stats: &PlayerStats) -> f32 {
+        self.cooldown_secs * stats.cooldown_multiplier()
+    }
+
+    pub fn damage(&self, stats: &PlayerStats) -> f32 {
+        match self.behaviour {
+            SpellBehaviour::Projectile {
+                damage_multiplier, ..
+            }
+            | SpellBehaviour::Area {
+                damage_multiplier, ..
+            } => stats.missile_damage() * damage_multiplier,
+        }
+    }
+
+    pub fn is_area(&self) -> bool {
+        matches!(self.behaviour, SpellBehaviour::Area { .. })
+    }
+}
+
+/// The spell registry, every SpellId is guaranteed to have a definition.
+pub struct SpellDefinitions(pub BTreeMap<SpellId, SpellDefinition>);
+
+impl SpellDefinitions {
+    pub fn register(world: &mut World) -> amethyst::Result<()> {
+        let spell_definitions = Self::load(Path::new(SPELL_DEFINITIONS_PATH))?;
+        world.insert(spell_definitions);
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> amethyst::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        Self::from_ron(&contents)
+            .map_err(|err| Error::from_string(format!("{} ({})", err, path.display())))
+    }
+
+    pub fn from_ron(contents: &str) -> amethyst::Result<Self> {
+        let definitions: Vec<SpellDefinition> = ron::de::from_str(contents)
+            .map_err(|err| Error::from_string(format!("invalid RON: {}", err)))?;
+
+        let mut map = BTreeMap::new();
+        for definition in definitions {
+            definition.validate().map_err(|err| {
+                Error::from_string(format!("invalid spell {:?}: {}", definition.id, err))
+            })?;
+            let id = definition.id;
+            if map.insert(id, definition).is_some() {
+                return Err(Error::from_string(format!(
+                    "spell {:?} is defined more than once",
+                    id
+                )));
+            }
+        }
+
+        if let Some(id) = SpellId::ALL.iter().find(|id| !map.contains_key(id)) {
+            return Err(Error::from_string(format!("spell {:?} is not defined", id)));
+        }
+        Ok(Self(map))
+    }
+
+    pub fn get(&self, spell: SpellId) -> &SpellDefinition {
+        self.0
+            .get(&spell)
+            .expect("Expected every spell to have a definition")
+    }
+}
+
+/// Describes when and which monsters a spawn director should spawn during a level.
+#[derive(Clone, Debug, Deserialize)]
+pub struct WaveScript {
+    /// Every `escalation_interval_secs` the escalation level goes up by one (it starts with 1).
+    pub escalation_interval_secs: f32,
+    pub waves: Vec<Wave>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Wave {
+    /// Seconds since the level start.
+    pub start_secs: f32,
+    /// A repeating wave stops spawning after this moment.
+    #[serde(default)]
+    pub end_secs: Option<f32>,
+    /// Seconds between spawns of a repeating wave. If not set, a wave spawns only once.
+    #[serde(default)]
+    pub interval_secs: Option<Curve>,
+    /// A monster definition name, if not set, it's picked randomly according to spawn weights.
+    #[serde(default)]
+    pub monster_type: Option<String>,
+    pub spawn: WaveSpawn,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum WaveSpawn {
+    /// Spawns monsters one by one, at random positions along the level borders
+    /// if `position` isn't set.
+    Single {
+        #[serde(default)]
+        position: Option<(f32, f32)>,
+        count: Curve,
+    },
+    /// Spawns a row of monsters along one side (a random one if not set), walking towards
+    /// the opposite side. If `count` isn't set, monsters fill the whole side.
+    Borderline {
+        #[serde(default)]
+        side: Option<Side>,
+        #[serde(default)]
+        count: Option<Curve>,
+    },
+}
+
+/// A value depending on the escalation level.
+#[derive(Clone, Debug, Deserialize)]
+pub enum Curve {
+    Constant(f32),
+    /// `base + per_level * (level - 1)`, optionally capped with `max`.
+    Linear {
+        base: f32,
+        per_level: f32,
+        #[serde(default)]
+        max: Option<f32>,
+    },
+    /// Starts close to `from` and approaches `to` as the level grows,
+    /// the bigger the `scale` is, the slower it goes.
+    Atan {
+        from: f32,
+        to: f32,
+        scale: f32,
+    },
+}
+
+impl Curve {
+    pub fn value(&self, level: usize) -> f32 {
+        let level = level as f32;
+        match *self {
+            Curve::Constant(value) => value,
+            Curve::Linear {
+                base,
+                per_level,
+                max,
+            } => {
+                let value = base + per_level * (level - 1.0);
+                max.map_or(value, |max| value.min(max))
+            }
+            Curve::Atan { from, to, scale } => {
+                from + (level / scale).atan() / std::f32::consts::PI * 2.0 * (to - from)
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Curve::Atan { scale, .. } = self {
+            if *scale <= 0.0 {
+                return Err(format!("Atan scale must be positive, got {}", scale));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WaveScript {
+    pub fn load(path: &Path, monster_definitions: &MonsterDefinitions) -> amethyst::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        let wave_script: Self = ron::de::from_str(&contents).map_err(|err| {
+            Error::from_string(format!("invalid RON: {} ({})", err, path.display()))
+        })?;
+        wave_script
+            .validate(monster_definitions)
+            .map_err(|err| Error::from_string(format!("{} ({})", err, path.display())))?;
+        Ok(wave_script)
+    }
+
+    fn validate(&self, monster_definitions: &MonsterDefinitions) -> Result<(), String> {
+        if self.escalation_interval_secs <= 0.0 {
+            return Err(format!(
+                "escalation_interval_secs must be positive, got {}",
+                self.escalation_interval_secs
+            ));
+        }
+        for (i, wave) in self.waves.iter().enumerate() {
+            wave.validate(monster_definitions)
+                .map_err(|err| format!("invalid wave #{}: {}", i, err))?;
+        }
+        Ok(())
+    }
+}
+
+impl Wave {
+    fn validate(&self, monster_definitions: &MonsterDefinitions) -> Result<(), String> {
+        if self.start_secs < 0.0 {
+            return Err(format!(
+                "start_secs must not be negative, got {}",
+                self.start_secs
+            ));
+        }
+        if let Some(end_secs) = self.end_secs {
+            if end_secs < self.start_secs {
+                return Err(format!(
+                    "end_secs ({}) must not be less than start_secs ({})",
+                    end_secs, self.start_secs
+                ));
+            }
+        }
+        if let Some(interval_secs) = &self.interval_secs {
+            interval_secs.validate()?;
+        }
+        if let Some(monster_type) = &self.monster_type {
+            if monster_definitions.get(monster_type).is_none() {
+                return Err(format!("unknown monster type \"{}\"", monster_type));
+            }
+        }
+        match &self.spawn {
+            WaveSpawn::Single { count, .. } => count.validate(),
+            WaveSpawn::Borderline { count, .. } => count.as_ref().map_or(Ok(()), Curve::validate),
+        }
+    }
+}
+
+/// Wave scripts of survival and all the campaign levels, keyed by their paths.
+/// Are loaded on start, so that starting a level can't fail.
+pub struct WaveScripts(HashMap<String, WaveScript>);
+
+impl WaveScripts {
+    /// Expects MonsterDefinitions and Campaign to be registered.
+    pub fn register(world: &mut World) -> amethyst::Result<()> {
+        let wave_scripts = {
+            let monster_definitions = world.fetch::<MonsterDefinitions>();
+            let campaign = world.fetch::<Campaign>();
+            let paths = iter::once(SURVIVAL_WAVE_SCRIPT_PATH).chain(
+                campaign
+                    .levels
+                    .iter()
+                    .map(|level| level.wave_script.as_str()),
+            );
+            let mut wave_scripts = HashMap::new();
+            for path in paths {
+                let wave_script = WaveScript::load(Path::new(path), &monster_definitions)?;
+                wave_scripts.insert(path.to_owned(), wave_script);
+            }
+            Self(wave_scripts)
+        };
+        world.insert(wave_scripts);
+        Ok(())
+    }
+
+    pub fn get(&self, path: &str) -> Option<&WaveScript> {
+        self.0.get(path)
+    }
+}
+
+/// Loads monsters, spells, the campaign and wave scripts, inserts the survival wave script
+/// as the current one.
+pub fn register_definitions(world: &mut World) -> amethyst::Result<()> {
+    MonsterDefinitions::register(world)?;
+    SpellDefinitions::register(world)?;
+    Campaign::register(world)?;
+    WaveScripts::register(world)?;
+    let survival_wave_script = world
+        .fetch::<WaveScripts>()
+        .get(SURVIVAL_WAVE_SCRIPT_PATH)
+        .cloned()
+        .expect("Expected the survival wave script to be loaded");
+    world.insert(survival_wave_script);
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameMode {
+    Survival,
+    Campaign { level_index: usize },
+}
+
+impl GameMode {
+    /// Returns None for survival, which isn't a part of a campaign.
+    pub fn level_definition<'a>(&self, campaign: &'a Campaign) -> Option<&'a LevelDefinition> {
+        match *self {
+            GameMode::Survival => None,
+            GameMode::Campaign { level_index } => campaign.levels.get(level_index),
+        }
+    }
+
+    pub fn wave_script_path<'a>(&self, campaign: &'a Campaign) -> &'a str {
+        self.level_definition(campaign)
+            .map_or(SURVIVAL_WAVE_SCRIPT_PATH, |level| {
+                level.wave_script.as_str()
+            })
+    }
+
+    pub fn landscape<'a>(&self, campaign: &'a Campaign) -> &'a LandscapeDefinition {
+        self.level_definition(campaign)
+            .map_or(&DEFAULT_LANDSCAPE, |level| &level.landscape)
+    }
+}
+
+impl GameMode {
+    pub fn campaign_level(self) -> Option<usize> {
+        match self {
+            GameMode::Survival => None,
+            GameMode::Campaign { level_index } => Some(level_index),
+        }
+    }
+
+    pub fn from_campaign_level(campaign_level: Option<usize>) -> Self {
+        campaign_level.map_or(GameMode::Survival, |level_index| GameMode::Campaign {
+            level_index,
+        })
+    }
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Survival
+    }
+}
+
+/// Campaign levels, played one after another.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Campaign {
+    pub levels: Vec<LevelDefinition>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LevelDefinition {
+    pub name: String,
+    pub dimensions: (f32, f32),
+    pub landscape: LandscapeDefinition,
+    /// A path to a WaveScript.
+    pub wave_script: String,
+    pub objective: Objective,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LandscapeDefinition {
+    pub texture: Cow<'static, str>,
+    /// A path to a sprite sheet, which also serves as a landscape id in AssetHandles.
+    pub sprite_sheet: Cow<'static, str>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum Objective {
+    Survive { secs: f32 },
+    KillMonsters { count: u32 },
+}
+
+impl Objective {
+    pub fn is_completed(&self, level_duration: Duration, monsters_killed: u32) -> bool {
+        match *self {
+            Objective::Survive { secs } => level_duration >= Duration::from_secs_f32(secs),
+            Objective::KillMonsters { count } => monsters_killed >= count,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match *self {
+            Objective::Survive { secs } => format!("Survive for {} seconds", secs.round()),
+            Objective::KillMonsters { count } => format!("Kill {} monsters", count),
+        }
+    }
+}
+
+impl Campaign {
+    pub fn register(world: &mut World) -> amethyst::Result<()> {
+        let campaign = {
+            let monster_definitions = world.fetch::<MonsterDefinitions>();
+            Self::load(Path::new(CAMPAIGN_PATH), &monster_definitions)?
+        };
+        world.insert(campaign);
+        Ok(())
+    }
+
+    /// Loads wave scripts of all the levels as well, so that broken ones are reported early.
+    pub fn load(path: &Path, monster_definitions: &MonsterDefinitions) -> amethyst::Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            Error::from_string(format!("couldn't read {}: {}", path.display(), err))
+        })?;
+        let campaign: Self = ron::de::from_str(&contents).map_err(|err| {
+            Error::from_string(format!("invalid RON: {} ({})", err, path.display()))
+        })?;
+
+        if campaign.levels.is_empty() {
+            return Err(Error::from_string(format!(
+                "a campaign must have at least one level ({})",
+                path.display()
+            )));
+        }
+        for level in &campaign.levels {
+            level.validate().map_err(|err| {
+                Error::from_string(format!(
+                    "invalid level \"{}\": {} ({})",
+                    level.name,
+                    err,
+                    path.display()
+                ))
+            })?;
+            WaveScript::load(Path::new(&level.wave_script), monster_definitions)?;
+        }
+        Ok(campaign)
+    }
+
+    pub fn is_last_level(&self, level_index: usize) -> bool {
+        level_index + 1 >= self.levels.len()
+    }
+}
+
+impl LevelDefinition {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("name must not be empty".to_owned());
+        }
+        if self.dimensions.0 <= 0.0 || self.dimensions.1 <= 0.0 {
+            return Err(format!(
+                "dimensions must be positive, got {:?}",
+                self.dimensions
+            ));
+        }
+        if let Objective::Survive { secs } = self.objective {
+            if secs <= 0.0 {
+                return Err(format!("Survive secs must be positive, got {}", secs));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Records single player games if a replay path is set (`--record-replay` on client).
+/// Every new game overwrites the file.
+#[derive(Default)]
+pub struct ReplayRecorder {
+    path: Option<PathBuf>,
+    replay: Option<Replay>,
+    players: Vec<Entity>,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            replay: None,
+            players: Vec::new(),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Does nothing if there's no replay path set. Multiplayer games aren't recorded,
+    /// as replays don't capture server updates, so `players` are expected to be empty for them.
+    pub fn start(&mut self, seed: u64, game_mode: GameMode, players: Vec<(Entity, PlayerStats)>) {
+        if self.path.is_none() {
+            return;
+        }
+        if players.is_empty() {
+            log::warn!("Only single player games can be recorded, skipping the replay");
+            return;
+        }
+        let (entities, stats) = players.into_iter().unzip();
+        self.players = entities;
+        self.replay = Some(Replay::new(seed, game_mode.campaign_level(), stats));
+    }
+
+    pub fn players(&self) -> &[Entity] {
+        &self.players
+    }
+
+    /// Returns `None` if the recording isn't started.
+    pub fn frame_mut(&mut self, frame_number: u64) -> Option<&mut ReplayFrame> {
+        self.replay
+            .as_mut()
+            .map(|replay| replay.frame_mut(frame_number))
+    }
+
+    /// Saves the recorded game, does nothing if the recording isn't started.
+    pub fn finish(&mut self) {
+        self.players.clear();
+        if let (Some(path), Some(replay)) = (&self.path, self.replay.take()) {
+            match replay.save(path) {
+                Ok(()) => log::info!(
+                    "Saved a replay of {} frames to {}",
+                    replay.frames.len(),
+                    path.display()
+                ),
+                Err(err) => log::error!("Failed to save the replay: {}", err),
+            }
+        }
+    }
+}
+
+/// Plays a recorded game back (`--replay` on client): recorded actions replace player input
+/// and SpawnDirectorSystem. Is active until the first replayed game is over.
+#[derive(Default)]
+pub struct ReplayPlayback {
+    replay: Option<Replay>,
+    players: Vec<Entity>,
+    pub paused: bool,
+    /// Makes a paused replay advance by a single frame.
+    pub step_requested: bool,
+    skips_frame: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay: Some(replay),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn game_mode(&self) -> Option<GameMode> {
+        self.replay
+            .as_ref()
+            .map(|replay| GameMode::from_campaign_level(replay.campaign_level))
+    }
+
+    /// Players are expected to be passed in the order of `Replay::players`.
+    pub fn start(&mut self, players: Vec<Entity>) {
+        self.players = players;
+    }
+
+    pub fn players(&self) -> &[Entity] {
+        &self.players
+    }
+
+    pub fn frame(&self, frame_number: u64) -> Option<&ReplayFrame> {
+        self.replay
+            .as_ref()
+            .and_then(|replay| replay.frame(frame_number))
+    }
+
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Is called by PauseSystem once a frame, consumes a requested step.
+    pub fn update_skips_frame(&mut self) -> bool {
+        self.skips_frame = self.is_active() && self.paused && !self.step_requested;
+        self.step_requested = false;
+        self.skips_frame
+    }
+
+    pub fn skips_frame(&self) -> bool {
+        self.skips_frame
     }
 }
 
@@ -60,3 +839,198 @@ impl MonsterDefinitions {
 pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ServerMessage>>);
 #[cfg(not(feature = "client"))]
 pub struct ConnectionEvents(pub Vec<ConnectionNetEvent<ClientMessage>>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GHOUL: &str = r#"(
+        name: "Ghoul",
+        base_health: 100.0,
+        base_speed: 180.0,
+        base_attack_damage: 15.0,
+        attack_type: SlowMelee(cooldown: 0.75),
+        collision_radius: 12.0,
+        prefab: "resources/prefabs/beetle.ron",
+        spawn_weight: 3,
+    )"#;
+    const SPITTER: &str = r#"(
+        name: "Spitter",
+        base_health: 60.0,
+        base_speed: 140.0,
+        base_attack_damage: 10.0,
+        attack_type: Range(cooldown: 1.5, distance: 180.0),
+        collision_radius: 12.0,
+        prefab: "resources/prefabs/beetle.ron",
+        spawn_weight: 0,
+    )"#;
+
+    fn ghoul() -> MonsterDefinition {
+        ron::de::from_str(GHOUL).expect("Expected a valid monster definition")
+    }
+
+    fn from_ron_error(contents: &str) -> String {
+        match MonsterDefinitions::from_ron(contents) {
+            Ok(_) => panic!("Expected monster definitions to be invalid"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_monster_definitions() {
+        let monster_definitions =
+            MonsterDefinitions::from_ron(&format!("[{}, {}]", GHOUL, SPITTER)).unwrap();
+
+        assert_eq!(monster_definitions.len(), 2);
+        assert_eq!(monster_definitions.type_id("Ghoul"), Some(0));
+        assert_eq!(monster_definitions.by_type_id(1).name, "Spitter");
+        assert!(!monster_definitions.contains_type_id(2));
+        let spitter = monster_definitions.get("Spitter").unwrap();
+        assert_eq!(spitter.base_health, 60.0);
+        match spitter.attack_type {
+            MobAttackType::Range { cooldown, distance } => {
+                assert_eq!((cooldown, distance), (1.5, 180.0));
+            }
+            _ => panic!("Expected a ranged attack"),
+        }
+        assert!(monster_definitions.get("Beetle").is_none());
+        // Spitter has no spawn weight, so Ghoul is the only one to be picked.
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            assert_eq!(monster_definitions.random_monster_type(&mut rng), 0);
+        }
+    }
+
+    #[test]
+    fn parses_shipped_monster_definitions() {
+        let contents = fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../..")
+                .join(MONSTER_DEFINITIONS_PATH),
+        )
+        .unwrap();
+        assert!(MonsterDefinitions::from_ron(&contents).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_ron() {
+        assert!(from_ron_error("[(name: \"Ghoul\",").contains("invalid RON"));
+        assert!(from_ron_error(GHOUL).contains("invalid RON"));
+    }
+
+    #[test]
+    fn rejects_missing_fields() {
+        let without_prefab = GHOUL.replace("prefab: \"resources/prefabs/beetle.ron\",", "");
+        let err = from_ron_error(&format!("[{}]", without_prefab));
+        assert!(err.contains("invalid RON"));
+        assert!(err.contains("prefab"));
+    }
+
+    #[test]
+    fn rejects_missing_definitions() {
+        assert!(from_ron_error("[]").contains("positive spawn_weight"));
+        assert!(from_ron_error(&format!("[{}]", SPITTER)).contains("positive spawn_weight"));
+    }
+
+    #[test]
+    fn rejects_duplicate_definitions() {
+        let err = from_ron_error(&format!("[{}, {}]", GHOUL, GHOUL));
+        assert!(err.contains("monster \"Ghoul\" is defined more than once"));
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let invalid_ghoul = GHOUL.replace("base_health: 100.0", "base_health: 0.0");
+        let err = from_ron_error(&format!("[{}, {}]", invalid_ghoul, SPITTER));
+        assert!(err.contains("invalid monster \"Ghoul\": base_health must be positive"));
+    }
+
+    #[test]
+    fn validates_monster_definitions() {
+        assert_eq!(ghoul().validate(), Ok(()));
+
+        let invalid_definitions: Vec<(fn(&mut MonsterDefinition), &str)> = vec![
+            (|ghoul| ghoul.name.clear(), "name must not be empty"),
+            (
+                |ghoul| ghoul.base_health = -1.0,
+                "base_health must be positive",
+            ),
+            (
+                |ghoul| ghoul.base_speed = -1.0,
+                "base_speed must not be negative",
+            ),
+            (
+                |ghoul| ghoul.base_attack_damage = -1.0,
+                "base_attack_damage must not be negative",
+            ),
+            (
+                |ghoul| ghoul.collision_radius = 0.0,
+                "collision_radius must be positive",
+            ),
+            (
+                |ghoul| ghoul.attack_type = MobAttackType::SlowMelee { cooldown: 0.0 },
+                "SlowMelee cooldown must be positive",
+            ),
+            (
+                |ghoul| {
+                    ghoul.attack_type = MobAttackType::Range {
+                        cooldown: -1.0,
+                        distance: 180.0,
+                    }
+                },
+                "Range cooldown must be positive",
+            ),
+            (
+                |ghoul| {
+                    ghoul.attack_type = MobAttackType::Range {
+                        cooldown: 1.5,
+                        distance: 12.0,
+                    }
+                },
+                "Range distance must exceed collision_radius",
+            ),
+            (|ghoul| ghoul.prefab.clear(), "prefab must not be empty"),
+        ];
+        for (invalidate, expected_err) in invalid_definitions {
+            let mut ghoul = ghoul();
+            invalidate(&mut ghoul);
+            let err = ghoul.validate().unwrap_err();
+            assert!(
+                err.starts_with(expected_err),
+                "Expected \"{}\", got \"{}\"",
+                expected_err,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_survival_borderline_interval() {
+        let monster_definitions = MonsterDefinitions::load(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../..")
+                .join(MONSTER_DEFINITIONS_PATH),
+        )
+        .unwrap();
+        let wave_script = WaveScript::load(
+            &Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../..")
+                .join(SURVIVAL_WAVE_SCRIPT_PATH),
+            &monster_definitions,
+        )
+        .unwrap();
+        let interval_secs = wave_script
+            .waves
+            .iter()
+            .find(|wave| matches!(wave.spawn, WaveSpawn::Borderline { .. }))
+            .and_then(|wave| wave.interval_secs.as_ref())
+            .expect("Expected a repeating Borderline wave");
+
+        // The formula LevelSystem used before wave scripts were introduced.
+        for level in 1..20 {
+            let expected =
+                30.0 - (level as f32 / 7.0).atan() / std::f32::consts::PI * 2.0 * (5.0 - 30.0);
+            assert!((interval_secs.value(level) - expected).abs() < 1e-3);
+        }
+    }
+}
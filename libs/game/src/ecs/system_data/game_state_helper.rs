@@ -5,11 +5,14 @@ use amethyst::{
 
 use gv_core::ecs::resources::{net::MultiplayerGameState, GameEngineState, NewGameEngineState};
 
+use crate::ecs::resources::ReplayPlayback;
+
 #[derive(SystemData)]
 pub struct GameStateHelper<'s> {
     game_engine_state: ReadExpect<'s, GameEngineState>,
     new_game_engine_state: ReadExpect<'s, NewGameEngineState>,
     multiplayer_game_state: ReadExpect<'s, MultiplayerGameState>,
+    replay_playback: ReadExpect<'s, ReplayPlayback>,
 }
 
 impl<'s> GameStateHelper<'s> {
@@ -22,6 +25,11 @@ impl<'s> GameStateHelper<'s> {
         *self.game_engine_state == GameEngineState::Playing
             && self.new_game_engine_state.0 == GameEngineState::Playing
             && multiplayer_is_unpaused
+            && !self.replay_playback.skips_frame()
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay_playback.is_active()
     }
 
     pub fn is_multiplayer(&self) -> bool {
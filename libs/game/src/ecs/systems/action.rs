@@ -28,7 +28,9 @@ use gv_core::{
     },
     ecs::{
         components::{
+            area_spell::AreaSpell,
             damage_history::{DamageHistory, DamageHistoryEntries},
+            enemy_projectile::EnemyProjectile,
             missile::Missile,
             ClientPlayerActions, Dead, EntityNetMetadata, Monster, NetWorldPosition, Player,
             PlayerActions, PlayerLastCastedSpells, WorldPosition,
@@ -36,10 +38,10 @@ use gv_core::{
         resources::{
             net::{
                 ActionUpdateIdProvider, CastActionsToExecute, EntityNetMetadataStorage,
-                MultiplayerGameState,
+                MultiplayerGameState, RangedAttacksToExecute,
             },
             world::{FramedUpdates, SavedWorldState, WorldStates},
-            GameLevelState,
+            GameLevelState, GameRngs,
         },
         system_data::time::GameTimeService,
     },
@@ -48,9 +50,11 @@ use gv_core::{
 
 use crate::{
     ecs::{
-        resources::MonsterDefinitions,
+        resources::{MonsterDefinitions, SpellDefinitions},
         system_data::GameStateHelper,
         systems::{
+            area_spell::{AreaSpellPhysicsSubsystem, AreaSpellSpawnerSubsystem},
+            enemy_projectile::{EnemyProjectilePhysicsSubsystem, EnemyProjectileSpawnerSubsystem},
             missile::{MissileFactory, MissilePhysicsSubsystem, MissileSpawnerSubsystem},
             monster::{ApplyMonsterActionNetArgs, MonsterActionSubsystem},
             player::{
@@ -81,7 +85,10 @@ pub struct ActionSystemData<'s> {
     entity_net_metadata_storage: WriteExpect<'s, EntityNetMetadataStorage>,
     action_update_id_provider: WriteExpect<'s, ActionUpdateIdProvider>,
     cast_actions_to_execute: WriteExpect<'s, CastActionsToExecute>,
+    ranged_attacks_to_execute: WriteExpect<'s, RangedAttacksToExecute>,
     monster_definitions: ReadExpect<'s, MonsterDefinitions>,
+    spell_definitions: ReadExpect<'s, SpellDefinitions>,
+    game_rngs: WriteExpect<'s, GameRngs>,
     client_player_actions: ReadStorage<'s, ClientPlayerActions>,
     transforms: WriteStorage<'s, Transform>,
     entity_net_metadata: WriteStorage<'s, EntityNetMetadata>,
@@ -90,6 +97,8 @@ pub struct ActionSystemData<'s> {
     player_last_casted_spells: WriteStorage<'s, PlayerLastCastedSpells>,
     monsters: WriteStorage<'s, Monster>,
     missiles: WriteStorage<'s, Missile>,
+    area_spells: WriteStorage<'s, AreaSpell>,
+    enemy_projectiles: WriteStorage<'s, EnemyProjectile>,
     world_positions: WriteStorage<'s, WorldPosition>,
     net_world_positions: WriteStorage<'s, NetWorldPosition>,
     dead: WriteStorage<'s, Dead>,
@@ -154,11 +163,16 @@ impl<'s> System<'s> for ActionSystem {
             Rc::new(RefCell::new(system_data.player_last_casted_spells));
         let monsters = Rc::new(RefCell::new(system_data.monsters));
         let missiles = Rc::new(RefCell::new(system_data.missiles));
+        let area_spells = Rc::new(RefCell::new(system_data.area_spells));
+        let enemy_projectiles = Rc::new(RefCell::new(system_data.enemy_projectiles));
         let cast_actions_to_execute = Rc::new(RefCell::new(system_data.cast_actions_to_execute));
+        let ranged_attacks_to_execute =
+            Rc::new(RefCell::new(system_data.ranged_attacks_to_execute));
         let world_positions = Rc::new(RefCell::new(system_data.world_positions));
         let net_world_positions = Rc::new(RefCell::new(system_data.net_world_positions));
         let dead = Rc::new(RefCell::new(system_data.dead));
         let damage_histories = Rc::new(RefCell::new(system_data.damage_histories));
+        let game_rngs = Rc::new(RefCell::new(system_data.game_rngs));
 
         let world_state_subsystem = WorldStateSubsystem {
             entities: &system_data.entities,
@@ -167,8 +181,11 @@ impl<'s> System<'s> for ActionSystem {
             player_last_casted_spells: player_last_casted_spells.clone(),
             monsters: monsters.clone(),
             missiles: missiles.clone(),
+            area_spells: area_spells.clone(),
+            enemy_projectiles: enemy_projectiles.clone(),
             world_positions: world_positions.clone(),
             dead: dead.clone(),
+            game_rngs: game_rngs.clone(),
         };
         let player_action_subsystem = PlayerActionSubsystem {
             game_time_service: &system_data.game_time_service,
@@ -176,12 +193,14 @@ impl<'s> System<'s> for ActionSystem {
             entities: &system_data.entities,
             game_level_state: &system_data.game_level_state,
             multiplayer_game_state: &system_data.multiplayer_game_state,
+            spell_definitions: &system_data.spell_definitions,
             client_player_actions: &system_data.client_player_actions,
             action_update_id_provider: action_update_id_provider.clone(),
             cast_actions_to_execute: cast_actions_to_execute.clone(),
             player_actions: player_actions.clone(),
             player_last_casted_spells: player_last_casted_spells.clone(),
             missiles: missiles.clone(),
+            area_spells: area_spells.clone(),
             world_positions: world_positions.clone(),
             animations_resource_bundle: &animations_resource_bundle,
         };
@@ -197,6 +216,8 @@ impl<'s> System<'s> for ActionSystem {
             world_positions: world_positions.clone(),
             net_world_positions: net_world_positions.clone(),
             damage_histories: damage_histories.clone(),
+            ranged_attacks_to_execute: ranged_attacks_to_execute.clone(),
+            game_rngs: game_rngs.clone(),
             animations_resource_bundle: &animations_resource_bundle,
         };
         let missile_factory = MissileFactory::new(
@@ -210,6 +231,7 @@ impl<'s> System<'s> for ActionSystem {
             game_state_helper: &system_data.game_state_helper,
             entities: &system_data.entities,
             missile_factory: &missile_factory,
+            spell_definitions: &system_data.spell_definitions,
             cast_actions_to_execute: cast_actions_to_execute.clone(),
             monsters: monsters.clone(),
             dead: dead.clone(),
@@ -225,6 +247,42 @@ impl<'s> System<'s> for ActionSystem {
             dead: dead.clone(),
             damage_histories: damage_histories.clone(),
             world_positions: world_positions.clone(),
+            game_rngs,
+        };
+        let area_spell_spawner_subsystem = AreaSpellSpawnerSubsystem {
+            entities: &system_data.entities,
+            spell_definitions: &system_data.spell_definitions,
+            cast_actions_to_execute: cast_actions_to_execute.clone(),
+            transforms: transforms.clone(),
+            area_spells: area_spells.clone(),
+            world_positions: world_positions.clone(),
+        };
+        let area_spell_physics_subsystem = AreaSpellPhysicsSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            monsters: monsters.clone(),
+            area_spells,
+            dead: dead.clone(),
+            damage_histories: damage_histories.clone(),
+            world_positions: world_positions.clone(),
+        };
+        let enemy_projectile_spawner_subsystem = EnemyProjectileSpawnerSubsystem {
+            entities: &system_data.entities,
+            ranged_attacks_to_execute,
+            transforms: transforms.clone(),
+            enemy_projectiles: enemy_projectiles.clone(),
+            world_positions: world_positions.clone(),
+        };
+        let enemy_projectile_physics_subsystem = EnemyProjectilePhysicsSubsystem {
+            game_time_service: &system_data.game_time_service,
+            game_state_helper: &system_data.game_state_helper,
+            entities: &system_data.entities,
+            players: players.clone(),
+            enemy_projectiles,
+            dead: dead.clone(),
+            damage_histories: damage_histories.clone(),
+            world_positions: world_positions.clone(),
         };
         let damage_subsystem = DamageSubsystem {
             game_state_helper: &system_data.game_state_helper,
@@ -420,6 +478,7 @@ impl<'s> System<'s> for ActionSystem {
                 player_action_subsystem.apply_cast_action(
                     frame_updated.frame_number,
                     entity,
+                    &mut player,
                     net_args,
                     client_side_actions,
                 );
@@ -475,9 +534,13 @@ impl<'s> System<'s> for ActionSystem {
             drop(dead_entities);
             drop(entity_net_metadata_storage);
 
-            // Run missile systems.
+            // Run spell systems, area spells have to drain their cast actions first.
+            area_spell_spawner_subsystem.spawn_area_spells(frame_updated.frame_number);
             missile_spawner_subsystem.spawn_missiles(frame_updated.frame_number);
             missile_physics_subsystem.process_physics(frame_updated.frame_number);
+            area_spell_physics_subsystem.process_physics(frame_updated.frame_number);
+            enemy_projectile_spawner_subsystem.spawn_projectiles(frame_updated.frame_number);
+            enemy_projectile_physics_subsystem.process_physics(frame_updated.frame_number);
 
             // Process damage history and add updates, if server.
             damage_subsystem.process_damage_history(
@@ -0,0 +1,41 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System};
+
+use gv_core::ecs::{
+    components::{area_spell::AreaSpell, Dead},
+    system_data::time::GameTimeService,
+};
+
+use crate::ecs::system_data::GameStateHelper;
+
+pub struct AreaSpellDyingSystem;
+
+pub const AREA_SPELL_TTL_SECS: f32 = 0.3;
+
+impl<'s> System<'s> for AreaSpellDyingSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, AreaSpell>,
+    );
+
+    fn run(
+        &mut self,
+        (game_state_helper, game_time_service, entities, dead, area_spells): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        for (area_spell_entity, dead, _) in (&entities, &dead, &area_spells).join() {
+            let to_be_deleted =
+                game_time_service.seconds_to_frame(dead.dead_since_frame) > AREA_SPELL_TTL_SECS;
+            if to_be_deleted {
+                entities
+                    .delete(area_spell_entity)
+                    .expect("Expected to delete an AreaSpell");
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod dying;
+mod physics_subsystem;
+mod spawner_subsystem;
+
+pub use self::{
+    dying::{AreaSpellDyingSystem, AREA_SPELL_TTL_SECS},
+    physics_subsystem::{AreaSpellPhysicsSubsystem, AREA_SPELL_EXPANSION_SECS},
+    spawner_subsystem::AreaSpellSpawnerSubsystem,
+};
@@ -0,0 +1,93 @@
+use amethyst::ecs::{Entities, Join};
+use gv_core::profile_scope;
+
+use gv_core::ecs::{
+    components::{
+        area_spell::AreaSpell,
+        damage_history::{DamageHistory, DamageHistoryEntry},
+        Dead, Monster, WorldPosition,
+    },
+    system_data::time::GameTimeService,
+};
+
+use crate::{
+    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    utils::entities::{area_spell_expansion, is_dead},
+};
+
+pub const AREA_SPELL_EXPANSION_SECS: f32 = 0.4;
+
+pub struct AreaSpellPhysicsSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub monsters: WriteStorageCell<'s, Monster>,
+    pub area_spells: WriteStorageCell<'s, AreaSpell>,
+    pub dead: WriteStorageCell<'s, Dead>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+}
+
+impl<'s> AreaSpellPhysicsSubsystem<'s> {
+    pub fn process_physics(&self, frame_number: u64) {
+        profile_scope!("AreaSpellPhysicsSubsystem::process_physics");
+        let monsters = self.monsters.borrow();
+        let mut area_spells = self.area_spells.borrow_mut();
+        let mut dead = self.dead.borrow_mut();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let world_positions = self.world_positions.borrow();
+
+        for (area_spell_entity, area_spell, area_spell_position) in
+            (self.entities, &mut *area_spells, &*world_positions).join()
+        {
+            if area_spell.frame_spawned > frame_number
+                || is_dead(area_spell_entity, &*dead, frame_number)
+            {
+                continue;
+            }
+
+            let expansion =
+                area_spell_expansion(&area_spell, &self.game_time_service, frame_number);
+            let current_radius = area_spell.radius * expansion;
+            for (monster_entity, monster, monster_position) in
+                (self.entities, &*monsters, &*world_positions).join()
+            {
+                if is_dead(monster_entity, &*dead, frame_number)
+                    || area_spell.hit_monsters.contains(&monster_entity)
+                {
+                    continue;
+                }
+
+                let distance = (**monster_position - **area_spell_position).norm();
+                if distance > current_radius + monster.radius {
+                    continue;
+                }
+
+                area_spell.hit_monsters.push(monster_entity);
+                if self.game_state_helper.is_authoritative() {
+                    damage_histories
+                        .get_mut(monster_entity)
+                        .expect("Expected a DamageHistory")
+                        .add_entry(
+                            frame_number,
+                            DamageHistoryEntry {
+                                damage: area_spell.damage,
+                                caster: area_spell.caster,
+                            },
+                        );
+                }
+            }
+
+            if expansion >= 1.0 {
+                let dead_since_frame = frame_number + 1;
+                let frame_acknowledged =
+                    dead_since_frame.max(self.game_time_service.game_frame_number());
+                dead.insert(
+                    area_spell_entity,
+                    Dead::new(dead_since_frame, frame_acknowledged),
+                )
+                .expect("Expected to insert a Dead component");
+            }
+        }
+    }
+}
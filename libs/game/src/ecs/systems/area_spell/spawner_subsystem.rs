@@ -0,0 +1,74 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, ReadExpect},
+};
+use gv_core::profile_scope;
+
+use gv_core::{
+    actions::IdentifiableAction,
+    ecs::{
+        components::{area_spell::AreaSpell, WorldPosition},
+        resources::net::{CastActionToExecute, CastActionsToExecute},
+    },
+};
+
+use crate::ecs::{
+    resources::{SpellBehaviour, SpellDefinitions},
+    systems::{WriteExpectCell, WriteStorageCell},
+};
+
+pub struct AreaSpellSpawnerSubsystem<'s> {
+    pub entities: &'s Entities<'s>,
+    pub spell_definitions: &'s ReadExpect<'s, SpellDefinitions>,
+    pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
+    pub transforms: WriteStorageCell<'s, Transform>,
+    pub area_spells: WriteStorageCell<'s, AreaSpell>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+}
+
+impl<'s> AreaSpellSpawnerSubsystem<'s> {
+    /// Leaves the rest of the cast actions to MissileSpawnerSubsystem.
+    pub fn spawn_area_spells(&self, frame_number: u64) {
+        profile_scope!("AreaSpellSpawnerSubsystem::spawn_area_spells");
+        let mut cast_actions_to_execute = self.cast_actions_to_execute.borrow_mut();
+        let mut transforms = self.transforms.borrow_mut();
+        let mut area_spells = self.area_spells.borrow_mut();
+        let mut world_positions = self.world_positions.borrow_mut();
+
+        let area_cast_actions = cast_actions_to_execute
+            .actions
+            .drain_filter(|cast_action| {
+                self.spell_definitions
+                    .get(cast_action.action.action.spell)
+                    .is_area()
+            });
+        for cast_action in area_cast_actions {
+            let CastActionToExecute {
+                action:
+                    IdentifiableAction {
+                        action_id,
+                        action: cast_action,
+                    },
+                damage,
+                caster,
+            } = cast_action;
+            let radius = match self.spell_definitions.get(cast_action.spell).behaviour {
+                SpellBehaviour::Area { radius, .. } => radius,
+                SpellBehaviour::Projectile { .. } => unreachable!(),
+            };
+
+            let position = cast_action.cast_position;
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(position.x, position.y, 0.0);
+            self.entities
+                .build_entity()
+                .with(transform, &mut transforms)
+                .with(WorldPosition::new(position), &mut world_positions)
+                .with(
+                    AreaSpell::new(action_id, radius, damage, frame_number, caster),
+                    &mut area_spells,
+                )
+                .build();
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use amethyst::ecs::{Entities, Join, WriteStorage};
 
+use std::collections::HashMap;
+
 use gv_core::{
     ecs::{
         components::{
@@ -72,6 +74,8 @@ impl<'s> DamageSubsystem<'s> {
         let mut players = self.players.borrow_mut();
         let mut monsters = self.monsters.borrow_mut();
         let mut dead = self.dead.borrow_mut();
+        // Monsters are credited to a player who has dealt the killing blow.
+        let mut killers = HashMap::new();
 
         for (entity, damage_history) in (self.entities, &*damage_histories).join() {
             if is_dead(entity, &*dead, frame_number) {
@@ -104,6 +108,9 @@ impl<'s> DamageSubsystem<'s> {
                     player.health -= damage_history_entry.damage;
                 } else if let Some(monster) = monsters.get_mut(entity) {
                     monster.health -= damage_history_entry.damage;
+                    if monster.health < 0.001 {
+                        killers.entry(entity).or_insert(damage_history_entry.caster);
+                    }
                 };
             }
         }
@@ -127,7 +134,9 @@ impl<'s> DamageSubsystem<'s> {
                     let dead_since_frame = frame_number + 1;
                     let frame_acknowledged =
                         dead_since_frame.max(self.game_time_service.game_frame_number());
-                    dead.insert(entity, Dead::new(dead_since_frame, frame_acknowledged))
+                    let mut entity_dead = Dead::new(dead_since_frame, frame_acknowledged);
+                    entity_dead.killer = killers.get(&entity).copied().flatten();
+                    dead.insert(entity, entity_dead)
                         .expect("Expected to insert Dead component");
                 }
             } else {
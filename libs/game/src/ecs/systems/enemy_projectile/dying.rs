@@ -0,0 +1,41 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System};
+
+use gv_core::ecs::{
+    components::{enemy_projectile::EnemyProjectile, Dead},
+    system_data::time::GameTimeService,
+};
+
+use crate::ecs::system_data::GameStateHelper;
+
+pub struct EnemyProjectileDyingSystem;
+
+pub const ENEMY_PROJECTILE_TTL_SECS: f32 = 0.3;
+
+impl<'s> System<'s> for EnemyProjectileDyingSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadStorage<'s, Dead>,
+        ReadStorage<'s, EnemyProjectile>,
+    );
+
+    fn run(
+        &mut self,
+        (game_state_helper, game_time_service, entities, dead, projectiles): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+
+        for (projectile_entity, dead, _) in (&entities, &dead, &projectiles).join() {
+            let to_be_deleted = game_time_service.seconds_to_frame(dead.dead_since_frame)
+                > ENEMY_PROJECTILE_TTL_SECS;
+            if to_be_deleted {
+                entities
+                    .delete(projectile_entity)
+                    .expect("Expected to delete an EnemyProjectile");
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod dying;
+mod physics_subsystem;
+mod spawner_subsystem;
+
+pub use self::{
+    dying::{EnemyProjectileDyingSystem, ENEMY_PROJECTILE_TTL_SECS},
+    physics_subsystem::{
+        EnemyProjectilePhysicsSubsystem, ENEMY_PROJECTILE_LIFESPAN_SECS, ENEMY_PROJECTILE_SPEED,
+    },
+    spawner_subsystem::EnemyProjectileSpawnerSubsystem,
+};
@@ -0,0 +1,98 @@
+use amethyst::ecs::{Entities, Join};
+use gv_core::profile_scope;
+
+use gv_core::ecs::{
+    components::{
+        damage_history::{DamageHistory, DamageHistoryEntry},
+        enemy_projectile::EnemyProjectile,
+        Dead, Player, WorldPosition,
+    },
+    system_data::time::GameTimeService,
+};
+
+use crate::{
+    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    utils::entities::is_dead,
+};
+
+pub const ENEMY_PROJECTILE_SPEED: f32 = 250.0;
+pub const ENEMY_PROJECTILE_LIFESPAN_SECS: f32 = 3.0;
+pub(super) const ENEMY_PROJECTILE_RADIUS: f32 = 6.0;
+
+pub struct EnemyProjectilePhysicsSubsystem<'s> {
+    pub game_time_service: &'s GameTimeService<'s>,
+    pub game_state_helper: &'s GameStateHelper<'s>,
+    pub entities: &'s Entities<'s>,
+    pub players: WriteStorageCell<'s, Player>,
+    pub enemy_projectiles: WriteStorageCell<'s, EnemyProjectile>,
+    pub dead: WriteStorageCell<'s, Dead>,
+    pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+}
+
+impl<'s> EnemyProjectilePhysicsSubsystem<'s> {
+    pub fn process_physics(&self, frame_number: u64) {
+        profile_scope!("EnemyProjectilePhysicsSubsystem::process_physics");
+        let players = self.players.borrow();
+        let enemy_projectiles = self.enemy_projectiles.borrow();
+        let mut dead = self.dead.borrow_mut();
+        let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut world_positions = self.world_positions.borrow_mut();
+
+        for (projectile_entity, projectile) in (self.entities, &*enemy_projectiles).join() {
+            if projectile.frame_spawned > frame_number
+                || is_dead(projectile_entity, &*dead, frame_number)
+            {
+                continue;
+            }
+
+            let projectile_position = **world_positions
+                .get(projectile_entity)
+                .expect("Expected a WorldPosition for an EnemyProjectile");
+            let hit_player = (self.entities, &*players, &*world_positions)
+                .join()
+                .find(|(player_entity, player, player_position)| {
+                    let hit_distance = player.radius + projectile.radius;
+                    !is_dead(*player_entity, &*dead, frame_number)
+                        && (***player_position - projectile_position).norm_squared()
+                            < hit_distance * hit_distance
+                })
+                .map(|(player_entity, _, _)| player_entity);
+            let is_expired = self
+                .game_time_service
+                .seconds_between_frames(frame_number, projectile.frame_spawned)
+                > ENEMY_PROJECTILE_LIFESPAN_SECS;
+
+            if let Some(hit_player) = hit_player {
+                if self.game_state_helper.is_authoritative() {
+                    damage_histories
+                        .get_mut(hit_player)
+                        .expect("Expected player's DamageHistory")
+                        .add_entry(
+                            frame_number,
+                            DamageHistoryEntry {
+                                damage: projectile.damage,
+                                caster: None,
+                            },
+                        );
+                }
+            }
+            if hit_player.is_some() || is_expired {
+                let dead_since_frame = frame_number + 1;
+                let frame_acknowledged =
+                    dead_since_frame.max(self.game_time_service.game_frame_number());
+                dead.insert(
+                    projectile_entity,
+                    Dead::new(dead_since_frame, frame_acknowledged),
+                )
+                .expect("Expected to insert a Dead component");
+                continue;
+            }
+
+            **world_positions
+                .get_mut(projectile_entity)
+                .expect("Expected a WorldPosition for an EnemyProjectile") +=
+                projectile.velocity * self.game_time_service.engine_time().fixed_seconds();
+        }
+    }
+}
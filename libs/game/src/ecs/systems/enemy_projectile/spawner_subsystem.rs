@@ -0,0 +1,74 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, Join},
+};
+use gv_core::profile_scope;
+
+use gv_core::ecs::{
+    components::{enemy_projectile::EnemyProjectile, WorldPosition},
+    resources::net::RangedAttacksToExecute,
+};
+
+use crate::ecs::systems::{
+    enemy_projectile::physics_subsystem::{ENEMY_PROJECTILE_RADIUS, ENEMY_PROJECTILE_SPEED},
+    WriteExpectCell, WriteStorageCell,
+};
+
+pub struct EnemyProjectileSpawnerSubsystem<'s> {
+    pub entities: &'s Entities<'s>,
+    pub ranged_attacks_to_execute: WriteExpectCell<'s, RangedAttacksToExecute>,
+    pub transforms: WriteStorageCell<'s, Transform>,
+    pub enemy_projectiles: WriteStorageCell<'s, EnemyProjectile>,
+    pub world_positions: WriteStorageCell<'s, WorldPosition>,
+}
+
+impl<'s> EnemyProjectileSpawnerSubsystem<'s> {
+    pub fn spawn_projectiles(&self, frame_number: u64) {
+        profile_scope!("EnemyProjectileSpawnerSubsystem::spawn_projectiles");
+        let mut ranged_attacks_to_execute = self.ranged_attacks_to_execute.borrow_mut();
+        let mut transforms = self.transforms.borrow_mut();
+        let mut enemy_projectiles = self.enemy_projectiles.borrow_mut();
+        let mut world_positions = self.world_positions.borrow_mut();
+
+        for attack in ranged_attacks_to_execute.attacks.drain(..) {
+            let direction = attack.target_position - attack.position;
+            let velocity = if direction.norm_squared() > 0.0 {
+                direction.normalize() * ENEMY_PROJECTILE_SPEED
+            } else {
+                direction
+            };
+
+            // Re-simulated frames produce the same attacks, we just reset projectiles then.
+            let already_spawned = (self.entities, &mut *enemy_projectiles)
+                .join()
+                .find(|(_, projectile)| {
+                    projectile.owner == attack.monster && projectile.frame_spawned == frame_number
+                });
+            if let Some((projectile_entity, projectile)) = already_spawned {
+                projectile.velocity = velocity;
+                **world_positions
+                    .get_mut(projectile_entity)
+                    .expect("Expected a WorldPosition for an EnemyProjectile") = attack.position;
+                continue;
+            }
+
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(attack.position.x, attack.position.y, 0.0);
+            self.entities
+                .build_entity()
+                .with(transform, &mut transforms)
+                .with(WorldPosition::new(attack.position), &mut world_positions)
+                .with(
+                    EnemyProjectile::new(
+                        attack.monster,
+                        ENEMY_PROJECTILE_RADIUS,
+                        velocity,
+                        frame_number,
+                        attack.damage,
+                    ),
+                    &mut enemy_projectiles,
+                )
+                .build();
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, WriteExpect};
+
+use gv_core::{
+    ecs::{
+        components::{Dead, Monster},
+        resources::GameLevelState,
+        system_data::time::GameTimeService,
+    },
+    net::NetIdentifier,
+};
+
+use crate::ecs::{
+    resources::{Campaign, GameMode},
+    system_data::GameStateHelper,
+};
+
+/// Counts killed monsters and marks a campaign level as over once its objective is completed.
+pub struct LevelObjectiveSystem;
+
+impl<'s> System<'s> for LevelObjectiveSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        Entities<'s>,
+        ReadExpect<'s, GameMode>,
+        ReadExpect<'s, Campaign>,
+        WriteExpect<'s, GameLevelState>,
+        ReadStorage<'s, Monster>,
+        ReadStorage<'s, Dead>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            entities,
+            game_mode,
+            campaign,
+            mut game_level_state,
+            monsters,
+            dead,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() || game_level_state.is_over {
+            return;
+        }
+
+        let frame_number = game_time_service.game_frame_number();
+        game_level_state
+            .counted_kills
+            .retain(|monster| entities.is_alive(*monster));
+        let killed_monsters: Vec<(Entity, Option<NetIdentifier>)> = (&entities, &monsters, &dead)
+            .join()
+            .filter(|(monster, _, dead)| {
+                frame_number >= dead.frame_acknowledged
+                    && !game_level_state.counted_kills.contains(monster)
+            })
+            .map(|(monster, _, dead)| (monster, dead.killer))
+            .collect();
+        for (monster, killer) in killed_monsters {
+            game_level_state.counted_kills.insert(monster);
+            game_level_state.monsters_killed += 1;
+            if let Some(killer) = killer {
+                *game_level_state
+                    .monsters_killed_by_players
+                    .entry(killer)
+                    .or_insert(0) += 1;
+            }
+        }
+
+        if !game_state_helper.is_authoritative() {
+            return;
+        }
+        let level_definition = if let Some(level_definition) = game_mode.level_definition(&campaign)
+        {
+            level_definition
+        } else {
+            return;
+        };
+
+        let level_duration = game_time_service.level_duration();
+        if level_definition
+            .objective
+            .is_completed(level_duration, game_level_state.monsters_killed)
+        {
+            log::info!(
+                "Level \"{}\" is completed in {:.1}s ({} monster(s) killed)",
+                level_definition.name,
+                level_duration.as_secs_f32(),
+                game_level_state.monsters_killed
+            );
+            game_level_state.completed_at = Some(level_duration);
+            game_level_state.is_over = true;
+        }
+    }
+}
@@ -4,18 +4,24 @@ use amethyst::{
 };
 use gv_core::profile_scope;
 
-use gv_core::ecs::{
-    components::{
-        damage_history::{DamageHistory, DamageHistoryEntry},
-        missile::{Missile, MissileTarget},
-        Dead, Monster, WorldPosition,
+use gv_core::{
+    ecs::{
+        components::{
+            damage_history::{DamageHistory, DamageHistoryEntry},
+            missile::{Missile, MissileTarget},
+            Dead, Monster, WorldPosition,
+        },
+        resources::{GameLevelState, GameRngs},
+        system_data::time::GameTimeService,
     },
-    resources::GameLevelState,
-    system_data::time::GameTimeService,
+    FRAME_RATE,
 };
 
 use crate::{
-    ecs::{system_data::GameStateHelper, systems::WriteStorageCell},
+    ecs::{
+        system_data::GameStateHelper,
+        systems::{WriteExpectCell, WriteStorageCell},
+    },
     utils::{
         entities::{is_dead, missile_energy},
         world::{closest_monster, find_first_hit_monster, random_scene_position},
@@ -27,7 +33,7 @@ pub const MISSILE_MIN_SPEED: f32 = 80.0;
 pub const MISSILE_TIME_TO_FADE: f32 = 0.5;
 pub const MISSILE_LIFESPAN_SECS: f32 = 5.0;
 
-const MS_PER_FRAME: f32 = 1000.0 / 60.0;
+const MS_PER_FRAME: f32 = 1000.0 / FRAME_RATE as f32;
 
 const TIME_TO_ACCELERATE: f32 = 2000.0;
 const MISSILE_ACCELERATION: f32 =
@@ -45,6 +51,7 @@ pub struct MissilePhysicsSubsystem<'s> {
     pub dead: WriteStorageCell<'s, Dead>,
     pub damage_histories: WriteStorageCell<'s, DamageHistory>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
+    pub game_rngs: WriteExpectCell<'s, GameRngs>,
 }
 
 impl<'s> MissilePhysicsSubsystem<'s> {
@@ -55,6 +62,8 @@ impl<'s> MissilePhysicsSubsystem<'s> {
         let mut dead = self.dead.borrow_mut();
         let mut damage_histories = self.damage_histories.borrow_mut();
         let mut world_positions = self.world_positions.borrow_mut();
+        let mut game_rngs = self.game_rngs.borrow_mut();
+        let game_rng = &mut game_rngs.missiles;
 
         for (missile_entity, mut missile) in (self.entities, &mut *missiles).join() {
             let is_dead = is_dead(missile_entity, &*dead, frame_number);
@@ -94,7 +103,8 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                     ) {
                         (target_position, Some(MissileTarget::Target(target)))
                     } else {
-                        let target_position = random_scene_position(self.game_level_state);
+                        let target_position =
+                            random_scene_position(self.game_level_state, &mut *game_rng);
                         (
                             target_position,
                             Some(MissileTarget::Destination(target_position)),
@@ -116,7 +126,8 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                     {
                         (destination, None)
                     } else {
-                        let target_position = random_scene_position(&*self.game_level_state);
+                        let target_position =
+                            random_scene_position(&*self.game_level_state, &mut *game_rng);
                         (
                             target_position,
                             Some(MissileTarget::Destination(target_position)),
@@ -147,6 +158,7 @@ impl<'s> MissilePhysicsSubsystem<'s> {
                                     frame_number,
                                     DamageHistoryEntry {
                                         damage: missile.damage,
+                                        caster: missile.caster,
                                     },
                                 );
                         }
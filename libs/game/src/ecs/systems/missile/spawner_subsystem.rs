@@ -1,6 +1,6 @@
 use amethyst::{
     core::Transform,
-    ecs::{Entities, Entity, WriteStorage},
+    ecs::{Entities, Entity, ReadExpect, WriteStorage},
 };
 use gv_core::profile_scope;
 
@@ -8,14 +8,16 @@ use gv_core::{
     actions::IdentifiableAction,
     ecs::{
         components::{missile::*, Dead, Monster, WorldPosition},
-        resources::net::CastActionsToExecute,
+        resources::net::{CastActionToExecute, CastActionsToExecute},
         system_data::time::GameTimeService,
     },
     math::Vector2,
+    net::NetIdentifier,
 };
 
 use crate::{
     ecs::{
+        resources::{SpellBehaviour, SpellDefinitions},
         system_data::GameStateHelper,
         systems::{
             missile::physics_subsystem::MISSILE_MAX_SPEED, GraphicsResourceBundle, WriteExpectCell,
@@ -30,6 +32,7 @@ pub struct MissileSpawnerSubsystem<'a, 's> {
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub entities: &'s Entities<'s>,
     pub missile_factory: &'a MissileFactory<'a, 's>,
+    pub spell_definitions: &'s ReadExpect<'s, SpellDefinitions>,
     pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
     pub monsters: WriteStorageCell<'s, Monster>,
     pub dead: WriteStorageCell<'s, Dead>,
@@ -44,11 +47,24 @@ impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
         let dead = self.dead.borrow();
         let monsters = self.monsters.borrow();
 
+        // Area spells are drained by AreaSpellSpawnerSubsystem beforehand.
         for cast_action in cast_actions_to_execute.actions.drain(..) {
-            let IdentifiableAction {
-                action_id,
-                action: cast_action,
+            let CastActionToExecute {
+                action:
+                    IdentifiableAction {
+                        action_id,
+                        action: cast_action,
+                    },
+                damage,
+                caster,
             } = cast_action;
+            let radius = match self.spell_definitions.get(cast_action.spell).behaviour {
+                SpellBehaviour::Projectile { radius, .. } => radius,
+                SpellBehaviour::Area { .. } => {
+                    log::warn!("Expected a projectile spell: {:?}", cast_action.spell);
+                    continue;
+                }
+            };
 
             let search_result = closest_monster(
                 cast_action.target_position,
@@ -70,11 +86,13 @@ impl<'a, 's> MissileSpawnerSubsystem<'a, 's> {
             self.missile_factory.create(
                 action_id,
                 &mut *world_positions,
-                5.0,
+                radius,
                 target,
                 velocity,
                 frame_number,
                 cast_action.cast_position,
+                damage,
+                caster,
             );
         }
     }
@@ -114,6 +132,8 @@ impl<'a, 's> MissileFactory<'a, 's> {
         velocity: Vector2,
         frame_spawned: u64,
         position: Vector2,
+        damage: f32,
+        caster: Option<NetIdentifier>,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 50.0);
@@ -123,7 +143,15 @@ impl<'a, 's> MissileFactory<'a, 's> {
             .with(transform, &mut self.transforms.borrow_mut())
             .with(WorldPosition::new(position), world_positions)
             .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
+                Missile::new(
+                    action_id,
+                    radius,
+                    target,
+                    velocity,
+                    frame_spawned,
+                    damage,
+                    caster,
+                ),
                 &mut self.missiles.borrow_mut(),
             )
             .build()
@@ -139,6 +167,8 @@ impl<'a, 's> MissileFactory<'a, 's> {
         velocity: Vector2,
         frame_spawned: u64,
         position: Vector2,
+        damage: f32,
+        caster: Option<NetIdentifier>,
     ) -> Entity {
         let mut transform = Transform::default();
         transform.set_translation_xyz(position.x, position.y, 0.0);
@@ -148,7 +178,15 @@ impl<'a, 's> MissileFactory<'a, 's> {
             .with(transform, &mut self.transforms.borrow_mut())
             .with(WorldPosition::new(position), world_positions)
             .with(
-                Missile::new(action_id, radius, target, velocity, frame_spawned),
+                Missile::new(
+                    action_id,
+                    radius,
+                    target,
+                    velocity,
+                    frame_spawned,
+                    damage,
+                    caster,
+                ),
                 &mut self.missiles.borrow_mut(),
             )
             .build()
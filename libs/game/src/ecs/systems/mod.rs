@@ -1,12 +1,16 @@
+pub mod area_spell;
+pub mod enemy_projectile;
 pub mod missile;
 pub mod monster;
 pub mod player;
 
 mod action;
 mod damage_subsystem;
-mod level;
+mod level_objective;
 mod net_connection_manager;
 mod pause;
+mod replay;
+mod spawn_director;
 mod state_switcher;
 mod world_position_transform;
 mod world_state_subsystem;
@@ -14,9 +18,11 @@ mod world_state_subsystem;
 pub use self::{
     action::ActionSystem,
     damage_subsystem::DamageSubsystem,
-    level::LevelSystem,
+    level_objective::LevelObjectiveSystem,
     net_connection_manager::{NetConnectionManagerDesc, NetConnectionManagerSystem},
     pause::PauseSystem,
+    replay::ReplaySystem,
+    spawn_director::SpawnDirectorSystem,
     state_switcher::StateSwitcherSystem,
     world_position_transform::WorldPositionTransformSystem,
     world_state_subsystem::WorldStateSubsystem,
@@ -13,7 +13,10 @@ use gv_core::{
             ClientPlayerActions, EntityNetMetadata, Monster, NetWorldPosition, Player,
             WorldPosition,
         },
-        resources::GameLevelState,
+        resources::{
+            net::{RangedAttackToExecute, RangedAttacksToExecute},
+            GameLevelState, GameRngs,
+        },
         system_data::time::GameTimeService,
     },
     math::{Vector2, ZeroVector},
@@ -24,12 +27,16 @@ use crate::{
     ecs::{
         resources::MonsterDefinitions,
         system_data::GameStateHelper,
-        systems::{AnimationsResourceBundle, OutcomingNetUpdates, WriteStorageCell},
+        systems::{
+            AnimationsResourceBundle, OutcomingNetUpdates, WriteExpectCell, WriteStorageCell,
+        },
     },
     utils::world::random_scene_position,
 };
 
 const MAX_IDLE_TIME_SECS: f32 = 0.5;
+/// Ranged monsters stay a bit closer than their attack distance, so targets don't slip away.
+const RANGED_KEEP_DISTANCE_FACTOR: f32 = 0.8;
 
 pub struct MonsterActionSubsystem<'a, 's> {
     pub entities: &'s Entities<'s>,
@@ -43,6 +50,8 @@ pub struct MonsterActionSubsystem<'a, 's> {
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
     pub net_world_positions: WriteStorageCell<'s, NetWorldPosition>,
     pub damage_histories: WriteStorageCell<'s, DamageHistory>,
+    pub ranged_attacks_to_execute: WriteExpectCell<'s, RangedAttacksToExecute>,
+    pub game_rngs: WriteExpectCell<'s, GameRngs>,
     pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
 }
 
@@ -105,6 +114,10 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             self.new_action(frame_number, &monster, monster_position.clone())
         };
 
+        let monster_definition = self
+            .monster_definitions
+            .get(&monster.name)
+            .expect("Expected a MonsterDefinition");
         let world_positions = self.world_positions.borrow();
         let net_world_positions = self.net_world_positions.borrow();
         let is_multiplayer = self.game_state_helper.is_multiplayer();
@@ -118,24 +131,26 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             );
             match new_action {
                 MobAction::Move(position) => Some(*position),
-                MobAction::Chase(target) => Some(target_position(
-                    *target,
-                    &world_positions,
-                    &net_world_positions,
-                    &self.client_player_actions,
-                    is_multiplayer,
+                MobAction::Chase(target) => Some(chase_destination(
+                    *monster_position,
+                    target_position(
+                        *target,
+                        &world_positions,
+                        &net_world_positions,
+                        &self.client_player_actions,
+                        is_multiplayer,
+                    ),
+                    &monster_definition.attack_type,
                 )),
                 MobAction::Attack(MobAttackAction {
                     target,
                     attack_type,
                 }) => {
-                    if let MobAttackType::Melee | MobAttackType::SlowMelee { .. } = attack_type {
-                        self.animations_resource_bundle.play_animation(
-                            entity,
-                            MONSTER_BODY,
-                            AnimationId::Attack,
-                        );
-                    }
+                    self.animations_resource_bundle.play_animation(
+                        entity,
+                        MONSTER_BODY,
+                        AnimationId::Attack,
+                    );
 
                     match attack_type {
                         MobAttackType::Melee => Some(target_position(
@@ -145,19 +160,47 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                             &self.client_player_actions,
                             is_multiplayer,
                         )),
-                        _ => Some(monster_position.position),
+                        MobAttackType::Range { .. } => {
+                            let target_position = target_position(
+                                *target,
+                                &world_positions,
+                                &net_world_positions,
+                                &self.client_player_actions,
+                                is_multiplayer,
+                            );
+                            // Is executed by EnemyProjectileSpawnerSubsystem both on server
+                            // and clients, as every peer receives the attack action.
+                            self.ranged_attacks_to_execute.borrow_mut().attacks.push(
+                                RangedAttackToExecute {
+                                    monster: entity,
+                                    position: *monster_position,
+                                    target_position,
+                                    damage: monster.attack_damage,
+                                },
+                            );
+                            Some(chase_destination(
+                                *monster_position,
+                                target_position,
+                                attack_type,
+                            ))
+                        }
+                        MobAttackType::SlowMelee { .. } => Some(monster_position.position),
                     }
                 }
                 _ => None,
             }
         } else {
             match monster.action.action {
-                MobAction::Chase(target) => Some(target_position(
-                    target,
-                    &world_positions,
-                    &net_world_positions,
-                    &self.client_player_actions,
-                    is_multiplayer,
+                MobAction::Chase(target) => Some(chase_destination(
+                    *monster_position,
+                    target_position(
+                        target,
+                        &world_positions,
+                        &net_world_positions,
+                        &self.client_player_actions,
+                        is_multiplayer,
+                    ),
+                    &monster_definition.attack_type,
                 )),
                 _ => None,
             }
@@ -184,7 +227,6 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
 
         let monster_definition = self
             .monster_definitions
-            .0
             .get(&monster.name)
             .expect("Expected a MonsterDefinition");
 
@@ -218,10 +260,11 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
         let players = self.players.borrow();
         let world_positions = self.world_positions.borrow();
         let mut damage_histories = self.damage_histories.borrow_mut();
+        let mut game_rngs = self.game_rngs.borrow_mut();
+        let game_rng = &mut game_rngs.monsters;
 
         let monster_definition = self
             .monster_definitions
-            .0
             .get(&monster.name)
             .expect("Expected a MonsterDefinition");
 
@@ -240,6 +283,7 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                     if MAX_IDLE_TIME_SECS < time_being_idle {
                         Some(MobAction::Move(random_scene_position(
                             &*self.game_level_state,
+                            &mut *game_rng,
                         )))
                     } else {
                         None
@@ -263,9 +307,12 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                 if let Some((target, _player_position)) = find_player_in_radius(
                     (self.entities, &*players, &*world_positions).join(),
                     *monster_position,
-                    monster.radius,
+                    attack_radius(monster, &monster_definition.attack_type),
                 ) {
-                    if self.game_state_helper.is_authoritative() {
+                    // Ranged attacks deal damage with projectiles.
+                    let is_melee =
+                        !matches!(monster_definition.attack_type, MobAttackType::Range { .. });
+                    if is_melee && self.game_state_helper.is_authoritative() {
                         let damage_history = damage_histories
                             .get_mut(target)
                             .expect("Expected player's DamageHistory");
@@ -273,12 +320,15 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                             frame_number,
                             DamageHistoryEntry {
                                 damage: monster.attack_damage,
+                                caster: None,
                             },
                         );
                     }
                     Some(MobAction::Attack(MobAttackAction {
                         target,
-                        attack_type: monster_definition.attack_type.randomize_params(0.2),
+                        attack_type: monster_definition
+                            .attack_type
+                            .randomize_params(0.2, &mut *game_rng),
                     }))
                 } else {
                     None
@@ -286,7 +336,8 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
             }
             MobAction::Attack(ref attack_action) => {
                 let is_cooling_down = match attack_action.attack_type {
-                    MobAttackType::SlowMelee { cooldown } => {
+                    MobAttackType::SlowMelee { cooldown }
+                    | MobAttackType::Range { cooldown, .. } => {
                         self.game_time_service
                             .seconds_between_frames(frame_number, monster.action.frame_number)
                             < cooldown
@@ -296,11 +347,19 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                 let player_in_radius = find_player_in_radius(
                     (self.entities, &*players, &*world_positions).join(),
                     *monster_position,
-                    monster.radius,
+                    attack_radius(monster, &attack_action.attack_type),
                 );
                 match (&attack_action.attack_type, player_in_radius) {
-                    // TODO: implement cooling down for other attacks as well.
-                    (MobAttackType::SlowMelee { .. }, _) if is_cooling_down => None,
+                    // TODO: implement cooling down for melee attacks as well.
+                    _ if is_cooling_down => None,
+                    (MobAttackType::Range { .. }, Some((target, _player_position))) => {
+                        Some(MobAction::Attack(MobAttackAction {
+                            target,
+                            attack_type: monster_definition
+                                .attack_type
+                                .randomize_params(0.2, &mut *game_rng),
+                        }))
+                    }
                     (_, Some((target, _player_position))) => {
                         if self.game_state_helper.is_authoritative() {
                             let damage_history = damage_histories
@@ -310,12 +369,15 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
                                 frame_number,
                                 DamageHistoryEntry {
                                     damage: monster.attack_damage,
+                                    caster: None,
                                 },
                             );
                         }
                         Some(MobAction::Attack(MobAttackAction {
                             target,
-                            attack_type: monster_definition.attack_type.randomize_params(0.2),
+                            attack_type: monster_definition
+                                .attack_type
+                                .randomize_params(0.2, &mut *game_rng),
                         }))
                     }
                     (_, None) => Some(MobAction::Idle),
@@ -325,6 +387,31 @@ impl<'a, 's> MonsterActionSubsystem<'a, 's> {
     }
 }
 
+fn attack_radius(monster: &Monster, attack_type: &MobAttackType) -> f32 {
+    match attack_type {
+        MobAttackType::Range { distance, .. } => *distance,
+        _ => monster.radius,
+    }
+}
+
+/// Ranged monsters approach (or retreat from) a target to keep their attack distance.
+fn chase_destination(
+    monster_position: Vector2,
+    target_position: Vector2,
+    attack_type: &MobAttackType,
+) -> Vector2 {
+    match attack_type {
+        MobAttackType::Range { distance, .. } => {
+            let offset = monster_position - target_position;
+            if offset.norm_squared() < 0.01 {
+                return monster_position;
+            }
+            target_position + offset.normalize() * distance * RANGED_KEEP_DISTANCE_FACTOR
+        }
+        _ => target_position,
+    }
+}
+
 fn find_player_in_radius<'a>(
     mut players: impl Iterator<Item = (Entity, &'a Player, &'a WorldPosition)>,
     position: Vector2,
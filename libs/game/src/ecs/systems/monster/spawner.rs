@@ -97,12 +97,20 @@ impl<'s> MonsterSpawnerSystemData<'s> {
         let spawn_actions = self.get_spawn_actions(&spawn_actions);
 
         for spawn_action in spawn_actions {
-            let ghoul = self
+            let monster_definition = if self
                 .monster_definitions
-                .0
-                .get("Ghoul")
-                .expect("Failed to get Ghoul monster definition")
-                .clone();
+                .contains_type_id(spawn_action.monster_type)
+            {
+                self.monster_definitions
+                    .by_type_id(spawn_action.monster_type)
+                    .clone()
+            } else {
+                log::error!(
+                    "Skipping a spawn action with an unknown monster type: {}",
+                    spawn_action.monster_type
+                );
+                continue;
+            };
 
             match spawn_action.spawn_type {
                 SpawnType::Single {
@@ -116,7 +124,7 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                             frame_number,
                             action: MobAction::Idle,
                         },
-                        &ghoul,
+                        &monster_definition,
                         entity_net_id,
                     );
                 }
@@ -139,7 +147,7 @@ impl<'s> MonsterSpawnerSystemData<'s> {
                             frame_number,
                             position,
                             action,
-                            &ghoul,
+                            &monster_definition,
                             entity_net_id_range.as_mut().map(|entity_net_id_range| {
                                 entity_net_id_range
                                     .next()
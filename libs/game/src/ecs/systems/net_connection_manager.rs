@@ -10,6 +10,7 @@ use amethyst::{
 };
 
 use std::{
+    collections::HashMap,
     net::SocketAddr,
     time::{Duration, Instant},
 };
@@ -18,12 +19,13 @@ use gv_core::{
     ecs::{components::NetConnectionModel, system_data::time::GameTimeService},
     net::{
         client_message::{ClientMessage, ClientMessagePayload},
-        server_message::{ServerMessage, ServerMessagePayload},
-        ConnectionNetEvent, EncodedMessage, NetEvent, NetIdentifier,
+        fragmentation::{whole_message, FragmentationError, MessageReassembler},
+        server_message::{DisconnectReason, ServerMessage, ServerMessagePayload},
+        ConnectionNetEvent, EncodedMessage, NetEvent, NetIdentifier, PROTOCOL_VERSION,
     },
 };
 
-use crate::ecs::resources::ConnectionEvents;
+use crate::{ecs::resources::ConnectionEvents, utils::net::simulation::NetworkSimulator};
 
 const PING_INTERVAL_MILLIS: u64 = 500;
 
@@ -62,6 +64,7 @@ pub struct NetConnectionManagerSystem {
     connection_id_autoinc: NetIdentifier,
     ping_id_autoinc: NetIdentifier,
     reader: ReaderId<NetworkSimulationEvent>,
+    message_reassemblers: HashMap<SocketAddr, MessageReassembler>,
 }
 
 impl NetConnectionManagerSystem {
@@ -70,6 +73,7 @@ impl NetConnectionManagerSystem {
             connection_id_autoinc: Default::default(),
             ping_id_autoinc: Default::default(),
             reader,
+            message_reassemblers: HashMap::new(),
         }
     }
 
@@ -92,6 +96,7 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
         Write<'s, TransportResource>,
         Read<'s, EventChannel<NetworkSimulationEvent>>,
         WriteExpect<'s, ConnectionEvents>,
+        WriteExpect<'s, NetworkSimulator>,
         WriteStorage<'s, NetConnectionModel>,
         Entities<'s>,
     );
@@ -103,6 +108,7 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
             mut transport,
             net_events,
             mut connection_events,
+            mut network_simulator,
             mut net_connection_models,
             entities,
         ): Self::SystemData,
@@ -115,27 +121,40 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
             self.connection_id_autoinc = net_connection_models.count() as u64;
         }
 
+        let now = Instant::now();
         for net_event in net_events.read(&mut self.reader) {
-            let (event, response) = self.process_connection_event(
-                &net_event,
+            match net_event {
+                NetworkSimulationEvent::Message(addr, payload)
+                    if network_simulator.conditions.is_enabled() =>
+                {
+                    network_simulator.receive(*addr, payload.clone(), now);
+                }
+                _ => {
+                    // Otherwise delayed datagrams would create a new connection after this one is
+                    // deleted.
+                    if let NetworkSimulationEvent::Disconnect(addr) = net_event {
+                        network_simulator.drop_incoming(*addr);
+                    }
+                    self.handle_connection_event(
+                        net_event,
+                        &entities,
+                        &mut net_connection_models,
+                        &game_time_service,
+                        &mut connection_events,
+                        &mut transport,
+                    );
+                }
+            }
+        }
+        for (addr, payload) in network_simulator.release_incoming(now) {
+            self.handle_connection_event(
+                &NetworkSimulationEvent::Message(addr, payload),
                 &entities,
                 &mut net_connection_models,
                 &game_time_service,
+                &mut connection_events,
+                &mut transport,
             );
-
-            if let Some(event) = event {
-                connection_events.0.push(event);
-            }
-            if let Some(response) = response {
-                let addr = event_peer_addr(&net_event)
-                    .expect("Expected to respond to an event with SocketAddr");
-                transport.send_with_requirements(
-                    addr,
-                    &response,
-                    DeliveryRequirement::Unreliable,
-                    UrgencyRequirement::Immediate,
-                );
-            }
         }
 
         for connection_model in (&mut net_connection_models).join() {
@@ -162,11 +181,13 @@ impl<'s> System<'s> for NetConnectionManagerSystem {
 }
 
 fn ping_message(session_id: NetIdentifier, ping_id: NetIdentifier) -> EncodedMessage {
-    bincode::serialize(&OutcomingMessage {
-        session_id,
-        payload: OutcomingMessagePayload::Ping(ping_id),
-    })
-    .expect("Expected to serialize Ping message")
+    whole_message(
+        &bincode::serialize(&OutcomingMessage {
+            session_id,
+            payload: OutcomingMessagePayload::Ping(ping_id),
+        })
+        .expect("Expected to serialize Ping message"),
+    )
     .into()
 }
 
@@ -175,18 +196,51 @@ fn pong_message(
     ping_id: NetIdentifier,
     frame_number: u64,
 ) -> EncodedMessage {
-    bincode::serialize(&OutcomingMessage {
-        session_id,
-        payload: OutcomingMessagePayload::Pong {
-            ping_id,
-            frame_number,
-        },
-    })
-    .expect("Expected to serialize Pong message")
+    whole_message(
+        &bincode::serialize(&OutcomingMessage {
+            session_id,
+            payload: OutcomingMessagePayload::Pong {
+                ping_id,
+                frame_number,
+            },
+        })
+        .expect("Expected to serialize Pong message"),
+    )
     .into()
 }
 
 impl NetConnectionManagerSystem {
+    fn handle_connection_event(
+        &mut self,
+        net_event: &NetworkSimulationEvent,
+        entities: &Entities,
+        net_connection_models: &mut WriteStorage<NetConnectionModel>,
+        game_time_service: &GameTimeService,
+        connection_events: &mut ConnectionEvents,
+        transport: &mut TransportResource,
+    ) {
+        let (event, response) = self.process_connection_event(
+            net_event,
+            entities,
+            net_connection_models,
+            game_time_service,
+        );
+
+        if let Some(event) = event {
+            connection_events.0.push(event);
+        }
+        if let Some(response) = response {
+            let addr = event_peer_addr(net_event)
+                .expect("Expected to respond to an event with SocketAddr");
+            transport.send_with_requirements(
+                addr,
+                &response,
+                DeliveryRequirement::Unreliable,
+                UrgencyRequirement::Immediate,
+            );
+        }
+    }
+
     fn process_connection_event(
         &mut self,
         event: &NetworkSimulationEvent,
@@ -244,6 +298,7 @@ impl NetConnectionManagerSystem {
                 entities
                     .delete(connection_model_entity)
                     .expect("Expected to delete a NetConnectionModel");
+                self.message_reassemblers.remove(&peer_addr);
                 (
                     Some(ConnectionNetEvent {
                         connection_id,
@@ -253,12 +308,40 @@ impl NetConnectionManagerSystem {
                 )
             }
             NetworkSimulationEvent::Message(_, bytes) => {
-                if let Ok(IncomingMessage {
-                    session_id,
-                    payload,
-                }) = bincode::deserialize::<IncomingMessage>(bytes.as_ref())
+                let message = match self
+                    .message_reassemblers
+                    .entry(peer_addr)
+                    .or_default()
+                    .receive(bytes.as_ref())
                 {
-                    match payload {
+                    Ok(Some(message)) => message,
+                    Ok(None) => return (None, None),
+                    Err(FragmentationError::IncompatibleVersion { protocol_version }) => {
+                        log::warn!(
+                            "Received a datagram of protocol version {} from {}, expected {}",
+                            protocol_version,
+                            peer_addr,
+                            PROTOCOL_VERSION
+                        );
+                        return incompatible_version(connection_model, protocol_version);
+                    }
+                    Err(FragmentationError::MissingVersionHeader) => {
+                        log::warn!(
+                            "Received a datagram without a version header from {}",
+                            peer_addr
+                        );
+                        return (None, reject_baseline_client(bytes.as_ref()));
+                    }
+                    Err(err) => {
+                        log::warn!("Dropping a datagram from {}: {}", peer_addr, err);
+                        return (None, None);
+                    }
+                };
+                match bincode::deserialize::<IncomingMessage>(&message) {
+                    Ok(IncomingMessage {
+                        session_id,
+                        payload,
+                    }) => match payload {
                         IncomingMessagePayload::Ping(ping_id) => {
                             log::trace!("Received a new ping message: {:?}", &payload);
                             if connection_model.disconnected {
@@ -310,9 +393,15 @@ impl NetConnectionManagerSystem {
                                 None,
                             )
                         }
+                    },
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to decode a message from {} (incompatible build?): {:?}",
+                            peer_addr,
+                            err
+                        );
+                        (None, None)
                     }
-                } else {
-                    (None, None)
                 }
             }
             NetworkSimulationEvent::SendError(err, _) => {
@@ -332,6 +421,98 @@ impl NetConnectionManagerSystem {
     }
 }
 
+/// Tells a client of another protocol version to update.
+#[cfg(not(feature = "client"))]
+fn incompatible_version(
+    connection_model: &NetConnectionModel,
+    _protocol_version: u32,
+) -> (
+    Option<ConnectionNetEvent<IncomingMessage>>,
+    Option<EncodedMessage>,
+) {
+    let message = incompatible_version_message(connection_model.session_id);
+    (None, Some(whole_message(&message).into()))
+}
+
+/// Messages of a server of another protocol version can't be decoded, so we read its version
+/// from the header and disconnect as if the server has rejected us.
+#[cfg(feature = "client")]
+fn incompatible_version(
+    connection_model: &NetConnectionModel,
+    protocol_version: u32,
+) -> (
+    Option<ConnectionNetEvent<IncomingMessage>>,
+    Option<EncodedMessage>,
+) {
+    (
+        Some(ConnectionNetEvent {
+            connection_id: connection_model.id,
+            event: NetEvent::Message(ServerMessage {
+                session_id: connection_model.session_id,
+                payload: ServerMessagePayload::Disconnect(DisconnectReason::IncompatibleVersion {
+                    server_protocol_version: protocol_version,
+                }),
+            }),
+        }),
+        None,
+    )
+}
+
+/// Clients built before the version header was introduced send their JoinRoom messages
+/// without a protocol version, so they are answered with a Disconnect message in their own
+/// format. None of their disconnect reasons asks to update, `Closed` is the closest one.
+#[cfg(not(feature = "client"))]
+fn reject_baseline_client(datagram: &[u8]) -> Option<EncodedMessage> {
+    // Mirror the layout of ClientMessage and ServerMessage of these clients.
+    const JOIN_ROOM_VARIANT: u32 = 1;
+    const DISCONNECT_VARIANT: u32 = 11;
+    const CLOSED_REASON_VARIANT: u32 = 4;
+
+    #[derive(serde_derive::Deserialize)]
+    struct BaselineJoinRoom {
+        session_id: NetIdentifier,
+        payload_variant: u32,
+        _sent_at: Duration,
+        _nickname: String,
+    }
+
+    #[derive(serde_derive::Serialize)]
+    struct BaselineDisconnect {
+        session_id: NetIdentifier,
+        payload_variant: u32,
+        reason_variant: u32,
+    }
+
+    let join_room = bincode::deserialize::<BaselineJoinRoom>(datagram).ok()?;
+    if join_room.payload_variant != JOIN_ROOM_VARIANT {
+        return None;
+    }
+    log::warn!("Rejecting a client built without the protocol version header");
+    let message = bincode::serialize(&BaselineDisconnect {
+        session_id: join_room.session_id,
+        payload_variant: DISCONNECT_VARIANT,
+        reason_variant: CLOSED_REASON_VARIANT,
+    })
+    .expect("Expected to serialize Disconnect message");
+    Some(message.into())
+}
+
+#[cfg(feature = "client")]
+fn reject_baseline_client(_datagram: &[u8]) -> Option<EncodedMessage> {
+    None
+}
+
+#[cfg(not(feature = "client"))]
+fn incompatible_version_message(session_id: NetIdentifier) -> Vec<u8> {
+    bincode::serialize(&ServerMessage {
+        session_id,
+        payload: ServerMessagePayload::Disconnect(DisconnectReason::IncompatibleVersion {
+            server_protocol_version: PROTOCOL_VERSION,
+        }),
+    })
+    .expect("Expected to serialize Disconnect message")
+}
+
 fn event_peer_addr(event: &NetworkSimulationEvent) -> Option<SocketAddr> {
     match event {
         NetworkSimulationEvent::Connect(addr)
@@ -341,3 +522,34 @@ fn event_peer_addr(event: &NetworkSimulationEvent) -> Option<SocketAddr> {
         _ => None,
     }
 }
+
+#[cfg(all(test, not(feature = "client")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_baseline_clients_in_their_format() {
+        #[derive(serde_derive::Serialize)]
+        struct BaselineMessage {
+            session_id: NetIdentifier,
+            payload_variant: u32,
+            sent_at: Duration,
+            nickname: String,
+        }
+
+        let mut join_room = BaselineMessage {
+            session_id: 42,
+            payload_variant: 1,
+            sent_at: Duration::from_secs(1),
+            nickname: "player".to_owned(),
+        };
+        let reply = reject_baseline_client(&bincode::serialize(&join_room).unwrap())
+            .expect("Expected a reply to JoinRoom");
+        let reply: (NetIdentifier, u32, u32) = bincode::deserialize(&reply).unwrap();
+        assert_eq!(reply, (42, 11, 4));
+
+        join_room.payload_variant = 0;
+        assert!(reject_baseline_client(&bincode::serialize(&join_room).unwrap()).is_none());
+        assert!(reject_baseline_client(&[1, 2, 3]).is_none());
+    }
+}
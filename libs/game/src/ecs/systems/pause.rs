@@ -2,16 +2,28 @@ use amethyst::ecs::{ReadExpect, System, WriteExpect};
 
 use gv_core::ecs::resources::{net::MultiplayerGameState, GameTime};
 
+use crate::ecs::resources::ReplayPlayback;
+
 pub struct PauseSystem;
 
 impl<'s> System<'s> for PauseSystem {
     type SystemData = (
         ReadExpect<'s, MultiplayerGameState>,
+        WriteExpect<'s, ReplayPlayback>,
         WriteExpect<'s, GameTime>,
     );
 
-    fn run(&mut self, (multiplayer_game_state, mut game_time): Self::SystemData) {
-        if multiplayer_game_state.waiting_network {
+    fn run(
+        &mut self,
+        (multiplayer_game_state, mut replay_playback, mut game_time): Self::SystemData,
+    ) {
+        if replay_playback.update_skips_frame() {
+            game_time.frames_skipped += 1;
+            log::trace!(
+                "Skipping a frame, reason: replay is paused (skipped: {})",
+                game_time.frames_skipped
+            );
+        } else if multiplayer_game_state.waiting_network {
             game_time.frames_skipped += 1;
             log::info!(
                 "Skipping a frame, reason: waiting for network (skipped: {})",
@@ -4,23 +4,24 @@ use amethyst::{
 };
 use rand::seq::SliceRandom;
 
-use std::time::Duration;
-
 use gv_animation_prefabs::{AnimationId, MAGE_TORSO};
 #[cfg(not(feature = "client"))]
 use gv_core::net::NetUpdateWithPosition;
 use gv_core::{
     actions::{
-        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction},
+        player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction, SpellId},
         ClientActionUpdate, IdentifiableAction,
     },
     ecs::{
         components::{
-            missile::Missile, ClientPlayerActions, Player, PlayerActions, PlayerLastCastedSpells,
-            WorldPosition,
+            area_spell::AreaSpell, missile::Missile, ClientPlayerActions, Player, PlayerActions,
+            PlayerLastCastedSpells, WorldPosition,
         },
         resources::{
-            net::{ActionUpdateIdProvider, CastActionsToExecute, MultiplayerGameState},
+            net::{
+                ActionUpdateIdProvider, CastActionToExecute, CastActionsToExecute,
+                MultiplayerGameState,
+            },
             GameLevelState,
         },
         system_data::time::GameTimeService,
@@ -28,9 +29,11 @@ use gv_core::{
     math::{Vector2, ZeroVector},
     net::{NetIdentifier, NetUpdate},
     profile_scope,
+    progression::MANA_REGENERATION_PER_SEC,
 };
 
 use crate::ecs::{
+    resources::SpellDefinitions,
     system_data::GameStateHelper,
     systems::{
         AnimationsResourceBundle, ClientFrameUpdate, OutcomingNetUpdates, WriteExpectCell,
@@ -38,20 +41,20 @@ use crate::ecs::{
     },
 };
 
-const MISSILE_CAST_COOLDOWN: Duration = Duration::from_millis(500);
-
 pub struct PlayerActionSubsystem<'a, 's> {
     pub game_time_service: &'s GameTimeService<'s>,
     pub game_state_helper: &'s GameStateHelper<'s>,
     pub entities: &'s Entities<'s>,
     pub game_level_state: &'s ReadExpect<'s, GameLevelState>,
     pub multiplayer_game_state: &'s ReadExpect<'s, MultiplayerGameState>,
+    pub spell_definitions: &'s ReadExpect<'s, SpellDefinitions>,
     pub client_player_actions: &'s ReadStorage<'s, ClientPlayerActions>,
     pub action_update_id_provider: WriteExpectCell<'s, ActionUpdateIdProvider>,
     pub cast_actions_to_execute: WriteExpectCell<'s, CastActionsToExecute>,
     pub player_actions: WriteStorageCell<'s, PlayerActions>,
     pub player_last_casted_spells: WriteStorageCell<'s, PlayerLastCastedSpells>,
     pub missiles: WriteStorageCell<'s, Missile>,
+    pub area_spells: WriteStorageCell<'s, AreaSpell>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
     #[cfg_attr(not(feature = "client"), allow(dead_code))]
     pub animations_resource_bundle: &'a AnimationsResourceBundle<'s>,
@@ -78,8 +81,6 @@ pub struct ApplyCastActionNetArgs<'a> {
     pub update: Option<IdentifiableAction<ClientActionUpdate<PlayerCastAction>>>,
 }
 
-const PLAYER_SPEED: f32 = 200.0;
-
 impl<'a, 's> PlayerActionSubsystem<'a, 's> {
     pub fn apply_walk_action<'n>(
         &self,
@@ -159,7 +160,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let PlayerWalkAction::Walk { direction } = &player_actions.walk_action {
             player.walking_direction = *direction;
             player.velocity = if *direction != Vector2::zero() {
-                direction.normalize() * PLAYER_SPEED
+                direction.normalize() * player.stats.move_speed()
             } else {
                 Vector2::zero()
             };
@@ -245,6 +246,7 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         &self,
         frame_number: u64,
         entity: Entity,
+        player: &mut Player,
         mut net_args: Option<ApplyCastActionNetArgs<'n>>,
         _client_side_actions: &mut ClientFrameUpdate,
     ) {
@@ -270,11 +272,10 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         let client_player_actions = self.client_player_actions.get(entity);
 
         let is_latest_frame = self.game_time_service.game_frame_number() == frame_number;
-        let is_cooling_down = self
-            .game_time_service
-            .seconds_between_frames(frame_number, player_last_casted_spells.missile)
-            < MISSILE_CAST_COOLDOWN.as_secs_f32();
 
+        let regenerated_mana =
+            MANA_REGENERATION_PER_SEC * self.game_time_service.engine_time().fixed_seconds();
+        player.mana = (player.mana + regenerated_mana).min(player.stats.max_mana());
         player_actions.cast_action = None;
 
         if self.multiplayer_game_state.is_playing {
@@ -291,7 +292,13 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                 action: mut cast_action,
             }) = cast_action_update.clone()
             {
-                if !is_cooling_down || !self.game_state_helper.is_authoritative() {
+                let can_cast = self.can_cast(
+                    frame_number,
+                    player,
+                    player_last_casted_spells,
+                    cast_action.action.spell,
+                );
+                if can_cast || !self.game_state_helper.is_authoritative() {
                     log::trace!(
                         "Applying a new cast update ({}) for {} (frame {}): {:?}",
                         action_id,
@@ -301,9 +308,14 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                     );
                 }
 
-                if self.game_state_helper.is_authoritative() && !is_cooling_down {
+                if self.game_state_helper.is_authoritative() && can_cast {
                     // Update player actions.
-                    player_last_casted_spells.missile = frame_number;
+                    self.spend_cast(
+                        frame_number,
+                        player,
+                        player_last_casted_spells,
+                        cast_action.action.spell,
+                    );
                     cast_action.action.cast_position = *player_position;
                     player_actions.cast_action = Some(cast_action.action.clone());
 
@@ -315,7 +327,12 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                         cast_action,
                     );
                 } else if !self.game_state_helper.is_authoritative() {
-                    player_last_casted_spells.missile = frame_number;
+                    self.spend_cast(
+                        frame_number,
+                        player,
+                        player_last_casted_spells,
+                        cast_action.action.spell,
+                    );
                     player_actions.cast_action = Some(cast_action.action);
                 }
 
@@ -325,10 +342,29 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                             .get_mut(missile)
                             .expect("Expected a WorldPosition for a Missile");
                         **missile_position = cast_action.cast_position;
+                    } else if let Some(area_spell) = self.already_casted_area_spell(action_id) {
+                        let area_spell_position = world_positions
+                            .get_mut(area_spell)
+                            .expect("Expected a WorldPosition for an AreaSpell");
+                        **area_spell_position = cast_action.cast_position;
+                        // The frame is re-simulated, monsters have to be damaged again.
+                        self.area_spells
+                            .borrow_mut()
+                            .get_mut(area_spell)
+                            .expect("Expected an AreaSpell")
+                            .hit_monsters
+                            .clear();
                     } else {
-                        cast_actions_to_execute.actions.push(IdentifiableAction {
-                            action_id,
-                            action: cast_action.clone(),
+                        cast_actions_to_execute.actions.push(CastActionToExecute {
+                            action: IdentifiableAction {
+                                action_id,
+                                action: cast_action.clone(),
+                            },
+                            damage: self
+                                .spell_definitions
+                                .get(cast_action.spell)
+                                .damage(&player.stats),
+                            caster: Some(*entity_net_id),
                         });
 
                         let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
@@ -349,7 +385,13 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
         if let Some(client_player_actions) = client_player_actions.cloned() {
             if is_latest_frame {
                 if let Some(mut cast_action) = client_player_actions.cast_action {
-                    if !is_cooling_down {
+                    let can_cast = self.can_cast(
+                        frame_number,
+                        player,
+                        player_last_casted_spells,
+                        cast_action.spell,
+                    );
+                    if can_cast {
                         if self.multiplayer_game_state.is_playing {
                             let ApplyCastActionNetArgs {
                                 entity_net_id,
@@ -383,9 +425,16 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                                 frame_number,
                                 &cast_action
                             );
-                            cast_actions_to_execute.actions.push(IdentifiableAction {
-                                action_id: 0,
-                                action: cast_action.clone(),
+                            cast_actions_to_execute.actions.push(CastActionToExecute {
+                                action: IdentifiableAction {
+                                    action_id: 0,
+                                    action: cast_action.clone(),
+                                },
+                                damage: self
+                                    .spell_definitions
+                                    .get(cast_action.spell)
+                                    .damage(&player.stats),
+                                caster: None,
                             });
 
                             let animation_id = [AnimationId::Spell1, AnimationId::Spell2]
@@ -402,12 +451,43 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
                     }
                 }
             }
-            if player_actions.cast_action.is_some() {
-                player_last_casted_spells.missile = frame_number;
+            if let Some(cast_action) = &player_actions.cast_action {
+                self.spend_cast(
+                    frame_number,
+                    player,
+                    player_last_casted_spells,
+                    cast_action.spell,
+                );
             }
         }
     }
 
+    fn can_cast(
+        &self,
+        frame_number: u64,
+        player: &Player,
+        player_last_casted_spells: &PlayerLastCastedSpells,
+        spell: SpellId,
+    ) -> bool {
+        let spell_definition = self.spell_definitions.get(spell);
+        let is_cooling_down = self
+            .game_time_service
+            .seconds_between_frames(frame_number, player_last_casted_spells.frame(spell))
+            < spell_definition.cooldown_secs(&player.stats);
+        !is_cooling_down && player.mana >= spell_definition.mana_cost
+    }
+
+    fn spend_cast(
+        &self,
+        frame_number: u64,
+        player: &mut Player,
+        player_last_casted_spells: &mut PlayerLastCastedSpells,
+        spell: SpellId,
+    ) {
+        player_last_casted_spells.set_frame(spell, frame_number);
+        player.mana = (player.mana - self.spell_definitions.get(spell).mana_cost).max(0.0);
+    }
+
     #[cfg(feature = "client")]
     fn actual_walk_action_update(
         &self,
@@ -636,4 +716,12 @@ impl<'a, 's> PlayerActionSubsystem<'a, 's> {
             .find(|(missile, _)| missile.action_id == cast_action_id)
             .map(|(_, entity)| entity)
     }
+
+    fn already_casted_area_spell(&self, cast_action_id: NetIdentifier) -> Option<Entity> {
+        let area_spells = self.area_spells.borrow();
+        (&*area_spells, self.entities)
+            .join()
+            .find(|(area_spell, _)| area_spell.action_id == cast_action_id)
+            .map(|(_, entity)| entity)
+    }
 }
@@ -0,0 +1,115 @@
+use amethyst::ecs::{System, WriteExpect, WriteStorage};
+
+use gv_core::{
+    actions::monster_spawn::SpawnActions,
+    ecs::{
+        components::{ClientPlayerActions, PlayerActions},
+        resources::world::FramedUpdates,
+        system_data::time::GameTimeService,
+    },
+};
+
+use crate::ecs::{
+    resources::{ReplayPlayback, ReplayRecorder},
+    system_data::GameStateHelper,
+};
+
+/// Records player input and spawn actions of the current frame to ReplayRecorder,
+/// or overwrites them with the recorded ones if ReplayPlayback is active.
+pub struct ReplaySystem;
+
+impl<'s> System<'s> for ReplaySystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        WriteExpect<'s, ReplayRecorder>,
+        WriteExpect<'s, ReplayPlayback>,
+        WriteExpect<'s, FramedUpdates<SpawnActions>>,
+        WriteStorage<'s, ClientPlayerActions>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            mut replay_recorder,
+            mut replay_playback,
+            mut spawn_actions,
+            mut client_player_actions,
+        ): Self::SystemData,
+    ) {
+        if !game_state_helper.is_running() {
+            return;
+        }
+        let frame_number = game_time_service.game_frame_number();
+
+        if replay_playback.is_active() {
+            let frame = replay_playback.frame(frame_number).cloned();
+            let frame = if let Some(frame) = frame {
+                frame
+            } else {
+                if !replay_playback.paused {
+                    log::info!("The replay has ended (frame {})", frame_number);
+                    replay_playback.paused = true;
+                }
+                for player in replay_playback.players() {
+                    if let Some(client_player_actions) = client_player_actions.get_mut(*player) {
+                        *client_player_actions = ClientPlayerActions::default();
+                    }
+                }
+                return;
+            };
+
+            for (player, actions) in replay_playback.players().iter().zip(frame.player_actions) {
+                let PlayerActions {
+                    walk_action,
+                    look_action,
+                    cast_action,
+                } = actions;
+                let client_player_actions = client_player_actions
+                    .get_mut(*player)
+                    .expect("Expected a ClientPlayerActions component for a replayed player");
+                client_player_actions.walk_action = walk_action;
+                client_player_actions.look_action = look_action;
+                if let Some(cast_action) = &cast_action {
+                    client_player_actions.selected_spell = cast_action.spell;
+                }
+                client_player_actions.cast_action = cast_action;
+            }
+
+            spawn_actions.reserve_updates(frame_number);
+            spawn_actions
+                .update_frame(frame_number)
+                .unwrap_or_else(|| panic!("Expected SpawnActions for frame {}", frame_number))
+                .spawn_actions = frame.spawn_actions;
+        } else if replay_recorder.is_recording() {
+            let player_actions = replay_recorder
+                .players()
+                .iter()
+                .map(|player| {
+                    client_player_actions
+                        .get(*player)
+                        .map(|actions| PlayerActions {
+                            walk_action: actions.walk_action.clone(),
+                            look_action: actions.look_action.clone(),
+                            cast_action: actions.cast_action.clone(),
+                        })
+                        .unwrap_or_default()
+                })
+                .collect();
+            let recorded_spawn_actions = spawn_actions
+                .updates
+                .iter()
+                .find(|update| update.frame_number == frame_number)
+                .map(|update| update.spawn_actions.clone())
+                .unwrap_or_default();
+
+            let frame = replay_recorder
+                .frame_mut(frame_number)
+                .expect("Expected a started replay recording");
+            frame.player_actions = player_actions;
+            frame.spawn_actions = recorded_spawn_actions;
+        }
+    }
+}
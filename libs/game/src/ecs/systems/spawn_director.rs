@@ -0,0 +1,181 @@
+use amethyst::ecs::{ReadExpect, System, WriteExpect};
+use rand::Rng;
+
+use std::time::Duration;
+
+use gv_core::{
+    actions::monster_spawn::{SpawnAction, SpawnActions, SpawnType},
+    ecs::{
+        resources::{net::EntityNetMetadataStorage, world::FramedUpdates, GameLevelState},
+        system_data::time::GameTimeService,
+    },
+    math::Vector2,
+};
+
+use crate::{
+    ecs::{
+        resources::{MonsterDefinitions, Wave, WaveScript, WaveSpawn},
+        system_data::GameStateHelper,
+    },
+    utils::world::{random_spawn_position, spawning_side},
+};
+
+const BORDERLINE_SPAWN_MARGIN: f32 = 50.0;
+
+/// Interprets WaveScript, emitting SpawnActions for the current frame.
+#[derive(Default)]
+pub struct SpawnDirectorSystem;
+
+impl<'s> System<'s> for SpawnDirectorSystem {
+    type SystemData = (
+        GameStateHelper<'s>,
+        GameTimeService<'s>,
+        ReadExpect<'s, MonsterDefinitions>,
+        ReadExpect<'s, WaveScript>,
+        WriteExpect<'s, GameLevelState>,
+        WriteExpect<'s, FramedUpdates<SpawnActions>>,
+        WriteExpect<'s, EntityNetMetadataStorage>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            game_state_helper,
+            game_time_service,
+            monster_definitions,
+            wave_script,
+            mut game_level_state,
+            mut spawn_actions,
+            mut entity_net_metadata_storage,
+        ): Self::SystemData,
+    ) {
+        // Replays have the spawn actions recorded.
+        if !game_state_helper.is_running()
+            || !game_state_helper.is_authoritative()
+            || game_state_helper.is_replaying()
+        {
+            return;
+        }
+        spawn_actions.reserve_updates(game_time_service.game_frame_number());
+        let spawn_actions = spawn_actions
+            .update_frame(game_time_service.game_frame_number())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Expected SpawnActions for frame {}",
+                    game_time_service.game_frame_number()
+                )
+            });
+
+        let now = game_time_service.level_duration();
+
+        let escalation_interval = Duration::from_secs_f32(wave_script.escalation_interval_secs);
+        if now - game_level_state.spawn_level_started > escalation_interval {
+            game_level_state.spawn_level += 1;
+            game_level_state.spawn_level_started = now;
+        }
+
+        game_level_state
+            .waves_last_spawned_at
+            .resize(wave_script.waves.len(), None);
+        for (i, wave) in wave_script.waves.iter().enumerate() {
+            let last_spawned_at = game_level_state.waves_last_spawned_at[i];
+            if !wave_is_due(wave, now, last_spawned_at, game_level_state.spawn_level) {
+                continue;
+            }
+            game_level_state.waves_last_spawned_at[i] = Some(now);
+
+            let monster_type = wave.monster_type.as_ref().map_or_else(
+                || monster_definitions.random_monster_type(&mut game_level_state.spawn_rng),
+                |monster_type| {
+                    monster_definitions
+                        .type_id(monster_type)
+                        .expect("Expected wave monster types to be validated")
+                },
+            );
+            match &wave.spawn {
+                WaveSpawn::Single { position, count } => {
+                    let monsters_to_spawn = count_value(count.value(game_level_state.spawn_level));
+                    log::trace!(
+                        "Spawning {} monster(s) (SpawnType::Single)",
+                        monsters_to_spawn
+                    );
+                    for _ in 0..monsters_to_spawn {
+                        let position = position.map_or_else(
+                            || random_spawn_position(&mut game_level_state),
+                            |(x, y)| Vector2::new(x, y),
+                        );
+                        spawn_actions.spawn_actions.push(SpawnAction {
+                            monster_type,
+                            spawn_type: SpawnType::Single {
+                                entity_net_id: Some(
+                                    entity_net_metadata_storage.reserve_ids(1).start,
+                                ),
+                                position,
+                            },
+                        });
+                    }
+                }
+                WaveSpawn::Borderline { side, count } => {
+                    let side = side.unwrap_or_else(|| game_level_state.spawn_rng.gen());
+                    let monsters_to_spawn = if let Some(count) = count {
+                        count_value(count.value(game_level_state.spawn_level))
+                    } else {
+                        let (side_start, side_end, _) = spawning_side(side, &game_level_state);
+                        let d = (side_start - side_end) / BORDERLINE_SPAWN_MARGIN;
+                        count_value(num::Float::max(d.x.abs(), d.y.abs()))
+                    };
+                    if monsters_to_spawn == 0 {
+                        continue;
+                    }
+
+                    let entity_net_id_range = if game_state_helper.is_multiplayer() {
+                        Some(entity_net_metadata_storage.reserve_ids(monsters_to_spawn as usize))
+                    } else {
+                        None
+                    };
+
+                    log::trace!(
+                        "Spawning {} monster(s) (SpawnType::Borderline)",
+                        monsters_to_spawn
+                    );
+                    spawn_actions.spawn_actions.push(SpawnAction {
+                        monster_type,
+                        spawn_type: SpawnType::Borderline {
+                            count: monsters_to_spawn,
+                            entity_net_id_range,
+                            side,
+                        },
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn wave_is_due(
+    wave: &Wave,
+    now: Duration,
+    last_spawned_at: Option<Duration>,
+    spawn_level: usize,
+) -> bool {
+    let start = Duration::from_secs_f32(wave.start_secs);
+    if now < start {
+        return false;
+    }
+    if let Some(end_secs) = wave.end_secs {
+        if now > Duration::from_secs_f32(end_secs) {
+            return false;
+        }
+    }
+
+    if let Some(interval_secs) = &wave.interval_secs {
+        let interval = Duration::from_secs_f32(interval_secs.value(spawn_level).max(0.0));
+        now - last_spawned_at.unwrap_or(start) > interval
+    } else {
+        last_spawned_at.is_none()
+    }
+}
+
+fn count_value(value: f32) -> u8 {
+    value.round().max(0.0).min(255.0) as u8
+}
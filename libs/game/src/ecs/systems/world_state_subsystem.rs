@@ -2,13 +2,13 @@ use amethyst::ecs::Entities;
 
 use gv_core::ecs::{
     components::{
-        missile::Missile, Dead, Monster, Player, PlayerActions, PlayerLastCastedSpells,
-        WorldPosition,
+        area_spell::AreaSpell, enemy_projectile::EnemyProjectile, missile::Missile, Dead, Monster,
+        Player, PlayerActions, PlayerLastCastedSpells, WorldPosition,
     },
-    resources::world::SavedWorldState,
+    resources::{world::SavedWorldState, GameRngs},
 };
 
-use crate::ecs::systems::WriteStorageCell;
+use crate::ecs::systems::{WriteExpectCell, WriteStorageCell};
 
 pub struct WorldStateSubsystem<'s> {
     pub entities: &'s Entities<'s>,
@@ -17,8 +17,11 @@ pub struct WorldStateSubsystem<'s> {
     pub player_last_casted_spells: WriteStorageCell<'s, PlayerLastCastedSpells>,
     pub monsters: WriteStorageCell<'s, Monster>,
     pub missiles: WriteStorageCell<'s, Missile>,
+    pub area_spells: WriteStorageCell<'s, AreaSpell>,
+    pub enemy_projectiles: WriteStorageCell<'s, EnemyProjectile>,
     pub world_positions: WriteStorageCell<'s, WorldPosition>,
     pub dead: WriteStorageCell<'s, Dead>,
+    pub game_rngs: WriteExpectCell<'s, GameRngs>,
 }
 
 impl<'s> WorldStateSubsystem<'s> {
@@ -37,12 +40,21 @@ impl<'s> WorldStateSubsystem<'s> {
             SavedWorldState::copy_from_write_storage(&self.entities, &*self.monsters.borrow_mut());
         saved_world_state.missiles =
             SavedWorldState::copy_from_write_storage(&self.entities, &*self.missiles.borrow_mut());
+        saved_world_state.area_spells = SavedWorldState::copy_from_write_storage(
+            &self.entities,
+            &*self.area_spells.borrow_mut(),
+        );
+        saved_world_state.enemy_projectiles = SavedWorldState::copy_from_write_storage(
+            &self.entities,
+            &*self.enemy_projectiles.borrow_mut(),
+        );
         saved_world_state.world_positions = SavedWorldState::copy_from_write_storage(
             &self.entities,
             &*self.world_positions.borrow_mut(),
         );
         saved_world_state.dead =
             SavedWorldState::copy_from_write_storage(&self.entities, &*self.dead.borrow_mut());
+        saved_world_state.rngs = Some((**self.game_rngs.borrow()).clone());
     }
 
     pub fn load_from_world_state(&self, saved_world_state: &SavedWorldState) {
@@ -66,10 +78,21 @@ impl<'s> WorldStateSubsystem<'s> {
             &mut self.missiles.borrow_mut(),
             &saved_world_state.missiles,
         );
+        SavedWorldState::load_storage_from(
+            &mut self.area_spells.borrow_mut(),
+            &saved_world_state.area_spells,
+        );
+        SavedWorldState::load_storage_from(
+            &mut self.enemy_projectiles.borrow_mut(),
+            &saved_world_state.enemy_projectiles,
+        );
         SavedWorldState::load_storage_from(
             &mut self.world_positions.borrow_mut(),
             &saved_world_state.world_positions,
         );
         SavedWorldState::load_storage_from(&mut self.dead.borrow_mut(), &saved_world_state.dead);
+        if let Some(rngs) = &saved_world_state.rngs {
+            **self.game_rngs.borrow_mut() = rngs.clone();
+        }
     }
 }
@@ -16,15 +16,18 @@ use gv_core::{
     ecs::resources::{
         net::{
             ActionUpdateIdProvider, CastActionsToExecute, EntityNetMetadataStorage,
-            MultiplayerGameState,
+            MultiplayerGameState, RangedAttacksToExecute,
         },
         world::{FramedUpdates, PlayerActionUpdates, WorldStates},
     },
 };
 
 use crate::ecs::{
-    resources::ConnectionEvents,
-    systems::{missile::MissileDyingSystem, monster::*, *},
+    resources::{ConnectionEvents, ReplayPlayback, ReplayRecorder},
+    systems::{
+        area_spell::AreaSpellDyingSystem, enemy_projectile::EnemyProjectileDyingSystem,
+        missile::MissileDyingSystem, monster::*, *,
+    },
 };
 
 pub fn build_game_logic_systems<'a, 'b>(
@@ -35,18 +38,31 @@ pub fn build_game_logic_systems<'a, 'b>(
     world.insert(ConnectionEvents(Vec::new()));
     world.insert(MultiplayerGameState::new());
     world.insert(ActionUpdateIdProvider::default());
+    // Client replaces these after building the systems if replays are enabled.
+    world.insert(ReplayRecorder::default());
+    world.insert(ReplayPlayback::default());
 
     // The resources which we need to remember to reset on starting a game.
     world.insert(FramedUpdates::<PlayerActionUpdates>::default());
     world.insert(FramedUpdates::<SpawnActions>::default());
     world.insert(WorldStates::default());
     world.insert(CastActionsToExecute::default());
+    world.insert(RangedAttacksToExecute::default());
     world.insert(EntityNetMetadataStorage::new());
 
     let game_data_builder = game_data_builder
         .with(PauseSystem, "pause_system", &["game_network_system"])
-        .with(LevelSystem::default(), "level_system", &["pause_system"])
-        .with(MonsterSpawnerSystem, "spawner_system", &["level_system"])
+        .with(
+            SpawnDirectorSystem::default(),
+            "spawn_director_system",
+            &["pause_system"],
+        )
+        .with(
+            ReplaySystem,
+            "replay_system",
+            &dependencies_with_optional(&["spawn_director_system"], !is_server, &["mouse_system"]),
+        )
+        .with(MonsterSpawnerSystem, "spawner_system", &["replay_system"])
         .with(
             ActionSystem,
             "action_system",
@@ -62,11 +78,32 @@ pub fn build_game_logic_systems<'a, 'b>(
             "missile_dying_system",
             &["action_system"],
         )
+        .with(
+            AreaSpellDyingSystem,
+            "area_spell_dying_system",
+            &["action_system"],
+        )
+        .with(
+            EnemyProjectileDyingSystem,
+            "enemy_projectile_dying_system",
+            &["action_system"],
+        )
+        .with(
+            LevelObjectiveSystem,
+            "level_objective_system",
+            &["action_system"],
+        )
         .with(
             StateSwitcherSystem,
             "state_switcher_system",
             &dependencies_with_optional(
-                &["monster_dying_system", "missile_dying_system"],
+                &[
+                    "monster_dying_system",
+                    "missile_dying_system",
+                    "area_spell_dying_system",
+                    "enemy_projectile_dying_system",
+                    "level_objective_system",
+                ],
                 !is_server,
                 &["menu_system"],
             ),
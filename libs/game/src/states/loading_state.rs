@@ -1,14 +1,15 @@
-#[cfg(feature = "client")]
+// Assets, renderer and UI are loaded by clients only.
+#[cfg_attr(not(feature = "client"), allow(unused_imports))]
 use amethyst::{
-    assets::ProgressCounter,
-    assets::{AssetStorage, Handle, Loader, PrefabLoader, RonFormat},
+    assets::{AssetStorage, Handle, Loader, PrefabLoader, ProgressCounter, RonFormat},
+    ecs::{prelude::WorldExt, ReadExpect, World},
+    prelude::{GameData, SimpleState, SimpleTrans, StateData, Trans},
     renderer::{ImageFormat, SpriteSheet, SpriteSheetFormat, Texture},
     ui::{FontAsset, TtfFormat, UiCreator},
 };
-use amethyst::{
-    ecs::{prelude::WorldExt, World},
-    prelude::{GameData, SimpleState, SimpleTrans, StateData, Trans},
-};
+
+#[cfg(feature = "client")]
+use std::collections::HashMap;
 
 #[cfg(feature = "client")]
 use gv_animation_prefabs::GameSpriteAnimationPrefab;
@@ -17,9 +18,13 @@ use gv_client_shared::ecs::{
     components::{PlayerColor, SpellParticle},
     resources::{AssetHandles, DummyAssetHandles},
 };
-use gv_core::ecs::resources::{GameEngineState, GameLevelState, GameTime, NewGameEngineState};
+use gv_core::ecs::resources::{
+    GameEngineState, GameLevelState, GameRngs, GameTime, NewGameEngineState,
+};
 
-use crate::ecs::resources::MonsterDefinitions;
+use crate::ecs::resources::GameMode;
+#[cfg(feature = "client")]
+use crate::ecs::resources::{MonsterDefinitions, DEFAULT_LANDSCAPE};
 
 #[cfg(feature = "client")]
 #[derive(Default)]
@@ -39,9 +44,13 @@ impl SimpleState for LoadingState {
         log::info!("LoadingState started");
         let world = data.world;
 
+        // Monster and spell definitions, the campaign and wave scripts are registered
+        // by `register_definitions` before the application starts.
         self.register_client_dependencies(world);
-        MonsterDefinitions::register(world);
+        // A server might have already selected a game mode.
+        world.entry::<GameMode>().or_insert_with(GameMode::default);
         world.insert(GameLevelState::default());
+        world.insert(GameRngs::default());
         world.insert(GameTime::default());
         world.insert(GameEngineState::Loading);
         world.insert(NewGameEngineState(GameEngineState::Loading));
@@ -96,26 +105,54 @@ impl LoadingState {
                     )
                 };
 
-                let landscape_handle = load_sprite_sheet(
-                    world,
-                    "resources/assets/desert_level.png",
-                    "resources/levels/desert.ron",
-                    &mut self.progress_counter,
-                );
-
-                let (mage_prefab, beetle_prefab) = world.exec(
-                    |prefab_loader: PrefabLoader<'_, GameSpriteAnimationPrefab>| {
-                        let mage_prefab = prefab_loader.load(
-                            "resources/prefabs/mage.ron",
-                            RonFormat,
+                // Levels may share landscapes, so we deduplicate them by sprite sheets.
+                let landscapes = {
+                    let campaign = world.fetch::<Campaign>();
+                    std::iter::once(&DEFAULT_LANDSCAPE)
+                        .chain(campaign.levels.iter().map(|level| &level.landscape))
+                        .map(|landscape| {
+                            (
+                                landscape.sprite_sheet.to_string(),
+                                landscape.texture.to_string(),
+                            )
+                        })
+                        .collect::<HashMap<_, _>>()
+                };
+                let landscapes = landscapes
+                    .into_iter()
+                    .map(|(sprite_sheet, texture)| {
+                        let landscape_handle = load_sprite_sheet(
+                            world,
+                            &texture,
+                            &sprite_sheet,
                             &mut self.progress_counter,
                         );
-                        let beetle_prefab = prefab_loader.load(
-                            "resources/prefabs/beetle.ron",
+                        (sprite_sheet, landscape_handle)
+                    })
+                    .collect();
+
+                let (mage_prefab, monster_prefabs) = world.exec(
+                    |(prefab_loader, monster_definitions): (
+                        PrefabLoader<'_, GameSpriteAnimationPrefab>,
+                        ReadExpect<MonsterDefinitions>,
+                    )| {
+                        let mage_prefab = prefab_loader.load(
+                            "resources/prefabs/mage.ron",
                             RonFormat,
                             &mut self.progress_counter,
                         );
-                        (mage_prefab, beetle_prefab)
+                        let monster_prefabs = monster_definitions
+                            .iter()
+                            .map(|monster_definition| {
+                                let prefab = prefab_loader.load(
+                                    monster_definition.prefab.as_str(),
+                                    RonFormat,
+                                    &mut self.progress_counter,
+                                );
+                                (monster_definition.name.clone(), prefab)
+                            })
+                            .collect();
+                        (mage_prefab, monster_prefabs)
                     },
                 );
 
@@ -127,6 +164,8 @@ impl LoadingState {
                         creator.create("resources/ui/lobby_menu.ron", ()),
                         creator.create("resources/ui/multiplayer_menu.ron", ()),
                         creator.create("resources/ui/restart_menu.ron", ()),
+                        creator.create("resources/ui/level_results_menu.ron", ()),
+                        creator.create("resources/ui/character_menu.ron", ()),
                         creator.create("resources/ui/modal.ron", ()),
                         creator.create("resources/ui/game_overlays.ron", ()),
                     )
@@ -134,8 +173,8 @@ impl LoadingState {
 
                 world.insert(AssetHandles {
                     mage_prefab,
-                    beetle_prefab,
-                    landscape: landscape_handle,
+                    monster_prefabs,
+                    landscapes,
                     ui_font: ui_font_handle,
                 });
 
@@ -7,9 +7,12 @@ use amethyst::{
 use gv_core::{
     actions::monster_spawn::SpawnActions,
     ecs::{
-        components::{missile::Missile, EntityNetMetadata, Monster, Player},
+        components::{
+            area_spell::AreaSpell, enemy_projectile::EnemyProjectile, missile::Missile,
+            EntityNetMetadata, Monster, Player,
+        },
         resources::{
-            net::{CastActionsToExecute, EntityNetMetadataStorage},
+            net::{CastActionsToExecute, EntityNetMetadataStorage, RangedAttacksToExecute},
             world::{FramedUpdates, WorldStates},
             GameEngineState, GameLevelState,
         },
@@ -36,15 +39,27 @@ impl SimpleState for MenuState {
             world.insert(FramedUpdates::<SpawnActions>::default());
             world.insert(WorldStates::default());
             world.insert(CastActionsToExecute::default());
+            world.insert(RangedAttacksToExecute::default());
             world.insert(EntityNetMetadataStorage::new());
 
             world.exec(
-                |(entities, entity_net_metadata, players, monsters, missiles, cameras): (
+                |(
+                    entities,
+                    entity_net_metadata,
+                    players,
+                    monsters,
+                    missiles,
+                    area_spells,
+                    enemy_projectiles,
+                    cameras,
+                ): (
                     Entities,
                     ReadStorage<EntityNetMetadata>,
                     ReadStorage<Player>,
                     ReadStorage<Monster>,
                     ReadStorage<Missile>,
+                    ReadStorage<AreaSpell>,
+                    ReadStorage<EnemyProjectile>,
                     ReadStorage<Camera>,
                 )| {
                     for (entity_net_metadata, _) in (&entities, &entity_net_metadata).join() {
@@ -67,6 +82,16 @@ impl SimpleState for MenuState {
                             .delete(missile_entity)
                             .expect("Expected to clean up an entity");
                     }
+                    for (area_spell_entity, _) in (&entities, &area_spells).join() {
+                        entities
+                            .delete(area_spell_entity)
+                            .expect("Expected to clean up an entity");
+                    }
+                    for (projectile_entity, _) in (&entities, &enemy_projectiles).join() {
+                        entities
+                            .delete(projectile_entity)
+                            .expect("Expected to clean up an entity");
+                    }
                     for (camera_entity, _) in (&entities, &cameras).join() {
                         entities
                             .delete(camera_entity)
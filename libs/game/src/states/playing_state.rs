@@ -1,39 +1,56 @@
 #[cfg(feature = "client")]
+use amethyst::prelude::{SimpleTrans, StateEvent, Trans};
 use amethyst::{
-    ecs::ReadExpect,
-    prelude::{SimpleTrans, StateEvent, Trans},
+    core::Transform,
+    ecs::{Entity, ReadExpect, SystemData, World, WriteExpect, WriteStorage},
+    prelude::{GameData, SimpleState, StateData},
 };
 #[cfg(not(feature = "client"))]
 use amethyst::{
     ecs::{Join, ReadStorage, Write},
     network::simulation::TransportResource,
 };
-use amethyst::{
-    ecs::{SystemData, World, WriteExpect, WriteStorage},
-    prelude::{GameData, SimpleState, StateData},
-};
 
 #[cfg(feature = "client")]
 use gv_client_shared::ecs::factories::PlayerClientFactory;
 #[cfg(feature = "client")]
 use gv_client_shared::{
     ecs::{factories::CameraFactory, resources::MultiplayerRoomState},
+    profile::Profile,
     utils,
 };
-use gv_core::ecs::{
-    components::EntityNetMetadata,
-    resources::{
-        net::{EntityNetMetadataStorage, MultiplayerGameState},
-        GameEngineState, GameLevelState,
+use gv_core::{
+    actions::{mob::MobAction, Action},
+    ecs::{
+        components::{
+            area_spell::AreaSpell,
+            damage_history::DamageHistory,
+            enemy_projectile::EnemyProjectile,
+            missile::{Missile, MissileTarget},
+            Dead, EntityNetMetadata, NetWorldPosition, Player, PlayerActions,
+            PlayerLastCastedSpells, WorldPosition,
+        },
+        resources::{
+            net::{EntityNetMetadataStorage, MultiplayerGameState},
+            world::{WorldSnapshot, WorldStates},
+            GameEngineState, GameLevelState, GameRng, GameRngs,
+        },
+        system_data::time::GameTimeService,
     },
-    system_data::time::GameTimeService,
+    math::Vector2,
+    progression::PlayerStats,
 };
-#[cfg(feature = "client")]
-use gv_core::PLAYER_COLORS;
 #[cfg(not(feature = "client"))]
 use gv_core::{ecs::components::NetConnectionModel, net::server_message::ServerMessagePayload};
+#[cfg(feature = "client")]
+use gv_core::{player_color, SINGLE_PLAYER_COLOR};
 
-use crate::ecs::factories::{LandscapeFactory, PlayerFactory};
+use crate::ecs::{
+    factories::{LandscapeFactory, MonsterFactory, PlayerFactory},
+    resources::{
+        Campaign, GameMode, MonsterDefinitions, ReplayPlayback, ReplayRecorder, WaveScripts,
+    },
+};
 #[cfg(not(feature = "client"))]
 use crate::utils::net::broadcast_message_reliable;
 
@@ -46,13 +63,65 @@ impl SimpleState for PlayingState {
         let world = data.world;
         *world.fetch_mut::<GameEngineState>() = GameEngineState::Playing;
 
-        world.insert(GameLevelState::default());
+        let mut game_rng = GameRng::new(game_seed(world));
+        let seed = game_rng.seed();
+        log::info!("Starting a game with seed {}", seed);
+        let mut game_level_state = GameLevelState::new(game_rng.split());
+        let (wave_script_path, landscape) = {
+            let game_mode = world.fetch::<GameMode>();
+            let campaign = world.fetch::<Campaign>();
+            if let Some(level_definition) = game_mode.level_definition(&campaign) {
+                log::info!(
+                    "Starting level \"{}\" ({})",
+                    level_definition.name,
+                    level_definition.objective.description()
+                );
+                let (width, height) = level_definition.dimensions;
+                game_level_state.dimensions = Vector2::new(width, height);
+            }
+            (
+                game_mode.wave_script_path(&campaign).to_owned(),
+                game_mode.landscape(&campaign).sprite_sheet.clone(),
+            )
+        };
+        world.insert(game_level_state);
+        world.insert(GameRngs::split_from(&mut game_rng));
+        let wave_script = world
+            .fetch::<WaveScripts>()
+            .get(&wave_script_path)
+            .cloned()
+            .expect("Expected wave scripts of all the game modes to be loaded");
+        world.insert(wave_script);
+
+        let world_snapshot = world
+            .fetch_mut::<MultiplayerGameState>()
+            .world_snapshot
+            .take();
+        if let Some(world_snapshot) = &world_snapshot {
+            // The snapshot contains the state after simulating its frame.
+            let resumed_frame_number = world_snapshot.frame_number + 1;
+            log::info!("Resuming the game from frame {}", resumed_frame_number);
+            GameTimeService::fetch(&world).set_game_start_time_at_frame(resumed_frame_number);
+            world.insert(WorldStates::starting_from(resumed_frame_number));
+        } else {
+            GameTimeService::fetch(&world).set_game_start_time();
+        }
 
-        GameTimeService::fetch(&world).set_game_start_time();
+        let players = initialize_players(world);
+        if let Some(world_snapshot) = world_snapshot {
+            resume_world(world, world_snapshot);
+        }
+        let game_mode = *world.fetch::<GameMode>();
+        world
+            .fetch_mut::<ReplayRecorder>()
+            .start(seed, game_mode, players);
 
-        initialize_players(world);
+        world.exec(|mut landscape_factory: LandscapeFactory| landscape_factory.create(&landscape));
+    }
 
-        world.exec(|mut landscape_factory: LandscapeFactory| landscape_factory.create());
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        data.world.fetch_mut::<ReplayRecorder>().finish();
+        data.world.fetch_mut::<ReplayPlayback>().stop();
     }
 
     #[cfg(feature = "client")]
@@ -68,8 +137,29 @@ impl SimpleState for PlayingState {
 }
 
 #[cfg(feature = "client")]
-fn initialize_players(world: &mut World) {
+fn game_seed(world: &mut World) -> u64 {
+    let multiplayer_game_state = world.fetch::<MultiplayerGameState>();
+    if multiplayer_game_state.is_playing {
+        multiplayer_game_state.game_seed
+    } else if let Some(replay) = world.fetch::<ReplayPlayback>().replay() {
+        replay.seed
+    } else {
+        rand::random()
+    }
+}
+
+#[cfg(not(feature = "client"))]
+fn game_seed(world: &mut World) -> u64 {
+    let seed = rand::random();
+    world.fetch_mut::<MultiplayerGameState>().game_seed = seed;
+    seed
+}
+
+/// Returns the players to record in a replay, which is possible for single player games only.
+#[cfg(feature = "client")]
+fn initialize_players(world: &mut World) -> Vec<(Entity, PlayerStats)> {
     let mut main_player = None;
+    let mut single_players = Vec::new();
 
     world.exec(
         |(
@@ -77,24 +167,45 @@ fn initialize_players(world: &mut World) {
             mut player_client_factory,
             mut entity_net_metadata,
             mut entity_net_metadata_service,
+            mut replay_playback,
             multiplayer_room_state,
             multiplayer_game_state,
+            profile,
         ): (
             PlayerFactory,
             PlayerClientFactory,
             WriteStorage<EntityNetMetadata>,
             WriteExpect<EntityNetMetadataStorage>,
+            WriteExpect<ReplayPlayback>,
             ReadExpect<MultiplayerRoomState>,
             ReadExpect<MultiplayerGameState>,
+            ReadExpect<Profile>,
         )| {
+            if let Some(replay) = replay_playback.replay() {
+                for (i, stats) in replay.players.iter().enumerate() {
+                    let player_entity = player_factory.create(*stats);
+                    let color = if i == 0 {
+                        SINGLE_PLAYER_COLOR
+                    } else {
+                        player_color(i - 1)
+                    };
+                    player_client_factory.create(player_entity, color, true);
+                    main_player = main_player.or(Some(player_entity));
+                    single_players.push((player_entity, *stats));
+                }
+                replay_playback.start(single_players.iter().map(|(entity, _)| *entity).collect());
+                return;
+            }
+
             if !multiplayer_game_state.is_playing {
-                let player_entity = player_factory.create();
-                player_client_factory.create(player_entity, PLAYER_COLORS[4], true);
+                let player_entity = player_factory.create(profile.stats);
+                player_client_factory.create(player_entity, SINGLE_PLAYER_COLOR, true);
                 main_player = Some(player_entity);
+                single_players.push((player_entity, profile.stats));
             }
 
             for player in &multiplayer_game_state.players {
-                let player_entity = player_factory.create();
+                let player_entity = player_factory.create(player.stats);
                 entity_net_metadata_service.set_net_id(player_entity, player.entity_net_id);
                 entity_net_metadata
                     .insert(
@@ -106,7 +217,11 @@ fn initialize_players(world: &mut World) {
                     )
                     .expect("Expected to insert EntityNetMetadata component");
 
-                if player.entity_net_id == multiplayer_room_state.player_net_id {
+                if multiplayer_room_state.is_spectator {
+                    // Spectators follow the first player until they switch to another one.
+                    player_client_factory.create(player_entity, player.color, false);
+                    main_player = main_player.or(Some(player_entity));
+                } else if player.entity_net_id == multiplayer_room_state.player_net_id {
                     player_client_factory.create(player_entity, player.color, true);
                     main_player = Some(player_entity);
                 } else {
@@ -120,16 +235,18 @@ fn initialize_players(world: &mut World) {
     world.exec(move |mut camera_factory: CameraFactory| {
         camera_factory.create(main_player);
     });
+    single_players
 }
 
 #[cfg(not(feature = "client"))]
-fn initialize_players(world: &mut World) {
+fn initialize_players(world: &mut World) -> Vec<(Entity, PlayerStats)> {
     world.exec(
         |(
             mut player_factory,
             mut entity_net_metadata,
             mut entity_net_metadata_service,
             mut multiplayer_game_state,
+            game_mode,
             net_connections,
             mut transport,
         ): (
@@ -137,6 +254,7 @@ fn initialize_players(world: &mut World) {
             WriteStorage<EntityNetMetadata>,
             WriteExpect<EntityNetMetadataStorage>,
             WriteExpect<MultiplayerGameState>,
+            ReadExpect<GameMode>,
             ReadStorage<NetConnectionModel>,
             Write<TransportResource>,
         )| {
@@ -144,7 +262,7 @@ fn initialize_players(world: &mut World) {
                 .players
                 .iter_mut()
                 .map(|player| {
-                    let player_entity = player_factory.create();
+                    let player_entity = player_factory.create(player.stats);
                     let entity_net_id =
                         entity_net_metadata_service.register_new_entity(player_entity);
                     player.entity_net_id = entity_net_id;
@@ -163,8 +281,255 @@ fn initialize_players(world: &mut World) {
             broadcast_message_reliable(
                 &mut transport,
                 (&net_connections).join(),
-                ServerMessagePayload::StartGame(player_net_identifiers),
+                ServerMessagePayload::StartGame {
+                    seed: multiplayer_game_state.game_seed,
+                    players: player_net_identifiers,
+                    campaign_level: game_mode.campaign_level(),
+                },
             );
         },
     );
+    Vec::new()
+}
+
+/// Recreates the world of a running game for a client that has rejoined it.
+fn resume_world(world: &mut World, world_snapshot: WorldSnapshot) {
+    let resumed_frame_number = world_snapshot.frame_number + 1;
+    *world.fetch_mut::<GameRngs>() = GameRngs::from_state(world_snapshot.rngs);
+    world_snapshot
+        .level_progress
+        .apply(&mut world.fetch_mut::<GameLevelState>());
+    world.exec(
+        |(
+            mut monster_factory,
+            mut entity_net_metadata,
+            mut entity_net_metadata_service,
+            monster_definitions,
+            mut players,
+            mut player_actions,
+            mut player_last_casted_spells,
+            mut net_world_positions,
+            mut dead,
+            mut missiles,
+            mut area_spells,
+            mut enemy_projectiles,
+        ): (
+            MonsterFactory,
+            WriteStorage<EntityNetMetadata>,
+            WriteExpect<EntityNetMetadataStorage>,
+            ReadExpect<MonsterDefinitions>,
+            WriteStorage<Player>,
+            WriteStorage<PlayerActions>,
+            WriteStorage<PlayerLastCastedSpells>,
+            WriteStorage<NetWorldPosition>,
+            WriteStorage<Dead>,
+            WriteStorage<Missile>,
+            WriteStorage<AreaSpell>,
+            WriteStorage<EnemyProjectile>,
+        )| {
+            for player_snapshot in world_snapshot.players {
+                let player_entity = if let Some(player_entity) =
+                    entity_net_metadata_service.get_entity(player_snapshot.net_metadata.id)
+                {
+                    player_entity
+                } else {
+                    log::warn!(
+                        "Couldn't find a player with net id {} to resume",
+                        player_snapshot.net_metadata.id
+                    );
+                    continue;
+                };
+                entity_net_metadata
+                    .insert(player_entity, player_snapshot.net_metadata)
+                    .expect("Expected to insert EntityNetMetadata component");
+
+                let player = players
+                    .get_mut(player_entity)
+                    .expect("Expected a Player component");
+                player.health = player_snapshot.health;
+                player.mana = player_snapshot.mana;
+                player.velocity = player_snapshot.velocity;
+                player.walking_direction = player_snapshot.walking_direction;
+                player.looking_direction = player_snapshot.looking_direction;
+                player.stats = player_snapshot.stats;
+                *player_actions
+                    .get_mut(player_entity)
+                    .expect("Expected a PlayerActions component") = player_snapshot.actions;
+                let last_casted_spells = player_last_casted_spells
+                    .get_mut(player_entity)
+                    .expect("Expected a PlayerLastCastedSpells component");
+                for (spell, frame_number) in player_snapshot.last_casted_spells {
+                    last_casted_spells.set_frame(spell, frame_number);
+                }
+                **monster_factory
+                    .world_positions
+                    .get_mut(player_entity)
+                    .expect("Expected a WorldPosition component") = player_snapshot.position;
+                **net_world_positions
+                    .get_mut(player_entity)
+                    .expect("Expected a NetWorldPosition component") = player_snapshot.position;
+                monster_factory
+                    .damage_histories
+                    .insert(player_entity, DamageHistory::new(resumed_frame_number))
+                    .expect("Expected to insert DamageHistory component");
+                if let Some(dead_since_frame) = player_snapshot.dead_since_frame {
+                    dead.insert(
+                        player_entity,
+                        Dead::new(dead_since_frame, resumed_frame_number),
+                    )
+                    .expect("Expected to insert Dead component");
+                }
+            }
+
+            // Monster actions can target other entities, so we load them after spawning.
+            let mut monster_actions = Vec::with_capacity(world_snapshot.monsters.len());
+            for monster_snapshot in world_snapshot.monsters {
+                let definition =
+                    if let Some(definition) = monster_definitions.get(&monster_snapshot.name) {
+                        definition.clone()
+                    } else {
+                        log::warn!("Unknown monster type: {}", monster_snapshot.name);
+                        continue;
+                    };
+
+                let monster_entity = monster_factory.create(
+                    resumed_frame_number,
+                    definition,
+                    monster_snapshot.position,
+                    monster_snapshot.destination,
+                    Action {
+                        frame_number: monster_snapshot.action.frame_number,
+                        action: MobAction::Idle,
+                    },
+                );
+                let monster = monster_factory
+                    .monsters
+                    .get_mut(monster_entity)
+                    .expect("Expected a Monster component");
+                monster.health = monster_snapshot.health;
+                monster.attack_damage = monster_snapshot.attack_damage;
+                monster.facing_direction = monster_snapshot.facing_direction;
+                monster.velocity = monster_snapshot.velocity;
+                monster.radius = monster_snapshot.radius;
+
+                entity_net_metadata_service
+                    .set_net_id(monster_entity, monster_snapshot.net_metadata.id);
+                entity_net_metadata
+                    .insert(monster_entity, monster_snapshot.net_metadata)
+                    .expect("Expected to insert EntityNetMetadata component");
+                monster_actions.push((monster_entity, monster_snapshot.action));
+            }
+            for (monster_entity, action) in monster_actions {
+                monster_factory
+                    .monsters
+                    .get_mut(monster_entity)
+                    .expect("Expected a Monster component")
+                    .action = Action {
+                    frame_number: action.frame_number,
+                    action: action
+                        .action
+                        .load_entity_by_net_id(&entity_net_metadata_service),
+                };
+            }
+
+            for missile_snapshot in world_snapshot.missiles {
+                let target = match missile_snapshot.target {
+                    MissileTarget::Target(target) => {
+                        match entity_net_metadata_service.get_entity(target) {
+                            Some(target) => MissileTarget::Target(target),
+                            None => continue,
+                        }
+                    }
+                    MissileTarget::Destination(destination) => {
+                        MissileTarget::Destination(destination)
+                    }
+                };
+
+                let position = missile_snapshot.position;
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(position.x, position.y, 50.0);
+                monster_factory
+                    .entities
+                    .build_entity()
+                    .with(transform, &mut monster_factory.transforms)
+                    .with(
+                        WorldPosition::new(position),
+                        &mut monster_factory.world_positions,
+                    )
+                    .with(
+                        Missile::new(
+                            missile_snapshot.action_id,
+                            missile_snapshot.radius,
+                            target,
+                            missile_snapshot.velocity,
+                            missile_snapshot.frame_spawned,
+                            missile_snapshot.damage,
+                            missile_snapshot.caster,
+                        ),
+                        &mut missiles,
+                    )
+                    .build();
+            }
+
+            for area_spell_snapshot in world_snapshot.area_spells {
+                let mut area_spell = AreaSpell::new(
+                    area_spell_snapshot.action_id,
+                    area_spell_snapshot.radius,
+                    area_spell_snapshot.damage,
+                    area_spell_snapshot.frame_spawned,
+                    area_spell_snapshot.caster,
+                );
+                area_spell.hit_monsters = area_spell_snapshot
+                    .hit_monsters
+                    .into_iter()
+                    .filter_map(|monster| entity_net_metadata_service.get_entity(monster))
+                    .collect();
+
+                let position = area_spell_snapshot.position;
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(position.x, position.y, 0.0);
+                monster_factory
+                    .entities
+                    .build_entity()
+                    .with(transform, &mut monster_factory.transforms)
+                    .with(
+                        WorldPosition::new(position),
+                        &mut monster_factory.world_positions,
+                    )
+                    .with(area_spell, &mut area_spells)
+                    .build();
+            }
+
+            for projectile_snapshot in world_snapshot.enemy_projectiles {
+                let owner = match entity_net_metadata_service.get_entity(projectile_snapshot.owner)
+                {
+                    Some(owner) => owner,
+                    None => continue,
+                };
+
+                let position = projectile_snapshot.position;
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(position.x, position.y, 0.0);
+                monster_factory
+                    .entities
+                    .build_entity()
+                    .with(transform, &mut monster_factory.transforms)
+                    .with(
+                        WorldPosition::new(position),
+                        &mut monster_factory.world_positions,
+                    )
+                    .with(
+                        EnemyProjectile::new(
+                            owner,
+                            projectile_snapshot.radius,
+                            projectile_snapshot.velocity,
+                            projectile_snapshot.frame_spawned,
+                            projectile_snapshot.damage,
+                        ),
+                        &mut enemy_projectiles,
+                    )
+                    .build();
+            }
+        },
+    );
 }
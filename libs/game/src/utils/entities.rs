@@ -10,11 +10,14 @@ use amethyst::{
 #[cfg(feature = "client")]
 use gv_animation_prefabs::AnimationId;
 use gv_core::ecs::{
-    components::{missile::Missile, Dead},
+    components::{area_spell::AreaSpell, missile::Missile, Dead},
     system_data::time::GameTimeService,
 };
 
-use crate::ecs::systems::missile::{MISSILE_LIFESPAN_SECS, MISSILE_TIME_TO_FADE};
+use crate::ecs::systems::{
+    area_spell::AREA_SPELL_EXPANSION_SECS,
+    missile::{MISSILE_LIFESPAN_SECS, MISSILE_TIME_TO_FADE},
+};
 
 pub fn is_dead(
     entity: Entity,
@@ -126,3 +129,14 @@ pub fn missile_energy(
     }
     energy
 }
+
+/// Returns values within the range [0.0; 1.0], an area spell stops expanding on reaching 1.0.
+pub fn area_spell_expansion(
+    area_spell: &AreaSpell,
+    game_time_service: &GameTimeService,
+    frame_number: u64,
+) -> f32 {
+    (game_time_service.seconds_between_frames(frame_number, area_spell.frame_spawned)
+        / AREA_SPELL_EXPANSION_SECS)
+        .clamp(0.0, 1.0)
+}
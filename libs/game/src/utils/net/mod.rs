@@ -1,10 +1,22 @@
 use amethyst::network::simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement};
+use lazy_static::lazy_static;
+
+pub mod simulation;
+pub mod transport;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
 
-use gv_core::ecs::components::NetConnectionModel;
 #[cfg(feature = "client")]
 use gv_core::net::client_message::{ClientMessage, ClientMessagePayload};
 #[cfg(not(feature = "client"))]
 use gv_core::net::server_message::{ServerMessage, ServerMessagePayload};
+use gv_core::{ecs::components::NetConnectionModel, net::fragmentation::split_message};
 
 #[cfg(not(feature = "client"))]
 pub fn broadcast_message_reliable<'a>(
@@ -19,11 +31,12 @@ pub fn broadcast_message_reliable<'a>(
         })
         .expect("Expected to serialize a broadcasted message");
         if !connection.disconnected {
-            transport.send_with_requirements(
-                connection.addr,
+            send_datagrams(
+                transport,
+                connection,
+                payload.name(),
                 &sent_message,
                 DeliveryRequirement::Reliable,
-                UrgencyRequirement::Immediate,
             );
         }
     }
@@ -42,11 +55,12 @@ pub fn broadcast_message_unreliable<'a>(
         })
         .expect("Expected to serialize a broadcasted message");
         if !connection.disconnected {
-            transport.send_with_requirements(
-                connection.addr,
+            send_datagrams(
+                transport,
+                connection,
+                payload.name(),
                 &sent_message,
                 DeliveryRequirement::Unreliable,
-                UrgencyRequirement::Immediate,
             );
         }
     }
@@ -61,16 +75,18 @@ pub fn send_message_reliable(
     if net_connection.disconnected {
         return;
     }
+    let message_name = payload.name();
     let sent_message = bincode::serialize(&ClientMessage {
         session_id: net_connection.session_id,
         payload,
     })
     .expect("Expected to serialize a client message");
-    transport.send_with_requirements(
-        net_connection.addr,
+    send_datagrams(
+        transport,
+        net_connection,
+        message_name,
         &sent_message,
         DeliveryRequirement::Reliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -83,16 +99,18 @@ pub fn send_message_reliable(
     if net_connection.disconnected {
         return;
     }
+    let message_name = payload.name();
     let sent_message = bincode::serialize(&ServerMessage {
         session_id: net_connection.session_id,
         payload,
     })
     .expect("Expected to serialize a server message");
-    transport.send_with_requirements(
-        net_connection.addr,
+    send_datagrams(
+        transport,
+        net_connection,
+        message_name,
         &sent_message,
         DeliveryRequirement::Reliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -105,6 +123,7 @@ pub fn send_message_unreliable(
     if net_connection.disconnected {
         return;
     }
+    let message_name = payload.name();
     let message = ClientMessage {
         session_id: net_connection.session_id,
         payload,
@@ -112,11 +131,12 @@ pub fn send_message_unreliable(
     log::trace!("Sending: {:#?}", message);
     let sent_message =
         bincode::serialize(&message).expect("Expected to serialize a client message");
-    transport.send_with_requirements(
-        net_connection.addr,
+    send_datagrams(
+        transport,
+        net_connection,
+        message_name,
         &sent_message,
         DeliveryRequirement::Unreliable,
-        UrgencyRequirement::Immediate,
     );
 }
 
@@ -129,6 +149,7 @@ pub fn send_message_unreliable(
     if net_connection.disconnected {
         return;
     }
+    let message_name = payload.name();
     let message = ServerMessage {
         session_id: net_connection.session_id,
         payload,
@@ -137,10 +158,87 @@ pub fn send_message_unreliable(
     let sent_message =
         bincode::serialize(&message).expect("Expected to serialize a server message");
     log::trace!("Packet len: {}", sent_message.len());
-    transport.send_with_requirements(
-        net_connection.addr,
+    send_datagrams(
+        transport,
+        net_connection,
+        message_name,
         &sent_message,
         DeliveryRequirement::Unreliable,
-        UrgencyRequirement::Immediate,
     );
 }
+
+/// Fragments of a message share its id, so ids only have to be unique for a short while.
+static NEXT_MESSAGE_ID: AtomicU32 = AtomicU32::new(0);
+
+lazy_static! {
+    static ref SENT_MESSAGE_STATS: Mutex<HashMap<&'static str, SentMessageStats>> =
+        Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SentMessageStats {
+    pub count: u64,
+    pub bytes: u64,
+    pub max_bytes: u64,
+    /// Messages that didn't fit into a single datagram.
+    pub fragmented: u64,
+    /// Messages that exceeded `max_message_size` and weren't sent.
+    pub dropped: u64,
+}
+
+/// Returns the stats of the messages sent by this process, sorted by message names.
+pub fn sent_message_stats() -> Vec<(&'static str, SentMessageStats)> {
+    let mut stats = SENT_MESSAGE_STATS
+        .lock()
+        .expect("Expected to lock the sent message stats")
+        .iter()
+        .map(|(message_name, stats)| (*message_name, *stats))
+        .collect::<Vec<_>>();
+    stats.sort_by_key(|(message_name, _)| *message_name);
+    stats
+}
+
+fn send_datagrams(
+    transport: &mut TransportResource,
+    net_connection: &NetConnectionModel,
+    message_name: &'static str,
+    message: &[u8],
+    delivery: DeliveryRequirement,
+) {
+    let message_id = NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+    let datagrams = split_message(message_id, message, net_connection.mtu);
+    {
+        let mut stats = SENT_MESSAGE_STATS
+            .lock()
+            .expect("Expected to lock the sent message stats");
+        let stats = stats.entry(message_name).or_default();
+        match &datagrams {
+            Ok(datagrams) => {
+                stats.count += 1;
+                stats.bytes += message.len() as u64;
+                stats.max_bytes = stats.max_bytes.max(message.len() as u64);
+                if datagrams.len() > 1 {
+                    stats.fragmented += 1;
+                }
+            }
+            Err(err) => {
+                stats.dropped += 1;
+                log::error!(
+                    "Failed to send {} to {}: {}",
+                    message_name,
+                    net_connection.addr,
+                    err
+                );
+            }
+        }
+    }
+
+    for datagram in datagrams.into_iter().flatten() {
+        transport.send_with_requirements(
+            net_connection.addr,
+            &datagram,
+            delivery,
+            UrgencyRequirement::Immediate,
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use amethyst::{
+    ecs::{System, Write},
+    network::{
+        simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement},
+        Bytes,
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::{
+    fmt::{self, Display},
+    net::SocketAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Neither latency nor jitter can be configured higher than this.
+pub const MAX_SIMULATED_DELAY_MS: u32 = 5000;
+/// Reordered datagrams are held for at least this long, even if there's no latency.
+const MIN_REORDERING_DELAY: Duration = Duration::from_millis(50);
+
+/// Degrades the traffic of a client or a server to reproduce bad networks. Latency, jitter
+/// and reordering apply to datagrams in both directions. Loss and duplication apply only to
+/// outgoing unreliable datagrams: the simulator sits above laminar, so it can't drop
+/// reliable datagrams without losing them for good.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkConditions {
+    pub latency_ms: u32,
+    /// Up to this many milliseconds are randomly added to the latency of each datagram.
+    pub jitter_ms: u32,
+    /// Probabilities (from 0.0 to 1.0).
+    pub loss: f32,
+    pub duplication: f32,
+    /// A reordered datagram is held for longer, so that it arrives after the following ones.
+    pub reordering: f32,
+}
+
+impl NetworkConditions {
+    pub fn is_enabled(&self) -> bool {
+        *self != Self::default()
+    }
+
+    fn delay(&self, rng: &mut impl Rng) -> Duration {
+        let mut delay = Duration::from_millis(u64::from(self.latency_ms));
+        if self.jitter_ms > 0 {
+            delay += Duration::from_millis(rng.gen_range(0, u64::from(self.jitter_ms) + 1));
+        }
+        if rng.gen::<f32>() < self.reordering {
+            delay += Duration::from_millis(u64::from(self.latency_ms + self.jitter_ms))
+                .max(MIN_REORDERING_DELAY);
+        }
+        delay
+    }
+}
+
+impl Display for NetworkConditions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "latency={},jitter={},loss={},duplication={},reordering={}",
+            self.latency_ms,
+            self.jitter_ms,
+            self.loss * 100.0,
+            self.duplication * 100.0,
+            self.reordering * 100.0
+        )
+    }
+}
+
+/// Parses a comma separated list of options, such as `latency=150,jitter=30,loss=5`.
+/// Latency and jitter are in milliseconds, the rest of the options are in percents.
+impl FromStr for NetworkConditions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut conditions = Self::default();
+        for option in s
+            .split(',')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+        {
+            let mut name_value = option.splitn(2, '=').map(str::trim);
+            let (name, value) = match (name_value.next(), name_value.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => return Err(format!("expected name=value, got \"{}\"", option)),
+            };
+            match name {
+                "latency" => conditions.latency_ms = parse_delay(name, value)?,
+                "jitter" => conditions.jitter_ms = parse_delay(name, value)?,
+                "loss" => conditions.loss = parse_percentage(name, value)?,
+                "duplication" => conditions.duplication = parse_percentage(name, value)?,
+                "reordering" => conditions.reordering = parse_percentage(name, value)?,
+                _ => return Err(format!("unknown option \"{}\"", name)),
+            }
+        }
+        Ok(conditions)
+    }
+}
+
+fn parse_delay(name: &str, value: &str) -> Result<u32, String> {
+    let delay: u32 = value
+        .parse()
+        .map_err(|err| format!("invalid {} \"{}\": {}", name, value, err))?;
+    if delay > MAX_SIMULATED_DELAY_MS {
+        return Err(format!(
+            "{} must not exceed {} ms, got {}",
+            name, MAX_SIMULATED_DELAY_MS, delay
+        ));
+    }
+    Ok(delay)
+}
+
+fn parse_percentage(name: &str, value: &str) -> Result<f32, String> {
+    let percentage: f32 = value
+        .parse()
+        .map_err(|err| format!("invalid {} \"{}\": {}", name, value, err))?;
+    if !(0.0..=100.0).contains(&percentage) {
+        return Err(format!(
+            "{} must be between 0 and 100, got {}",
+            name, percentage
+        ));
+    }
+    Ok(percentage / 100.0)
+}
+
+pub struct OutgoingDatagram {
+    pub destination: SocketAddr,
+    pub payload: Bytes,
+    pub delivery: DeliveryRequirement,
+}
+
+/// Holds datagrams until the simulated network delivers them. Outgoing datagrams are held by
+/// NetworkSimulatorSystem, incoming ones by NetConnectionManagerSystem.
+pub struct NetworkSimulator {
+    pub conditions: NetworkConditions,
+    rng: StdRng,
+    outgoing: DelayQueue<OutgoingDatagram>,
+    incoming: DelayQueue<(SocketAddr, Bytes)>,
+}
+
+impl Default for NetworkSimulator {
+    fn default() -> Self {
+        Self::new(NetworkConditions::default(), None)
+    }
+}
+
+impl NetworkSimulator {
+    /// A simulator with the same seed loses and delays the same datagrams,
+    /// if they are sent in the same order.
+    pub fn new(conditions: NetworkConditions, seed: Option<u64>) -> Self {
+        let rng = if let Some(seed) = seed {
+            let mut rng_seed = <StdRng as SeedableRng>::Seed::default();
+            rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+            StdRng::from_seed(rng_seed)
+        } else {
+            StdRng::from_rng(rand::thread_rng()).expect("Expected to seed a network simulator")
+        };
+        Self {
+            conditions,
+            rng,
+            outgoing: DelayQueue::default(),
+            incoming: DelayQueue::default(),
+        }
+    }
+
+    pub fn send(&mut self, datagram: OutgoingDatagram, now: Instant) {
+        if let DeliveryRequirement::Unreliable = datagram.delivery {
+            if self.rng.gen::<f32>() < self.conditions.loss {
+                log::trace!(
+                    "Simulating a loss of a datagram to {}",
+                    datagram.destination
+                );
+                return;
+            }
+            if self.rng.gen::<f32>() < self.conditions.duplication {
+                let duplicate = OutgoingDatagram {
+                    destination: datagram.destination,
+                    payload: datagram.payload.clone(),
+                    delivery: DeliveryRequirement::Unreliable,
+                };
+                self.outgoing
+                    .push(now + self.conditions.delay(&mut self.rng), duplicate);
+            }
+        }
+        self.outgoing
+            .push(now + self.conditions.delay(&mut self.rng), datagram);
+    }
+
+    pub fn receive(&mut self, addr: SocketAddr, payload: Bytes, now: Instant) {
+        let delay = self.conditions.delay(&mut self.rng);
+        self.incoming.push(now + delay, (addr, payload));
+    }
+
+    /// Is called when a peer disconnects, so that its delayed datagrams
+    /// don't get released for a connection that no longer exists.
+    pub fn drop_incoming(&mut self, addr: SocketAddr) {
+        self.incoming
+            .retain(|(datagram_addr, _)| *datagram_addr != addr);
+    }
+
+    /// Returns the datagrams that are due to be sent, or all of them if the simulation is off.
+    pub fn release_outgoing(&mut self, now: Instant) -> Vec<OutgoingDatagram> {
+        let release_all = !self.conditions.is_enabled();
+        self.outgoing.release(now, release_all)
+    }
+
+    /// Returns the datagrams that are due to be received, or all of them if the simulation is off.
+    pub fn release_incoming(&mut self, now: Instant) -> Vec<(SocketAddr, Bytes)> {
+        let release_all = !self.conditions.is_enabled();
+        self.incoming.release(now, release_all)
+    }
+}
+
+/// Keeps datagrams sorted by their release time, datagrams released at the same time keep
+/// the order they were pushed in.
+struct DelayQueue<T> {
+    datagrams: Vec<(Instant, T)>,
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self {
+            datagrams: Vec::new(),
+        }
+    }
+}
+
+impl<T> DelayQueue<T> {
+    fn push(&mut self, release_at: Instant, datagram: T) {
+        let index = self
+            .datagrams
+            .partition_point(|(datagram_release_at, _)| *datagram_release_at <= release_at);
+        self.datagrams.insert(index, (release_at, datagram));
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.datagrams.retain(|(_, datagram)| f(datagram));
+    }
+
+    fn release(&mut self, now: Instant, release_all: bool) -> Vec<T> {
+        let count = if release_all {
+            self.datagrams.len()
+        } else {
+            self.datagrams
+                .partition_point(|(release_at, _)| *release_at <= now)
+        };
+        self.datagrams
+            .drain(..count)
+            .map(|(_, datagram)| datagram)
+            .collect()
+    }
+}
+
+/// Is added before the transport systems, so that they send only the released datagrams.
+pub struct NetworkSimulatorSystem;
+
+impl<'s> System<'s> for NetworkSimulatorSystem {
+    type SystemData = (Write<'s, TransportResource>, Write<'s, NetworkSimulator>);
+
+    fn run(&mut self, (mut transport, mut network_simulator): Self::SystemData) {
+        let now = Instant::now();
+        if network_simulator.conditions.is_enabled() {
+            for message in transport.drain_messages_to_send(|_| true) {
+                network_simulator.send(
+                    OutgoingDatagram {
+                        destination: message.destination,
+                        payload: message.payload,
+                        delivery: message.delivery,
+                    },
+                    now,
+                );
+            }
+        }
+
+        for datagram in network_simulator.release_outgoing(now) {
+            transport.send_with_requirements(
+                datagram.destination,
+                &datagram.payload,
+                datagram.delivery,
+                UrgencyRequirement::Immediate,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datagram(payload: u8) -> OutgoingDatagram {
+        OutgoingDatagram {
+            destination: "127.0.0.1:3455".parse().unwrap(),
+            payload: Bytes::from(vec![payload]),
+            delivery: DeliveryRequirement::Unreliable,
+        }
+    }
+
+    fn simulate(seed: u64) -> Vec<Bytes> {
+        let conditions = NetworkConditions {
+            latency_ms: 100,
+            jitter_ms: 50,
+            loss: 0.2,
+            duplication: 0.1,
+            reordering: 0.1,
+        };
+        let mut network_simulator = NetworkSimulator::new(conditions, Some(seed));
+        let now = Instant::now();
+        for payload in 0..100 {
+            network_simulator.send(datagram(payload), now);
+        }
+        network_simulator
+            .release_outgoing(now + Duration::from_secs(10))
+            .into_iter()
+            .map(|datagram| datagram.payload)
+            .collect()
+    }
+
+    #[test]
+    fn simulates_the_same_network_with_the_same_seed() {
+        let datagrams = simulate(42);
+        assert_eq!(datagrams, simulate(42));
+        assert_ne!(datagrams, simulate(43));
+    }
+
+    #[test]
+    fn drops_incoming_datagrams_of_disconnected_peers() {
+        let conditions = NetworkConditions {
+            latency_ms: 100,
+            ..NetworkConditions::default()
+        };
+        let mut network_simulator = NetworkSimulator::new(conditions, Some(42));
+        let disconnected_addr: SocketAddr = "127.0.0.1:3456".parse().unwrap();
+        let connected_addr: SocketAddr = "127.0.0.1:3457".parse().unwrap();
+        let now = Instant::now();
+        network_simulator.receive(disconnected_addr, Bytes::from(vec![0]), now);
+        network_simulator.receive(connected_addr, Bytes::from(vec![1]), now);
+
+        network_simulator.drop_incoming(disconnected_addr);
+        let datagrams = network_simulator.release_incoming(now + Duration::from_secs(1));
+        assert_eq!(datagrams, vec![(connected_addr, Bytes::from(vec![1]))]);
+    }
+}
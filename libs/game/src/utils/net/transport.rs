@@ -0,0 +1,236 @@
+use amethyst::{
+    core::{bundle::SystemBundle, shrev::EventChannel},
+    ecs::{DispatcherBuilder, System, World, Write},
+    network::{
+        simulation::{
+            laminar::{LaminarNetworkBundle, LaminarSocket},
+            NetworkSimulationEvent, TransportResource,
+        },
+        Bytes,
+    },
+    Error,
+};
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use super::simulation::{NetworkConditions, NetworkSimulator, NetworkSimulatorSystem};
+
+/// A network backend. Systems queue outgoing datagrams in `TransportResource` and read incoming
+/// ones from `EventChannel<NetworkSimulationEvent>`, a transport moves them between the two.
+pub trait Transport {
+    /// The address that peers send datagrams to.
+    fn local_addr(&self) -> SocketAddr;
+
+    /// Adds the systems that send and receive datagrams.
+    fn build(
+        self: Box<Self>,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'_, '_>,
+    ) -> Result<(), Error>;
+}
+
+/// Is inserted by TransportBundle, replaces asking a socket for its address.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalTransportAddr(pub SocketAddr);
+
+pub struct TransportBundle {
+    transport: Box<dyn Transport>,
+    network_conditions: NetworkConditions,
+    network_seed: Option<u64>,
+}
+
+impl TransportBundle {
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Box::new(transport),
+            network_conditions: NetworkConditions::default(),
+            network_seed: None,
+        }
+    }
+
+    pub fn with_network_conditions(mut self, network_conditions: NetworkConditions) -> Self {
+        self.network_conditions = network_conditions;
+        self
+    }
+
+    /// Makes the simulated network lose and delay the same datagrams on every run.
+    pub fn with_network_seed(mut self, network_seed: Option<u64>) -> Self {
+        self.network_seed = network_seed;
+        self
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for TransportBundle {
+    fn build(
+        self,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'a, 'b>,
+    ) -> Result<(), Error> {
+        world.insert(LocalTransportAddr(self.transport.local_addr()));
+        world.insert(NetworkSimulator::new(
+            self.network_conditions,
+            self.network_seed,
+        ));
+        // Systems that write the same resources run in the order they are added.
+        builder.add(NetworkSimulatorSystem, "network_simulator_system", &[]);
+        self.transport.build(world, builder)
+    }
+}
+
+pub struct LaminarTransport {
+    socket: LaminarSocket,
+    local_addr: SocketAddr,
+}
+
+impl LaminarTransport {
+    pub fn new(socket: LaminarSocket) -> Result<Self, Error> {
+        let local_addr = socket.local_addr()?;
+        Ok(Self { socket, local_addr })
+    }
+}
+
+impl Transport for LaminarTransport {
+    fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    fn build(
+        self: Box<Self>,
+        world: &mut World,
+        builder: &mut DispatcherBuilder<'_, '_>,
+    ) -> Result<(), Error> {
+        LaminarNetworkBundle::new(Some(self.socket)).build(world, builder)
+    }
+}
+
+enum LoopbackDatagram {
+    Message(SocketAddr, Bytes),
+    Disconnect(SocketAddr),
+}
+
+/// Connects worlds running in the same process. Datagrams are delivered without losses
+/// and in the order they were sent, every time the receiving world runs its dispatcher.
+#[derive(Clone, Default)]
+pub struct LoopbackNetwork {
+    inboxes: Arc<Mutex<HashMap<SocketAddr, VecDeque<LoopbackDatagram>>>>,
+}
+
+impl LoopbackNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an address, datagrams sent to addresses that aren't registered are dropped.
+    pub fn transport(&self, addr: SocketAddr) -> LoopbackTransport {
+        self.inboxes
+            .lock()
+            .expect("Expected to lock loopback inboxes")
+            .insert(addr, VecDeque::new());
+        LoopbackTransport {
+            network: self.clone(),
+            addr,
+        }
+    }
+
+    /// Unregisters an address, its peers receive `NetworkSimulationEvent::Disconnect`.
+    pub fn disconnect(&self, addr: SocketAddr) {
+        let mut inboxes = self
+            .inboxes
+            .lock()
+            .expect("Expected to lock loopback inboxes");
+        inboxes.remove(&addr);
+        for inbox in inboxes.values_mut() {
+            inbox.push_back(LoopbackDatagram::Disconnect(addr));
+        }
+    }
+
+    fn send(&self, from: SocketAddr, to: SocketAddr, payload: Bytes) {
+        let mut inboxes = self
+            .inboxes
+            .lock()
+            .expect("Expected to lock loopback inboxes");
+        if let Some(inbox) = inboxes.get_mut(&to) {
+            inbox.push_back(LoopbackDatagram::Message(from, payload));
+        } else {
+            log::trace!("Dropping a loopback datagram to an unknown address {}", to);
+        }
+    }
+
+    fn receive(&self, addr: SocketAddr) -> VecDeque<LoopbackDatagram> {
+        self.inboxes
+            .lock()
+            .expect("Expected to lock loopback inboxes")
+            .get_mut(&addr)
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+}
+
+pub struct LoopbackTransport {
+    network: LoopbackNetwork,
+    addr: SocketAddr,
+}
+
+impl Transport for LoopbackTransport {
+    fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn build(
+        self: Box<Self>,
+        _world: &mut World,
+        builder: &mut DispatcherBuilder<'_, '_>,
+    ) -> Result<(), Error> {
+        builder.add(
+            LoopbackTransportSystem {
+                network: self.network,
+                addr: self.addr,
+                peers: HashSet::new(),
+            },
+            "loopback_transport_system",
+            &[],
+        );
+        Ok(())
+    }
+}
+
+/// Emits `Connect` on the first datagram from a peer, like laminar does.
+struct LoopbackTransportSystem {
+    network: LoopbackNetwork,
+    addr: SocketAddr,
+    peers: HashSet<SocketAddr>,
+}
+
+impl<'s> System<'s> for LoopbackTransportSystem {
+    type SystemData = (
+        Write<'s, TransportResource>,
+        Write<'s, EventChannel<NetworkSimulationEvent>>,
+    );
+
+    fn run(&mut self, (mut transport, mut network_events): Self::SystemData) {
+        for message in transport.drain_messages_to_send(|_| true) {
+            self.network
+                .send(self.addr, message.destination, message.payload);
+        }
+
+        for datagram in self.network.receive(self.addr) {
+            match datagram {
+                LoopbackDatagram::Message(from, payload) => {
+                    if self.peers.insert(from) {
+                        network_events.single_write(NetworkSimulationEvent::Connect(from));
+                    }
+                    network_events.single_write(NetworkSimulationEvent::Message(from, payload));
+                }
+                LoopbackDatagram::Disconnect(from) => {
+                    if self.peers.remove(&from) {
+                        network_events.single_write(NetworkSimulationEvent::Disconnect(from));
+                    }
+                }
+            }
+        }
+    }
+}
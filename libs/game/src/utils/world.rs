@@ -2,7 +2,7 @@ use amethyst::ecs::{
     storage::{GenericReadStorage, MaskedStorage, Storage},
     Entities, Entity, Join,
 };
-use rand::{self, Rng};
+use rand::Rng;
 
 use std::ops::Deref;
 
@@ -75,8 +75,10 @@ pub fn find_first_hit_monster<
         .map(|result| result.1)
 }
 
-pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
-    let mut rng = rand::thread_rng();
+pub fn random_scene_position<R: Rng + ?Sized>(
+    game_scene: &GameLevelState,
+    rng: &mut R,
+) -> Vector2 {
     Vector2::new(
         rng.gen_range(
             -game_scene.dimensions_half_size().x,
@@ -89,11 +91,11 @@ pub fn random_scene_position(game_scene: &GameLevelState) -> Vector2 {
     )
 }
 
-pub fn random_spawn_position(game_level_state: &GameLevelState) -> Vector2 {
-    let mut rng = rand::thread_rng();
-
-    let (side_start, side_end, _) = spawning_side(rand::random(), &game_level_state);
+pub fn random_spawn_position(game_level_state: &mut GameLevelState) -> Vector2 {
+    let side = game_level_state.spawn_rng.gen();
+    let (side_start, side_end, _) = spawning_side(side, &game_level_state);
     let d = side_end - side_start;
+    let rng = &mut game_level_state.spawn_rng;
     let random_displacement = Vector2::new(
         if d.x == 0.0 {
             0.0
@@ -0,0 +1,40 @@
+use amethyst::core::Time;
+
+use std::time::Duration;
+
+/// Replaces the amethyst application loop in driving `Time`: each step advances the clock by
+/// exactly one fixed update and increments the frame number.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedStepClock {
+    step: Duration,
+}
+
+impl FixedStepClock {
+    pub fn new(step: Duration) -> Self {
+        Self { step }
+    }
+
+    pub fn from_frame_rate(frames_per_second: u32) -> Self {
+        Self::new(Duration::from_secs(1) / frames_per_second)
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn init(&self, time: &mut Time) {
+        time.set_fixed_time(self.step);
+        time.set_delta_time(self.step);
+    }
+
+    pub fn advance(&self, time: &mut Time) {
+        time.set_delta_time(self.step);
+        time.increment_frame_number();
+    }
+}
+
+impl Default for FixedStepClock {
+    fn default() -> Self {
+        Self::from_frame_rate(60)
+    }
+}
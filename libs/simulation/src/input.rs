@@ -0,0 +1,60 @@
+use amethyst::ecs::{Entity, System, WriteExpect, WriteStorage};
+
+use std::collections::BTreeMap;
+
+use gv_core::ecs::{components::ClientPlayerActions, system_data::time::GameTimeService};
+
+/// Player actions scheduled by frame numbers.
+#[derive(Default)]
+pub struct ScriptedActions {
+    actions: BTreeMap<u64, Vec<(Entity, ClientPlayerActions)>>,
+}
+
+impl ScriptedActions {
+    pub fn add(&mut self, frame_number: u64, player: Entity, actions: ClientPlayerActions) {
+        self.actions
+            .entry(frame_number)
+            .or_insert_with(Vec::new)
+            .push((player, actions));
+    }
+
+    /// Removes and returns every action scheduled up to `frame_number` inclusively.
+    pub fn drain_until(&mut self, frame_number: u64) -> Vec<(Entity, ClientPlayerActions)> {
+        let later_actions = self.actions.split_off(&(frame_number + 1));
+        let actions = std::mem::replace(&mut self.actions, later_actions);
+        actions.into_iter().flat_map(|(_, actions)| actions).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Writes scripted actions to `ClientPlayerActions`, the same way `InputSystem` does on client.
+pub struct ScriptedInputSystem;
+
+impl<'s> System<'s> for ScriptedInputSystem {
+    type SystemData = (
+        GameTimeService<'s>,
+        WriteExpect<'s, ScriptedActions>,
+        WriteStorage<'s, ClientPlayerActions>,
+    );
+
+    fn run(
+        &mut self,
+        (game_time_service, mut scripted_actions, mut client_player_actions): Self::SystemData,
+    ) {
+        let game_frame_number = game_time_service.game_frame_number();
+        for (player, actions) in scripted_actions.drain_until(game_frame_number) {
+            log::trace!(
+                "Applying scripted actions for {:?} (frame {}): {:?}",
+                player,
+                game_frame_number,
+                actions
+            );
+            client_player_actions
+                .insert(player, actions)
+                .expect("Expected to insert a ClientPlayerActions component");
+        }
+    }
+}
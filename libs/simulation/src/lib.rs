@@ -0,0 +1,201 @@
+//! A headless harness for running the game logic systems without a renderer or a socket.
+//!
+//! The simulation is driven by `FixedStepClock` instead of the amethyst application loop,
+//! so every run with the same scripted actions walks through the same sequence of frames.
+
+#![allow(clippy::type_complexity)]
+
+mod clock;
+mod input;
+
+pub use self::{
+    clock::FixedStepClock,
+    input::{ScriptedActions, ScriptedInputSystem},
+};
+
+use amethyst::{
+    core::Time,
+    ecs::{prelude::WorldExt, Entity, Join, World, WriteStorage},
+    error::Error,
+    prelude::{DataInit, GameData, GameDataBuilder},
+    shred::SystemData,
+};
+
+use std::path::Path;
+
+use gv_core::{
+    ecs::{
+        components::{ClientPlayerActions, Dead, Monster, Player, WorldPosition},
+        resources::{
+            world::FramedUpdates, GameEngineState, GameLevelState, GameRng, GameRngs, GameTime,
+            NewGameEngineState,
+        },
+        system_data::time::GameTimeService,
+    },
+    progression::PlayerStats,
+};
+use gv_game::{
+    build_game_logic_systems,
+    ecs::{
+        factories::PlayerFactory,
+        resources::{Campaign, GameMode, MonsterDefinitions, SpellDefinitions, WaveScript},
+        systems::{AggregatedOutcomingUpdates, ClientFrameUpdate, FrameUpdate},
+    },
+    utils::entities::is_dead,
+};
+
+/// Tests may run from any working directory, so we can't rely on the relative path LoadingState
+/// uses.
+const MONSTER_DEFINITIONS_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../../resources/monsters.ron");
+const SPELL_DEFINITIONS_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../../resources/spells.ron");
+const SURVIVAL_WAVE_SCRIPT_PATH: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../../resources/waves/survival.ron");
+
+pub struct Simulation<'a, 'b> {
+    world: World,
+    game_data: GameData<'a, 'b>,
+    clock: FixedStepClock,
+}
+
+impl<'a, 'b> Simulation<'a, 'b> {
+    /// Builds a world with the same resources `LoadingState` and `PlayingState` would insert,
+    /// leaving the game in the `Playing` state at frame 0. Runs with the same `seed` produce
+    /// the same simulation.
+    pub fn new(clock: FixedStepClock, seed: u64) -> Result<Self, Error> {
+        let mut world = World::new();
+
+        let mut time = Time::default();
+        clock.init(&mut time);
+        world.insert(time);
+
+        let monster_definitions = MonsterDefinitions::load(Path::new(MONSTER_DEFINITIONS_PATH))?;
+        world.insert(WaveScript::load(
+            Path::new(SURVIVAL_WAVE_SCRIPT_PATH),
+            &monster_definitions,
+        )?);
+        // Campaign levels reference wave scripts relative to the repository root, the simulation
+        // plays survival only, so we just need the resource to be there.
+        world.insert(Campaign { levels: Vec::new() });
+        world.insert(GameMode::Survival);
+        world.insert(monster_definitions);
+        world.insert(SpellDefinitions::load(Path::new(SPELL_DEFINITIONS_PATH))?);
+        let mut game_rng = GameRng::new(seed);
+        world.insert(GameLevelState::new(game_rng.split()));
+        world.insert(GameRngs::split_from(&mut game_rng));
+        world.insert(GameTime::default());
+        world.insert(GameEngineState::Playing);
+        world.insert(NewGameEngineState(GameEngineState::Playing));
+        world.insert(FramedUpdates::<ClientFrameUpdate>::default());
+        world.insert(FramedUpdates::<FrameUpdate>::default());
+        world.insert(AggregatedOutcomingUpdates::default());
+        world.insert(ScriptedActions::default());
+
+        // The scripted input takes the place of the network system, as this is where player
+        // actions come from on a server.
+        let game_data_builder =
+            GameDataBuilder::default().with(ScriptedInputSystem, "game_network_system", &[]);
+        let game_data_builder = build_game_logic_systems(game_data_builder, &mut world, true)?;
+        let game_data = game_data_builder.build(&mut world);
+
+        GameTimeService::fetch(&world).set_game_start_time();
+
+        Ok(Self {
+            world,
+            game_data,
+            clock,
+        })
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn game_frame_number(&self) -> u64 {
+        GameTimeService::fetch(&self.world).game_frame_number()
+    }
+
+    /// Creates a single player controllable with `ClientPlayerActions`.
+    pub fn spawn_player(&mut self, stats: PlayerStats) -> Entity {
+        let player = self
+            .world
+            .exec(|mut player_factory: PlayerFactory| player_factory.create(stats));
+        self.world
+            .exec(|mut client_player_actions: WriteStorage<ClientPlayerActions>| {
+                client_player_actions
+                    .insert(player, ClientPlayerActions::default())
+                    .expect("Expected to insert a ClientPlayerActions component");
+            });
+        player
+    }
+
+    /// Schedules actions that will be applied to a player starting from `frame_number`.
+    pub fn script_actions(
+        &mut self,
+        frame_number: u64,
+        player: Entity,
+        actions: ClientPlayerActions,
+    ) {
+        self.world
+            .write_resource::<ScriptedActions>()
+            .add(frame_number, player, actions);
+    }
+
+    pub fn run_frame(&mut self) {
+        self.game_data.update(&self.world);
+        self.world.maintain();
+        self.clock.advance(&mut self.world.write_resource::<Time>());
+    }
+
+    pub fn run_frames(&mut self, frames_count: u64) {
+        for _ in 0..frames_count {
+            self.run_frame();
+        }
+    }
+
+    /// Makes the next frame re-simulate the last `frames_count` frames, the same way receiving
+    /// an update for an older frame does.
+    pub fn roll_back(&mut self, frames_count: u64) {
+        let frame_number = self.game_frame_number().saturating_sub(frames_count);
+        let mut framed_updates = self.world.write_resource::<FramedUpdates<FrameUpdate>>();
+        framed_updates.oldest_updated_frame = framed_updates.oldest_updated_frame.min(frame_number);
+    }
+
+    pub fn monsters_killed(&self) -> u32 {
+        self.world.read_resource::<GameLevelState>().monsters_killed
+    }
+
+    pub fn world_position(&self, entity: Entity) -> Option<WorldPosition> {
+        self.world
+            .read_storage::<WorldPosition>()
+            .get(entity)
+            .cloned()
+    }
+
+    pub fn player_health(&self, player: Entity) -> Option<f32> {
+        self.world
+            .read_storage::<Player>()
+            .get(player)
+            .map(|player| player.health)
+    }
+
+    pub fn is_dead(&self, entity: Entity) -> bool {
+        is_dead(
+            entity,
+            &self.world.read_storage::<Dead>(),
+            self.game_frame_number(),
+        )
+    }
+
+    pub fn monsters(&self) -> Vec<Entity> {
+        (&self.world.entities(), &self.world.read_storage::<Monster>())
+            .join()
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+}
@@ -0,0 +1,199 @@
+use gv_core::{
+    actions::player::{PlayerCastAction, PlayerLookAction, PlayerWalkAction, SpellId},
+    ecs::components::ClientPlayerActions,
+    math::{Vector2, ZeroVector},
+    progression::PlayerStats,
+};
+use gv_simulation::{FixedStepClock, Simulation};
+
+const SEED: u64 = 42;
+const FRAMES_PER_SECOND: u64 = 60;
+
+fn walk(direction: Vector2) -> ClientPlayerActions {
+    ClientPlayerActions {
+        walk_action: PlayerWalkAction::Walk { direction },
+        ..ClientPlayerActions::default()
+    }
+}
+
+fn cast_missile(cast_position: Vector2, target_position: Vector2) -> ClientPlayerActions {
+    ClientPlayerActions {
+        look_action: PlayerLookAction {
+            direction: target_position - cast_position,
+        },
+        cast_action: Some(PlayerCastAction {
+            spell: SpellId::Missile,
+            cast_position,
+            target_position,
+        }),
+        ..ClientPlayerActions::default()
+    }
+}
+
+#[test]
+fn walking_moves_a_player() {
+    let mut simulation = Simulation::new(FixedStepClock::default(), SEED).unwrap();
+    let player = simulation.spawn_player(PlayerStats::default());
+    simulation.script_actions(1, player, walk(Vector2::new(1.0, 0.0)));
+    simulation.script_actions(FRAMES_PER_SECOND, player, ClientPlayerActions::default());
+
+    simulation.run_frames(FRAMES_PER_SECOND / 2);
+    let walking_position = simulation.world_position(player).unwrap();
+    assert!(walking_position.x > 0.0);
+    assert!(walking_position.y.abs() < 0.001);
+
+    simulation.run_frames(FRAMES_PER_SECOND);
+    let stopped_position = simulation.world_position(player).unwrap();
+    assert!(stopped_position.x > walking_position.x);
+    simulation.run_frames(FRAMES_PER_SECOND / 2);
+    let position = simulation.world_position(player).unwrap();
+    assert!((position.x - stopped_position.x).abs() < 0.001);
+}
+
+#[test]
+fn an_idle_player_gets_killed_by_monsters() {
+    let mut simulation = Simulation::new(FixedStepClock::default(), SEED).unwrap();
+    let player = simulation.spawn_player(PlayerStats::default());
+    let max_health = simulation.player_health(player).unwrap();
+    assert!(max_health > 0.0);
+    assert!(!simulation.is_dead(player));
+
+    let mut was_damaged = false;
+    for _ in 0..FRAMES_PER_SECOND * 180 {
+        simulation.run_frame();
+        let health = simulation.player_health(player).unwrap();
+        was_damaged |= health < max_health;
+        if simulation.is_dead(player) {
+            break;
+        }
+    }
+
+    assert!(!simulation.monsters().is_empty());
+    assert!(was_damaged);
+    assert!(simulation.is_dead(player));
+    assert_eq!(simulation.player_health(player), Some(0.0));
+}
+
+#[test]
+fn casting_missiles_doesnt_hurt_the_caster() {
+    let mut simulation = Simulation::new(FixedStepClock::default(), SEED).unwrap();
+    let player = simulation.spawn_player(PlayerStats::default());
+    let max_health = simulation.player_health(player).unwrap();
+    // The lone monster of the survival wave script spawns above the player.
+    simulation.script_actions(
+        20,
+        player,
+        cast_missile(Vector2::zero(), Vector2::new(0.0, 300.0)),
+    );
+    simulation.script_actions(21, player, ClientPlayerActions::default());
+
+    simulation.run_frames(40);
+
+    assert_eq!(simulation.player_health(player), Some(max_health));
+    assert!(!simulation.is_dead(player));
+}
+
+#[test]
+fn rollbacks_dont_count_kills_twice() {
+    let mut simulation = Simulation::new(FixedStepClock::default(), SEED).unwrap();
+    let player = simulation.spawn_player(PlayerStats::default());
+
+    // Keep shooting at the closest monster until it dies.
+    for _ in 0..FRAMES_PER_SECOND * 10 {
+        if simulation.monsters_killed() > 0 {
+            break;
+        }
+        let player_position = *simulation.world_position(player).unwrap();
+        let closest_monster_position = simulation
+            .monsters()
+            .into_iter()
+            .filter(|monster| !simulation.is_dead(*monster))
+            .filter_map(|monster| simulation.world_position(monster))
+            .map(|position| *position)
+            .min_by(|a, b| {
+                let a_distance = (a - player_position).norm_squared();
+                let b_distance = (b - player_position).norm_squared();
+                a_distance.partial_cmp(&b_distance).unwrap()
+            });
+        let actions = closest_monster_position
+            .map_or_else(ClientPlayerActions::default, |target| {
+                cast_missile(player_position, target)
+            });
+        simulation.script_actions(simulation.game_frame_number() + 1, player, actions);
+        simulation.run_frame();
+    }
+    assert_eq!(simulation.monsters_killed(), 1);
+    let killed_monster = simulation
+        .monsters()
+        .into_iter()
+        .find(|monster| simulation.is_dead(*monster))
+        .unwrap();
+
+    // Missiles are saved with the world state, so re-simulating the frames kills the monster
+    // with the same missile again.
+    simulation.script_actions(
+        simulation.game_frame_number() + 1,
+        player,
+        ClientPlayerActions::default(),
+    );
+    simulation.roll_back(5);
+    simulation.run_frames(2);
+
+    assert!(simulation.is_dead(killed_monster));
+    assert_eq!(simulation.monsters_killed(), 1);
+}
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    player_position: (f32, f32),
+    player_health: f32,
+    is_player_dead: bool,
+    monster_positions: Vec<(f32, f32)>,
+}
+
+fn run_scripted_game(seed: u64) -> Outcome {
+    let mut simulation = Simulation::new(FixedStepClock::default(), seed).unwrap();
+    let player = simulation.spawn_player(PlayerStats::default());
+    simulation.script_actions(1, player, walk(Vector2::new(-1.0, -1.0)));
+    simulation.script_actions(90, player, ClientPlayerActions::default());
+    simulation.run_frames(95);
+
+    let cast_position = *simulation.world_position(player).unwrap();
+    simulation.script_actions(
+        96,
+        player,
+        cast_missile(cast_position, Vector2::new(100.0, 100.0)),
+    );
+    simulation.script_actions(97, player, walk(Vector2::new(1.0, 0.0)));
+    simulation.script_actions(150, player, ClientPlayerActions::default());
+    simulation.run_frames(60);
+
+    let cast_position = *simulation.world_position(player).unwrap();
+    simulation.script_actions(
+        160,
+        player,
+        cast_missile(cast_position, Vector2::new(-100.0, 200.0)),
+    );
+    simulation.script_actions(161, player, ClientPlayerActions::default());
+    simulation.run_frames(FRAMES_PER_SECOND * 10);
+
+    let player_position = simulation.world_position(player).unwrap();
+    Outcome {
+        player_position: (player_position.x, player_position.y),
+        player_health: simulation.player_health(player).unwrap(),
+        is_player_dead: simulation.is_dead(player),
+        monster_positions: simulation
+            .monsters()
+            .into_iter()
+            .filter_map(|monster| simulation.world_position(monster))
+            .map(|position| (position.x, position.y))
+            .collect(),
+    }
+}
+
+#[test]
+fn simulations_with_the_same_seed_are_deterministic() {
+    let outcome = run_scripted_game(SEED);
+    assert!(!outcome.monster_positions.is_empty());
+    assert_eq!(outcome, run_scripted_game(SEED));
+}
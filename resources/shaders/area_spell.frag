@@ -0,0 +1,29 @@
+#version 450
+
+layout(location = 0) in VertexData {
+    vec2 uv;
+    float expansion;
+    float ttl;
+} vertex;
+layout(location = 0) out vec4 out_color;
+
+const vec3 ring_color = vec3(1.0, 0.45, 0.05);
+const vec3 core_color = vec3(1.0, 0.85, 0.4);
+
+const float ring_width = 0.12;
+const float fill_alpha = 0.15;
+
+void main() {
+    // Distance from the center, 1.0 is the spell radius.
+    float r = length(vertex.uv - vec2(0.5)) * 2.0;
+    float front = vertex.expansion;
+    if (r > front) {
+        discard;
+    }
+
+    float ring = smoothstep(front - ring_width, front, r);
+    float fill = fill_alpha * r / max(front, 0.001);
+    vec3 color = mix(ring_color, core_color, ring * 0.5);
+    float alpha = max(ring, fill) * vertex.ttl;
+    out_color = vec4(color, alpha);
+}
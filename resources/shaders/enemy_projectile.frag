@@ -0,0 +1,21 @@
+#version 450
+
+layout(location = 0) in VertexData {
+    vec2 uv;
+    float seconds_since_spawn;
+    float ttl;
+} vertex;
+layout(location = 0) out vec4 out_color;
+
+const vec3 core_color = vec3(0.85, 1.0, 0.3);
+const vec3 glow_color = vec3(0.3, 0.8, 0.1);
+
+void main() {
+    // Distance from the center, 1.0 is the quad edge.
+    float r = length(vertex.uv - vec2(0.5)) * 2.0;
+    float pulse = 0.85 + 0.15 * sin(vertex.seconds_since_spawn * 20.0);
+    float core = 1.0 - smoothstep(0.0, 0.35 * pulse, r);
+    float glow = 1.0 - smoothstep(0.2, 1.0, r);
+    vec3 color = mix(glow_color, core_color, core);
+    out_color = vec4(color, max(core, glow * 0.6) * vertex.ttl);
+}